@@ -0,0 +1,59 @@
+// Criterion benchmarks for map generation, FOV and pathfinding, run against the game library
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use deepdive::biome::BiomeType;
+use deepdive::components::{EllipseMask, FovConfig, GlobalRng, TileType};
+use deepdive::map::GameMap;
+use deepdive::{fov, player, simulation};
+
+// Fixed seed so results stay comparable run-to-run; matches the seeds used with simulation::generate_level
+const BENCH_SEED: u64 = 42;
+
+fn bench_generation(c: &mut Criterion) {
+    let ellipse_mask = EllipseMask::new(80, 50);
+    let mut group = c.benchmark_group("generate_with_biome");
+
+    for &biome in BiomeType::all() {
+        group.bench_function(biome.display_name(), |b| {
+            b.iter(|| {
+                let mut rng = GlobalRng::with_seed(BENCH_SEED);
+                let mut map = GameMap::new(80, 50);
+                map.generate_with_biome(biome, 1, &mut rng, &ellipse_mask);
+                black_box(map)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_fov(c: &mut Criterion) {
+    // Full recompute from the map center; visible_tiles_from skips the LOS cache like a cold calculate_fov
+    let map = simulation::generate_level(BiomeType::Caverns, 1, BENCH_SEED);
+    let fov_config = FovConfig::default();
+    let origin = (map.width / 2, map.height / 2);
+
+    c.bench_function("fov_full_recompute", |b| {
+        b.iter(|| black_box(fov::visible_tiles_from(&map, black_box(origin), &fov_config)))
+    });
+}
+
+fn bench_pathfinding(c: &mut Criterion) {
+    // Open map split by a wall with a single gap at the far end, forcing a long detour
+    let mut map = GameMap::new(80, 50);
+    for y in 0..map.height {
+        for x in 0..map.width {
+            map.set(x, y, TileType::Floor);
+        }
+    }
+    for y in 0..map.height - 1 {
+        map.set(map.width / 2, y, TileType::Wall);
+    }
+
+    c.bench_function("find_path_wall_barrier", |b| {
+        b.iter(|| black_box(player::find_path(black_box((2, 2)), black_box((77, 2)), &map, BiomeType::Caverns)))
+    });
+}
+
+criterion_group!(benches, bench_generation, bench_fov, bench_pathfinding);
+criterion_main!(benches);
@@ -28,73 +28,138 @@ pub struct SpritesJson {
     pub sprites: HashMap<String, SpriteConfig>,
 }
 
+// Tile sheet layout loaded from a RON file so the art can be swapped without code changes
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpriteDatabaseConfig {
+    pub sheet_columns: u32,
+    // Sprite name -> (x, y) position on the sheet
+    pub sprites: HashMap<String, (u32, u32)>,
+    // Category -> sprite names. A "<biome name>/<floors|walls|water>" category replaces
+    // that biome's built-in sprites, e.g. "Caverns/floors"
+    pub categories: HashMap<String, Vec<String>>,
+}
+
+impl Default for SpriteDatabaseConfig {
+    fn default() -> Self {
+        let sprites = [
+            // Wall sprites
+            ("dirt_wall_top", (0, 0)),
+            ("dirt_wall_side", (1, 0)),
+            ("rough_stone_wall_top", (0, 1)),
+            ("rough_stone_wall_side", (1, 1)),
+            // Floor sprites
+            ("floor_stone1", (1, 6)),
+            ("floor_stone2", (2, 6)),
+            ("floor_stone3", (3, 6)),
+            ("dirt1", (1, 8)),
+            ("dirt2", (2, 8)),
+            ("dirt3", (3, 8)),
+        ];
+        let categories = [
+            ("wall_top", vec!["dirt_wall_top", "rough_stone_wall_top"]),
+            ("wall_side", vec!["dirt_wall_side", "rough_stone_wall_side"]),
+            ("floors", vec!["floor_stone1", "floor_stone2", "floor_stone3", "dirt1", "dirt2", "dirt3"]),
+        ];
+
+        Self {
+            sheet_columns: DEFAULT_SHEET_COLUMNS,
+            sprites: sprites.into_iter().map(|(name, pos)| (name.to_string(), pos)).collect(),
+            categories: categories.into_iter()
+                .map(|(category, names)| (category.to_string(), names.into_iter().map(str::to_string).collect()))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct SpriteDatabase {
-    // Maps sprite names to their texture indices
-    pub sprite_indices: HashMap<String, u32>,
-    // Maps categories to lists of available sprite names
-    pub categories: HashMap<String, Vec<String>>,
+    // Maps categories to the sheet positions of their sprites
+    pub categories: HashMap<String, Vec<(u32, u32)>>,
+    // Per-biome overrides: biome name -> role (floors, walls, water) -> sheet positions
+    pub biome_categories: HashMap<String, HashMap<String, Vec<(u32, u32)>>>,
+    // Tiles per row on the sheet, used to turn positions into indices
+    pub sheet_columns: u32,
 }
 
 impl SpriteDatabase {
+    // Built-in layout for the bundled tiles.png
     pub fn new() -> Self {
-        let mut sprite_indices = HashMap::new();
-        let mut categories = HashMap::new();
-        
-        // Define some default sprites based on sprites.json structure
-        // Wall sprites
-        sprite_indices.insert("dirt_wall_top".to_string(), sprite_position_to_index(0, 0));
-        sprite_indices.insert("dirt_wall_side".to_string(), sprite_position_to_index(1, 0));
-        sprite_indices.insert("rough_stone_wall_top".to_string(), sprite_position_to_index(0, 1));
-        sprite_indices.insert("rough_stone_wall_side".to_string(), sprite_position_to_index(1, 1));
-        
-        // Floor sprites
-        sprite_indices.insert("floor_stone1".to_string(), sprite_position_to_index(1, 6));
-        sprite_indices.insert("floor_stone2".to_string(), sprite_position_to_index(2, 6));
-        sprite_indices.insert("floor_stone3".to_string(), sprite_position_to_index(3, 6));
-        sprite_indices.insert("dirt1".to_string(), sprite_position_to_index(1, 8));
-        sprite_indices.insert("dirt2".to_string(), sprite_position_to_index(2, 8));
-        sprite_indices.insert("dirt3".to_string(), sprite_position_to_index(3, 8));
-        
-        // Organize by categories
-        categories.insert("wall_top".to_string(), vec![
-            "dirt_wall_top".to_string(),
-            "rough_stone_wall_top".to_string(),
-        ]);
-        categories.insert("wall_side".to_string(), vec![
-            "dirt_wall_side".to_string(),
-            "rough_stone_wall_side".to_string(),
-        ]);
-        categories.insert("floors".to_string(), vec![
-            "floor_stone1".to_string(),
-            "floor_stone2".to_string(),
-            "floor_stone3".to_string(),
-            "dirt1".to_string(),
-            "dirt2".to_string(),
-            "dirt3".to_string(),
-        ]);
-        
-        Self { sprite_indices, categories }
+        Self::from_config(SpriteDatabaseConfig::default())
     }
-    
-    pub fn get_sprite_index(&self, sprite_name: &str) -> Option<u32> {
-        self.sprite_indices.get(sprite_name).copied()
+
+    pub fn from_config(config: SpriteDatabaseConfig) -> Self {
+        let mut categories = HashMap::new();
+        let mut biome_categories: HashMap<String, HashMap<String, Vec<(u32, u32)>>> = HashMap::new();
+        for (category, names) in config.categories {
+            let positions: Vec<(u32, u32)> = names.iter()
+                .filter_map(|name| {
+                    let position = config.sprites.get(name).copied();
+                    if position.is_none() {
+                        println!("Sprite database category '{}' names unknown sprite '{}'", category, name);
+                    }
+                    position
+                })
+                .collect();
+            match category.split_once('/') {
+                Some((biome, role)) => { biome_categories.entry(biome.to_string()).or_default().insert(role.to_string(), positions); }
+                None => { categories.insert(category, positions); }
+            }
+        }
+
+        Self { categories, biome_categories, sheet_columns: config.sheet_columns.max(1) }
     }
-    
-    pub fn get_random_sprite_from_category(&self, category: &str, rng: &mut impl Rng) -> Option<u32> {
-        if let Some(sprites) = self.categories.get(category) {
-            if !sprites.is_empty() {
-                let sprite_name = &sprites[rng.random_range(0..sprites.len())];
-                return self.get_sprite_index(sprite_name);
+
+    // Load a custom sheet layout; unreadable files use the built-in one, and categories
+    // the file leaves out keep their default sprites
+    pub fn load_or_default(path: &str) -> Self {
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(contents) => match ron::from_str::<SpriteDatabaseConfig>(&contents) {
+                Ok(config) => config,
+                Err(e) => {
+                    println!("Failed to parse sprite database {}: {} - using built-in sprites", path, e);
+                    return Self::new();
+                }
+            },
+            Err(_) => {
+                println!("No sprite database at {} - using built-in sprites", path);
+                return Self::new();
             }
+        };
+
+        let defaults = SpriteDatabaseConfig::default();
+        for (category, names) in defaults.categories {
+            if config.categories.contains_key(&category) {
+                continue;
+            }
+            println!("Sprite database is missing category '{}', using default sprites", category);
+            // Default coordinates are on the bundled sheet, so keep them unless the file overrides a name
+            for name in &names {
+                if let Some(&pos) = defaults.sprites.get(name) {
+                    config.sprites.entry(name.clone()).or_insert(pos);
+                }
+            }
+            config.categories.insert(category, names);
         }
-        None
+
+        Self::from_config(config)
     }
-}
 
-// Helper function to convert 2D sprite position to 1D texture index
-// The sprite sheet is arranged in a grid, so index = y * sheet_width + x
-pub fn sprite_position_to_index(x: u32, y: u32) -> u32 {
-    // Based on tiles.png dimensions (544x832) with 32x32 tiles: 17 tiles wide, 26 tiles high
-    y * 17 + x
+    pub fn position_to_index(&self, x: u32, y: u32) -> u32 {
+        y * self.sheet_columns + x
+    }
+    
+    pub fn get_random_sprite_from_category(&self, category: &str, rng: &mut impl Rng) -> Option<(u32, u32)> {
+        let sprites = self.categories.get(category).filter(|sprites| !sprites.is_empty())?;
+        Some(sprites[rng.random_range(0..sprites.len())])
+    }
+
+    // A biome's override for one role, if the loaded sheet has a non-empty one
+    pub fn biome_category(&self, biome: &str, role: &str) -> Option<&[(u32, u32)]> {
+        self.biome_categories.get(biome)?.get(role)
+            .filter(|sprites| !sprites.is_empty())
+            .map(Vec::as_slice)
+    }
 }
+
+// Based on tiles.png dimensions (544x832) with 32x32 tiles: 17 tiles wide, 26 tiles high
+pub const DEFAULT_SHEET_COLUMNS: u32 = 17;
@@ -1,7 +1,11 @@
+use bevy::color::Color;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+use crate::components::TileType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, bevy::reflect::Reflect)]
 pub enum BiomeType {
     Caverns,
     Underglade,
@@ -14,14 +18,82 @@ pub enum BiomeType {
     StygianPool,
 }
 
+/// Static rendering and generation data for one biome. Get one with `BiomeConfig::of`;
+/// nothing here needs a map, so tools can inspect any biome directly
 #[derive(Clone, Debug)]
 pub struct BiomeConfig {
+    /// Display name shown in the depth indicator and logs
     pub name: &'static str,
     pub description: &'static str,
+    /// Tileset coordinates (column, row) that floor tiles pick from uniformly
     pub allowed_floor_assets: Vec<(u32, u32)>,
+    /// Tileset coordinates for walls without an autotile match: column 0 are wall tops, the rest sides
     pub allowed_wall_assets: Vec<(u32, u32)>,
+    /// Tileset coordinates water tiles pick from
     pub allowed_water_assets: Vec<(u32, u32)>,
-    pub allowed_stair_assets: Vec<(u32, u32)>,
+    /// Stair sprites for this biome; None uses StairSprites::DEFAULT
+    pub stair_sprites: Option<StairSprites>,
+    /// Line printed when the player arrives here by the down stairs
+    pub descent_flavor: Option<&'static str>,
+    /// FOV palette; None leaves tiles untinted
+    pub fov_tint: Option<FovTint>,
+    /// Chance a level gets a trapdoor dropping straight to the next level
+    pub trapdoor_chance: f64,
+    /// Cardinal wall-neighbor mask (N=1, E=2, S=4, W=8) -> wall sprite; unlisted masks use the top/side
+    /// split. Biomes still on the placeholder wall art have no table
+    pub wall_autotile: Vec<(u8, (u32, u32))>,
+    /// Movement cost of wading through water; None means water blocks movement
+    pub swim_cost: Option<u32>,
+    /// Weighted decorative floor bands, rolled before the uniform floor pick
+    pub floor_decoration: Vec<FloorDecoration>,
+}
+
+// Color FOV blends into tiles, and how strongly for remembered vs. in-view tiles (0 = untinted)
+#[derive(Clone, Copy, Debug)]
+pub struct FovTint {
+    pub color: Color,
+    pub seen_intensity: f32,
+    pub visible_intensity: f32,
+}
+
+impl FovTint {
+    pub const NONE: FovTint = FovTint { color: Color::WHITE, seen_intensity: 0.0, visible_intensity: 0.0 };
+}
+
+// Sprite coordinates of a biome's staircases
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StairSprites {
+    pub up: (u32, u32),
+    pub down: (u32, u32),
+}
+
+impl StairSprites {
+    // staircase_up and staircase_down, shared by every biome without its own art
+    pub const DEFAULT: StairSprites = StairSprites { up: (8, 16), down: (7, 16) };
+}
+
+// Every wall mask dressed from one material: walls open to the south show their side, the rest
+// their top. Outer corners (open to the south and to the east or west) take `corner` and fully
+// enclosed walls take `inner`
+fn wall_autotile_table(top: (u32, u32), side: (u32, u32), corner: (u32, u32), inner: (u32, u32)) -> Vec<(u8, (u32, u32))> {
+    (0..16u8)
+        .map(|mask| {
+            let sprite = match mask {
+                15 => inner,
+                _ if mask & 4 != 0 => top,
+                3 | 9 => corner, // N+E and N+W
+                _ => side,
+            };
+            (mask, sprite)
+        })
+        .collect()
+}
+
+// One band of the floor decoration roll: with probability `chance`, a floor uses one of `sprites`
+#[derive(Clone, Debug)]
+pub struct FloorDecoration {
+    pub chance: f32,
+    pub sprites: Vec<(u32, u32)>,
 }
 
 // Static biome configurations initialized once
@@ -31,7 +103,13 @@ static CAVERNS_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
     allowed_floor_assets: vec![(0,6), (1,6), (2,6), (3,6)],
     allowed_wall_assets: vec![(0,0), (1,0), (0,1), (1,1)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(7,16), (8,16)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: None,
+    trapdoor_chance: 0.0,
+    wall_autotile: wall_autotile_table((0,0), (1,0), (1,1), (2,0)), // Dirt, rough stone corners, inner_wall when enclosed
+    swim_cost: None,
+    floor_decoration: vec![],
 });
 
 static UNDERGLADE_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -44,7 +122,15 @@ static UNDERGLADE_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
     ],
     allowed_wall_assets: vec![(0,0), (1,0), (0,1), (1,1)], // Dirt and rough stone walls
     allowed_water_assets: vec![(0,6)], // Dark grey water/blank floor for water areas
-    allowed_stair_assets: vec![(7,16), (8,16)], // Standard staircase assets
+    stair_sprites: Some(StairSprites { up: (8, 16), down: (12, 16) }), // Descents are root-ringed pits
+    descent_flavor: Some("Roots close around the stairs as you climb down into the glade."),
+    fov_tint: Some(FovTint { color: Color::srgb(0.8, 1.2, 0.9), seen_intensity: 0.4, visible_intensity: 0.6 }), // Green tint for lush biome
+    trapdoor_chance: 0.0,
+    wall_autotile: wall_autotile_table((0,1), (1,1), (1,0), (2,0)), // Rough stone, dirt corners
+    swim_cost: None,
+    floor_decoration: vec![
+        FloorDecoration { chance: 0.15, sprites: vec![(1,14), (2,14), (3,14)] }, // Grass tufts
+    ],
 });
 
 static FUNGAL_DEEP_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -53,7 +139,13 @@ static FUNGAL_DEEP_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig
     allowed_floor_assets: vec![(4,0), (5,0), (4,1), (5,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: Some(FovTint { color: Color::srgb(0.9, 0.8, 1.3), seen_intensity: 0.4, visible_intensity: 0.6 }), // Purple tint for spores
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: None,
+    floor_decoration: vec![],
 });
 
 static CINDER_GAOL_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -65,7 +157,16 @@ static CINDER_GAOL_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig
     ],
     allowed_wall_assets: vec![(0,3), (1,3), (0,5), (1,5)], // Igneous and catacombs walls only
     allowed_water_assets: vec![], // No water in prison
-    allowed_stair_assets: vec![(7,16), (8,16)], // Standard staircase assets
+    stair_sprites: Some(StairSprites { up: (6, 16), down: (11, 16) }), // Cell grates up, soot chutes down
+    descent_flavor: Some("The steps are warm underfoot; the air below smells of ash."),
+    fov_tint: Some(FovTint { color: Color::srgb(1.3, 0.7, 0.7), seen_intensity: 0.4, visible_intensity: 0.6 }), // Red tint for fire/prison theme
+    trapdoor_chance: 0.5, // The old gaol floors give way without warning
+    wall_autotile: wall_autotile_table((0,3), (1,3), (1,5), (0,5)), // Igneous, catacomb corners and cores
+    swim_cost: None,
+    floor_decoration: vec![
+        FloorDecoration { chance: 0.35, sprites: vec![(0,15)] }, // Dark brown prison floor is most common
+        FloorDecoration { chance: 0.20, sprites: vec![(0,11), (1,11), (2,11), (3,11)] }, // Brimstone red
+    ], // Remaining 45% is a uniform pick, which is where the bones show up
 });
 
 static ABYSSAL_HOLD_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -74,7 +175,13 @@ static ABYSSAL_HOLD_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig
     allowed_floor_assets: vec![(8,0), (9,0), (8,1), (9,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: Some(FovTint { color: Color::srgb(0.7, 0.7, 1.2), seen_intensity: 0.4, visible_intensity: 0.6 }), // Blue tint for dark waters
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: Some(3), // Shallow pools can be swum at a penalty
+    floor_decoration: vec![],
 });
 
 static NETHER_GRANGE_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -83,7 +190,13 @@ static NETHER_GRANGE_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfi
     allowed_floor_assets: vec![(10,0), (11,0), (10,1), (11,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: Some(FovTint { color: Color::srgb(1.4, 0.6, 0.4), seen_intensity: 0.4, visible_intensity: 0.6 }), // Orange-red for hellish landscape
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: None,
+    floor_decoration: vec![],
 });
 
 static CHTHONIC_CRYPTS_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -92,7 +205,13 @@ static CHTHONIC_CRYPTS_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeCon
     allowed_floor_assets: vec![(12,0), (13,0), (12,1), (13,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: None,
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: None,
+    floor_decoration: vec![],
 });
 
 static HYPOGEAL_KNOT_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -101,7 +220,13 @@ static HYPOGEAL_KNOT_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfi
     allowed_floor_assets: vec![(14,0), (15,0), (14,1), (15,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: None,
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: None,
+    floor_decoration: vec![],
 });
 
 static STYGIAN_POOL_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig {
@@ -110,12 +235,19 @@ static STYGIAN_POOL_CONFIG: LazyLock<BiomeConfig> = LazyLock::new(|| BiomeConfig
     allowed_floor_assets: vec![(16,0), (17,0), (16,1), (17,1)],
     allowed_wall_assets: vec![(1,7), (2,7), (3,7)],
     allowed_water_assets: vec![(0,6), (1,6), (2,6), (3,6)],
-    allowed_stair_assets: vec![(1,8), (2,8), (3,8)],
+    stair_sprites: None,
+    descent_flavor: None,
+    fov_tint: Some(FovTint { color: Color::srgb(0.6, 0.8, 1.3), seen_intensity: 0.4, visible_intensity: 0.6 }), // Cyan tint for underground lake
+    trapdoor_chance: 0.0,
+    wall_autotile: vec![],
+    swim_cost: Some(3), // Shallow pools can be swum at a penalty
+    floor_decoration: vec![],
 });
 
-impl BiomeType {
-    pub fn get_config(&self) -> &'static BiomeConfig {
-        match self {
+impl BiomeConfig {
+    /// The config for `biome`; every other accessor goes through here
+    pub fn of(biome: BiomeType) -> &'static BiomeConfig {
+        match biome {
             BiomeType::Caverns => &CAVERNS_CONFIG,
             BiomeType::Underglade => &UNDERGLADE_CONFIG,
             BiomeType::FungalDeep => &FUNGAL_DEEP_CONFIG,
@@ -127,4 +259,188 @@ impl BiomeType {
             BiomeType::StygianPool => &STYGIAN_POOL_CONFIG,
         }
     }
-}
\ No newline at end of file
+
+    pub fn tint(&self) -> FovTint {
+        self.fov_tint.unwrap_or(FovTint::NONE)
+    }
+
+    pub fn stairs(&self) -> StairSprites {
+        self.stair_sprites.unwrap_or(StairSprites::DEFAULT)
+    }
+
+    // Decorative floor sprite for this tile, or None to fall back to a uniform floor pick. Only
+    // sprites in `floor_assets` are used, so an overridden floor list never gets foreign tiles
+    pub fn roll_floor_decoration(&self, floor_assets: &[(u32, u32)], rng: &mut impl Rng) -> Option<(u32, u32)> {
+        if self.floor_decoration.is_empty() {
+            return None;
+        }
+
+        let roll = rng.random::<f32>();
+        let mut threshold = 0.0;
+        for band in &self.floor_decoration {
+            threshold += band.chance;
+            if roll < threshold {
+                let sprites: Vec<_> = band.sprites.iter().filter(|sprite| floor_assets.contains(sprite)).collect();
+                if sprites.is_empty() {
+                    return None;
+                }
+                return Some(*sprites[rng.random_range(0..sprites.len())]);
+            }
+        }
+        None
+    }
+}
+
+impl BiomeType {
+    // Every biome in depth order
+    pub fn all() -> &'static [BiomeType] {
+        &[
+            BiomeType::Caverns,
+            BiomeType::Underglade,
+            BiomeType::FungalDeep,
+            BiomeType::CinderGaol,
+            BiomeType::AbyssalHold,
+            BiomeType::NetherGrange,
+            BiomeType::ChthronicCrypts,
+            BiomeType::HypogealKnot,
+            BiomeType::StygianPool,
+        ]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        self.get_config().name
+    }
+
+    // Biomes with finished asset sets; the rest still use placeholder configs
+    pub fn is_implemented(&self) -> bool {
+        matches!(self, BiomeType::Caverns | BiomeType::CinderGaol | BiomeType::Underglade)
+    }
+
+    pub fn get_config(&self) -> &'static BiomeConfig {
+        BiomeConfig::of(*self)
+    }
+}
+
+// Tile cost of stepping onto a tile in this biome; None if the tile can't be entered
+pub fn move_cost(tile: TileType, biome: BiomeType) -> Option<u32> {
+    match tile {
+        TileType::Water => biome.get_config().swim_cost,
+        _ if tile.is_walkable() => Some(1),
+        _ => None,
+    }
+}
+
+/// Whether a walker may step onto `tile` in `biome`; shared by manual movement, autoexplore and pathfinding
+#[inline]
+pub fn can_enter(tile: TileType, biome: BiomeType) -> bool {
+    move_cost(tile, biome).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::components::ItemKind;
+
+    #[test]
+    fn floor_decoration_rolls_follow_the_band_weights() {
+        let config = BiomeType::CinderGaol.get_config();
+        let floors = &config.allowed_floor_assets;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let rolls = 20_000;
+        let (mut dark, mut red, mut uniform) = (0, 0, 0);
+        for _ in 0..rolls {
+            match config.roll_floor_decoration(floors, &mut rng) {
+                Some((0, 15)) => dark += 1,
+                Some((_, 11)) => red += 1,
+                Some(other) => panic!("unexpected decoration {:?}", other),
+                None => uniform += 1,
+            }
+        }
+        let share = |count: i32| count as f32 / rolls as f32;
+        assert!((share(dark) - 0.35).abs() < 0.02, "dark brown {}", share(dark));
+        assert!((share(red) - 0.20).abs() < 0.02, "red {}", share(red));
+        assert!((share(uniform) - 0.45).abs() < 0.02, "uniform {}", share(uniform));
+
+        assert_eq!(BiomeType::Caverns.get_config().roll_floor_decoration(floors, &mut rng), None);
+    }
+
+    #[test]
+    fn floor_decoration_skips_sprites_missing_from_the_floor_list() {
+        let config = BiomeType::CinderGaol.get_config();
+        let red_only = [(0, 11), (1, 11)];
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for _ in 0..1000 {
+            if let Some(sprite) = config.roll_floor_decoration(&red_only, &mut rng) {
+                assert!(red_only.contains(&sprite), "{:?} is not an allowed floor", sprite);
+            }
+        }
+    }
+
+    #[test]
+    fn all_lists_every_biome_once_with_a_name() {
+        // No wildcard arm: a new variant won't compile until it's given a slot here
+        fn slot(biome: BiomeType) -> usize {
+            match biome {
+                BiomeType::Caverns => 0,
+                BiomeType::Underglade => 1,
+                BiomeType::FungalDeep => 2,
+                BiomeType::CinderGaol => 3,
+                BiomeType::AbyssalHold => 4,
+                BiomeType::NetherGrange => 5,
+                BiomeType::ChthronicCrypts => 6,
+                BiomeType::HypogealKnot => 7,
+                BiomeType::StygianPool => 8,
+            }
+        }
+
+        let mut seen = [false; 9];
+        for &biome in BiomeType::all() {
+            assert!(!std::mem::replace(&mut seen[slot(biome)], true), "{:?} listed twice", biome);
+            assert!(!biome.display_name().trim().is_empty(), "{:?} has no display name", biome);
+        }
+        assert!(seen.iter().all(|&s| s), "all() is missing a biome");
+        assert!(BiomeType::all().iter().any(|b| b.is_implemented()));
+    }
+
+    #[test]
+    fn each_biome_lets_walkers_onto_ground_but_not_walls_and_swims_only_its_own_water() {
+        for &biome in BiomeType::all() {
+            let swims = matches!(biome, BiomeType::StygianPool | BiomeType::AbyssalHold);
+            assert_eq!(can_enter(TileType::Water, biome), swims, "{:?} water", biome);
+            assert_eq!(move_cost(TileType::Water, biome), swims.then_some(3), "{:?} swim cost", biome);
+
+            assert_eq!(move_cost(TileType::Floor, biome), Some(1), "{:?}", biome);
+            for tile in [TileType::StairUp, TileType::StairDown, TileType::Item(ItemKind::Gold), TileType::TrapHidden] {
+                assert!(can_enter(tile, biome), "{:?} {:?}", biome, tile);
+            }
+            assert!(!can_enter(TileType::Wall, biome), "{:?} walls", biome);
+        }
+    }
+
+    #[test]
+    fn every_biome_config_is_complete_and_consistent() {
+        for &biome in BiomeType::all() {
+            let config = BiomeConfig::of(biome);
+            assert!(std::ptr::eq(config, biome.get_config()));
+            assert!(!config.name.is_empty() && !config.description.is_empty(), "{:?}", biome);
+            assert!(!config.allowed_floor_assets.is_empty(), "{:?} has no floor sprites", biome);
+            assert!(!config.allowed_wall_assets.is_empty(), "{:?} has no wall sprites", biome);
+            // Water may be left out only by biomes that never flood any
+            let floods = crate::map_generation::MapGenParams::for_biome(biome, 1).water_bodies > 0 || config.swim_cost.is_some();
+            assert!(!floods || !config.allowed_water_assets.is_empty(), "{:?} has water but no water sprites", biome);
+            assert!((0.0..=1.0).contains(&config.trapdoor_chance), "{:?}", biome);
+
+            // Autotiling covers each mask at most once, and either all sixteen or none
+            let mut masks: Vec<u8> = config.wall_autotile.iter().map(|&(mask, _)| mask).collect();
+            masks.sort_unstable();
+            masks.dedup();
+            assert_eq!(masks.len(), config.wall_autotile.len(), "{:?} lists a mask twice", biome);
+            assert!(masks.is_empty() || masks == (0..16).collect::<Vec<u8>>(), "{:?} has a partial autotile table", biome);
+
+            for band in &config.floor_decoration {
+                assert!(band.chance > 0.0 && !band.sprites.is_empty(), "{:?} has an empty decoration band", biome);
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::components::*;
 use crate::map::GameMap;
+use crate::constants::TILE_SIZE;
 
 pub fn setup_camera(mut commands: Commands) {
     commands.spawn((
@@ -25,12 +26,92 @@ pub fn setup_camera_follow(
     }
 }
 
+// Pan speed for the free camera in world units per second at 1x zoom
+const FREE_CAMERA_SPEED: f32 = 600.0;
+
+/// Run condition for player input; in Free mode the movement keys belong to the camera
+pub fn camera_following(camera_mode: Res<CameraMode>) -> bool {
+    *camera_mode == CameraMode::Follow
+}
+
+pub fn toggle_camera_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut camera_query: Query<&mut Transform, (With<GameCamera>, Without<Player>)>,
+    player_query: Query<&Transform, (With<Player>, Without<GameCamera>)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyC) &&
+       (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
+        *camera_mode = match *camera_mode {
+            CameraMode::Follow => CameraMode::Free,
+            CameraMode::Free => CameraMode::Follow,
+        };
+
+        // Snap back onto the player instead of lerping across the map
+        if *camera_mode == CameraMode::Follow {
+            if let (Ok(mut camera_transform), Ok(player_transform)) = (camera_query.single_mut(), player_query.single()) {
+                camera_transform.translation.x = player_transform.translation.x;
+                camera_transform.translation.y = player_transform.translation.y;
+            }
+        }
+
+        println!("Camera mode: {:?}", *camera_mode);
+    }
+}
+
+/// Pan the camera with arrows/WASD while in Free mode, kept within the map
+pub fn free_camera_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    camera_mode: Res<CameraMode>,
+    mut camera_query: Query<(&mut Transform, &CameraFollow), With<GameCamera>>,
+    map: Option<Res<GameMap>>,
+) {
+    let Some(map) = map else { return; };
+    if *camera_mode != CameraMode::Free {
+        return;
+    }
+
+    if let Ok((mut camera_transform, camera_follow)) = camera_query.single_mut() {
+        let mut direction = Vec2::ZERO;
+        if keyboard_input.any_pressed([KeyCode::ArrowUp, KeyCode::KeyW]) {
+            direction.y += 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::ArrowDown, KeyCode::KeyS]) {
+            direction.y -= 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
+            direction.x -= 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
+            direction.x += 1.0;
+        }
+
+        if direction == Vec2::ZERO {
+            return;
+        }
+
+        // Pan slower when zoomed in so the on-screen speed stays constant
+        let delta = direction.normalize() * FREE_CAMERA_SPEED * time.delta_secs() / camera_follow.zoom_level;
+        let half_map_width = (map.width as f32 * TILE_SIZE) / 2.0;
+        let half_map_height = (map.height as f32 * TILE_SIZE) / 2.0;
+
+        camera_transform.translation.x = (camera_transform.translation.x + delta.x).clamp(-half_map_width, half_map_width);
+        camera_transform.translation.y = (camera_transform.translation.y + delta.y).clamp(-half_map_height, half_map_height);
+    }
+}
+
 pub fn camera_follow_system(
     time: Res<Time>,
+    camera_mode: Res<CameraMode>,
     mut camera_query: Query<(&mut Transform, &mut CameraFollow), (With<GameCamera>, Without<Player>)>,
     player_query: Query<&Transform, (With<Player>, Without<GameCamera>)>,
-    map: Res<GameMap>,
+    map: Option<Res<GameMap>>,
 ) {
+    let Some(map) = map else { return; };
+    if *camera_mode == CameraMode::Free {
+        return;
+    }
     if let (Ok((mut camera_transform, camera_follow)), Ok(player_transform)) = 
         (camera_query.single_mut(), player_query.single()) {
         
@@ -38,7 +119,7 @@ pub fn camera_follow_system(
             let target_pos = player_transform.translation;
             
             // Calculate map bounds in world coordinates
-            let tile_size = 32.0;
+            let tile_size = TILE_SIZE;
             let half_map_width = (map.width as f32 * tile_size) / 2.0;
             let half_map_height = (map.height as f32 * tile_size) / 2.0;
             
@@ -135,8 +216,13 @@ pub fn camera_debug_system(
         println!("Zoom In: + or NumPad +");
         println!("Zoom Out: - or NumPad -");
         println!("Reset Zoom: F3");
+        println!("Free Camera: Shift+C (pan with WASD or Arrow Keys)");
         println!("Debug Info: F1");
         println!("Controls Help: F2");
+        println!("Examine: X (arrows move the cursor)");
+        println!("Dive: hold the descend key to chain through down stairs");
+        println!("Settings: F10");
+        println!("Dump Level PNG: F12");
         println!("=======================");
     }
 }
@@ -1,7 +1,10 @@
+use bevy::ecs::component::Mutable;
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
 use serde::{Deserialize, Serialize};
 
 use crate::biome::BiomeType;
+use crate::constants::{MAX_DEPTH, TILE_SIZE};
 
 // ============================================================================
 // TILE TYPES & MAP ENUMS
@@ -14,6 +17,40 @@ pub enum TileType {
     Water,
     StairUp,
     StairDown,
+    Altar, // Goal tile on the bottom level
+    Item(ItemKind), // Pickup lying on the floor
+    TrapHidden,     // Looks and renders like floor until detected or stepped on
+    TrapRevealed,   // Known trap; still walkable, but auto-movement routes around it
+    Trapdoor,       // One-way drop to the next level the moment it's stepped on
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum ItemKind {
+    Potion,
+    Scroll,
+    Gold,
+}
+
+impl TileType {
+    // Whether a walker can stand on this tile
+    #[inline]
+    pub fn is_walkable(&self) -> bool {
+        matches!(self, TileType::Floor | TileType::StairUp | TileType::StairDown | TileType::Altar | TileType::Item(_)
+            | TileType::TrapHidden | TileType::TrapRevealed | TileType::Trapdoor)
+    }
+
+    // Tiles worth remembering once discovered (stairs, the altar, pickups, known traps, trapdoors)
+    #[inline]
+    pub fn is_landmark(&self) -> bool {
+        matches!(self, TileType::StairUp | TileType::StairDown | TileType::Altar | TileType::Item(_)
+            | TileType::TrapRevealed | TileType::Trapdoor)
+    }
+
+    // What the player believes the tile is: undetected traps pass for floor
+    #[inline]
+    pub fn as_perceived(self) -> TileType {
+        if self == TileType::TrapHidden { TileType::Floor } else { self }
+    }
 }
 
 // ============================================================================
@@ -42,11 +79,95 @@ pub struct MovementInput {
     pub is_holding: bool,
 }
 
+// Direction of the player's last step. The rogue sheet has no up/down frames, so only
+// horizontal facing is drawn (via flip_x) and vertical steps leave the sprite as it was.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Component)]
+pub enum Facing {
+    #[default]
+    Left, // Natural sprite direction
+    Right,
+    Up,
+    Down,
+}
+
+impl Facing {
+    pub fn from_step(from: (u32, u32), to: (u32, u32)) -> Option<Facing> {
+        if to.0 < from.0 {
+            Some(Facing::Left)
+        } else if to.0 > from.0 {
+            Some(Facing::Right)
+        } else if to.1 > from.1 {
+            Some(Facing::Up)
+        } else if to.1 < from.1 {
+            Some(Facing::Down)
+        } else {
+            None
+        }
+    }
+
+    // Sprite flip implied by this facing; vertical facings keep the current flip
+    pub fn flip_x(&self) -> Option<bool> {
+        match self {
+            Facing::Left => Some(false),
+            Facing::Right => Some(true),
+            Facing::Up | Facing::Down => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum AnimationMode {
+    Idle, // Stationary bob
+    Walk, // While a MovementAnimation is active
+}
+
+// Cycles sprite.rect through frames; never touches the transform, which MovementAnimation owns
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SpriteAnimation {
+    pub frames: Vec<Rect>,
+    pub timer: Timer,
+    pub mode: AnimationMode,
+    pub frame: usize,
+}
+
+impl SpriteAnimation {
+    pub fn new(frames: Vec<Rect>, frame_duration: f32, mode: AnimationMode) -> Self {
+        Self {
+            frames,
+            timer: Timer::from_seconds(frame_duration, TimerMode::Repeating),
+            mode,
+            frame: 0,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Inventory {
+    pub items: Vec<ItemKind>,
+}
+
+// What an auto-moving player does once its path runs out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrivalAction {
+    Retarget,                   // Keep the component and let the planner pick a new target
+    AnnounceStair(TileType),    // Report the reached stairwell and stop
+    TakeStair(TileType),        // Use the reached stairwell, finishing the trip
+}
+
+// Shared stepping behaviour for components that walk the player along a path
+pub trait AutoMove: Component<Mutability = Mutable> {
+    fn path_mut(&mut self) -> &mut std::collections::VecDeque<(u32, u32)>;
+    fn move_timer_mut(&mut self) -> &mut Timer;
+    fn on_arrival(&self) -> ArrivalAction;
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Autoexplore {
-    pub active: bool,
-    pub path: Vec<(u32, u32)>,
+    pub path: std::collections::VecDeque<(u32, u32)>,
     pub target: Option<(u32, u32)>,
     pub move_timer: Timer,
 }
@@ -54,30 +175,69 @@ pub struct Autoexplore {
 impl Default for Autoexplore {
     fn default() -> Self {
         Self {
-            active: false,
-            path: Vec::new(),
+            path: std::collections::VecDeque::new(),
             target: None,
             move_timer: Timer::from_seconds(0.001, TimerMode::Repeating), // Blazing fast auto-movement
         }
     }
 }
 
+impl AutoMove for Autoexplore {
+    fn path_mut(&mut self) -> &mut std::collections::VecDeque<(u32, u32)> {
+        &mut self.path
+    }
+
+    fn move_timer_mut(&mut self) -> &mut Timer {
+        &mut self.move_timer
+    }
+
+    fn on_arrival(&self) -> ArrivalAction {
+        ArrivalAction::Retarget
+    }
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct AutoMoveToStair {
-    pub path: Vec<(u32, u32)>,
+    pub path: std::collections::VecDeque<(u32, u32)>,
     pub target: (u32, u32),
     pub stair_type: TileType,
     pub move_timer: Timer,
+    // Take the stairs on arrival instead of waiting for a key press
+    pub take_on_arrival: bool,
 }
 
 impl AutoMoveToStair {
-    pub fn new(target: (u32, u32), path: Vec<(u32, u32)>, stair_type: TileType) -> Self {
+    pub fn new(target: (u32, u32), path: std::collections::VecDeque<(u32, u32)>, stair_type: TileType) -> Self {
         Self {
             path,
             target,
             stair_type,
             move_timer: Timer::from_seconds(0.001, TimerMode::Repeating), // Blazing fast auto-movement (same as autoexplore)
+            take_on_arrival: false,
+        }
+    }
+
+    pub fn with_take_on_arrival(mut self, take_on_arrival: bool) -> Self {
+        self.take_on_arrival = take_on_arrival;
+        self
+    }
+}
+
+impl AutoMove for AutoMoveToStair {
+    fn path_mut(&mut self) -> &mut std::collections::VecDeque<(u32, u32)> {
+        &mut self.path
+    }
+
+    fn move_timer_mut(&mut self) -> &mut Timer {
+        &mut self.move_timer
+    }
+
+    fn on_arrival(&self) -> ArrivalAction {
+        if self.take_on_arrival {
+            ArrivalAction::TakeStair(self.stair_type)
+        } else {
+            ArrivalAction::AnnounceStair(self.stair_type)
         }
     }
 }
@@ -110,6 +270,25 @@ pub struct TileVisibilityState {
     pub visibility: TileVisibility,
 }
 
+/// Landmarks the player has discovered on the current level, kept through Seen memory
+#[derive(Resource, Default)]
+pub struct RememberedFeatures {
+    pub features: std::collections::HashMap<(u32, u32), TileType>,
+}
+
+impl RememberedFeatures {
+    pub fn get(&self, x: u32, y: u32) -> Option<TileType> {
+        self.features.get(&(x, y)).copied()
+    }
+
+    // Remembered positions of one landmark type, e.g. the stairs for click-to-travel
+    pub fn positions_of(&self, tile_type: TileType) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.features.iter()
+            .filter(move |(_, remembered)| **remembered == tile_type)
+            .map(|(pos, _)| *pos)
+    }
+}
+
 // ============================================================================
 // PARTICLE COMPONENTS
 // ============================================================================
@@ -209,6 +388,57 @@ pub struct GameCamera;
 #[reflect(Component)]
 pub struct DepthIndicator;
 
+// Contextual hint shown while the player stands on stairs
+#[derive(Component)]
+pub struct StairPrompt;
+
+#[derive(Component)]
+pub struct MainMenuRoot;
+
+#[derive(Component)]
+pub struct VictoryScreenRoot;
+
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum MenuButton {
+    NewGame,
+    ReduceMotion,
+    Stairs,
+    BackToMenu,
+    Quit,
+}
+
+// Text of the reduce-motion toggle, rewritten whenever the setting flips
+#[derive(Component)]
+pub struct ReduceMotionLabel;
+
+// Text of the stair mode toggle
+#[derive(Component)]
+pub struct StairModeLabel;
+
+// Fullscreen overlay that hides the tilemap swap between levels
+#[derive(Component)]
+pub struct LevelFadeOverlay;
+
+// Root of the in-game settings overlay
+#[derive(Component)]
+pub struct SettingsMenuRoot;
+
+// One line of the settings overlay, by index into the item list
+#[derive(Component)]
+pub struct SettingsMenuRow(pub usize);
+
+// Text of the debug tile inspector panel
+#[derive(Component)]
+pub struct TileInspectorPanel;
+
+// Highlight sprite over the examined tile
+#[derive(Component)]
+pub struct ExamineCursor;
+
+// Text describing the examined tile
+#[derive(Component)]
+pub struct ExaminePanel;
+
 // ============================================================================
 // RESOURCES
 // ============================================================================
@@ -244,6 +474,17 @@ impl rand::RngCore for GlobalRng {
     }
 }
 
+/// Separate stream for trap detection rolls, so how often the player steps near a trap
+/// doesn't shift the GlobalRng draws that pick level seeds
+#[derive(Resource, Deref, DerefMut)]
+pub struct TrapRng(pub GlobalRng);
+
+impl Default for TrapRng {
+    fn default() -> Self {
+        Self(GlobalRng::new())
+    }
+}
+
 #[derive(Resource, Deref, DerefMut)]
 pub struct PlayerEntity(pub Entity);
 
@@ -251,51 +492,671 @@ pub struct PlayerEntity(pub Entity);
 pub struct PlayerSpriteConfig {
     pub sprite_rect: Rect,
     pub custom_size: Vec2,
+    // Pivot of the sprite; BottomCenter puts the feet on the tile's bottom edge for tall sprites
+    pub anchor: Anchor,
+    // Animation frames; a single frame (the sprite_rect) means no visible animation
+    pub idle_frames: Vec<Rect>,
+    pub walk_frames: Vec<Rect>,
+    pub frame_duration: f32,
 }
 
-#[derive(Resource)]
+impl PlayerSpriteConfig {
+    // Static sprite with no extra animation frames
+    pub fn single_frame(sprite_rect: Rect, custom_size: Vec2) -> Self {
+        Self {
+            sprite_rect,
+            custom_size,
+            anchor: Anchor::Center,
+            idle_frames: vec![sprite_rect],
+            walk_frames: vec![sprite_rect],
+            frame_duration: 0.25,
+        }
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    // Added to a tile-center position so the anchor lands on the matching point of the tile
+    // (zero for centered sprites, half a tile down for feet-anchored ones)
+    pub fn world_offset(&self) -> Vec2 {
+        self.anchor.as_vec() * TILE_SIZE
+    }
+
+    pub fn frames_for(&self, mode: AnimationMode) -> &[Rect] {
+        match mode {
+            AnimationMode::Idle => &self.idle_frames,
+            AnimationMode::Walk => &self.walk_frames,
+        }
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct CurrentLevel {
     pub level: u32,
     pub biome: BiomeType,
 }
 
+/// Length of the run. The bottom level (max_depth) holds the altar instead of down stairs and
+/// has no trapdoor; level 0 is the top and has no up stairs
+#[derive(Resource, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct DungeonConfig {
+    pub max_depth: u32,
+}
+
+impl Default for DungeonConfig {
+    fn default() -> Self {
+        Self { max_depth: MAX_DEPTH }
+    }
+}
+
+/// Where the player is placed on a map. The resource picks the spot on a new run or a regenerated
+/// level; stair transitions pass their own (arriving on the stairs they came through)
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub enum StartPolicy {
+    #[default]
+    Center,        // Floor tile nearest the map center
+    OnUpStair,     // The up stairs, as when arriving from above
+    NearDownStair, // The down stairs, as when arriving from below
+}
+
+/// Chosen on the main menu before a run starts. Descend-only generates no up stairs, refuses upward
+/// travel and forgets levels above the player, since they can never be revisited
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub enum StairMode {
+    #[default]
+    BothWays,
+    DescendOnly,
+}
+
+impl StairMode {
+    pub fn allows_ascent(self) -> bool {
+        self == StairMode::BothWays
+    }
+
+    pub fn label(self) -> String {
+        format!("Stairs: {}", match self {
+            StairMode::BothWays => "Both Ways",
+            StairMode::DescendOnly => "Descend Only",
+        })
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct LevelMaps {
     pub maps: std::collections::HashMap<u32, SavedMapData>,
 }
 
-#[derive(Resource)]
-pub struct FovSettings {
+/// Active generation seed per level so revisited or regenerated levels rebuild identically
+#[derive(Resource, Default)]
+pub struct LevelSeeds {
+    pub seeds: std::collections::HashMap<u32, u64>,
+}
+
+impl LevelSeeds {
+    // Seed for a level, drawing and remembering a fresh one the first time it's generated
+    pub fn seed_for(&mut self, level: u32, rng: &mut impl rand::RngCore) -> u64 {
+        *self.seeds.entry(level).or_insert_with(|| rng.next_u64())
+    }
+
+    // Replace a level's seed so the regenerated layout is the one that persists
+    pub fn reseed(&mut self, level: u32, rng: &mut impl rand::RngCore) -> u64 {
+        let seed = rng.next_u64();
+        self.seeds.insert(level, seed);
+        seed
+    }
+
+    // Step a level's seed by one, for walking through neighbouring seeds while debugging generation
+    pub fn advance(&mut self, level: u32, rng: &mut impl rand::RngCore) -> u64 {
+        let seed = self.seed_for(level, rng).wrapping_add(1);
+        self.seeds.insert(level, seed);
+        seed
+    }
+}
+
+/// Tiles currently in the player's field of view, kept in step with calculate_fov
+#[derive(Resource, Default)]
+pub struct VisibleTiles(pub std::collections::HashSet<(u32, u32)>);
+
+impl VisibleTiles {
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        self.0.contains(&(x, y))
+    }
+
+    // Record a tile's latest visibility, dropping it from the set once it leaves view
+    pub fn sync(&mut self, x: u32, y: u32, visibility: TileVisibility) {
+        if visibility == TileVisibility::Visible {
+            self.0.insert((x, y));
+        } else {
+            self.0.remove(&(x, y));
+        }
+    }
+}
+
+/// Every tile the player has ever seen on the current level (Seen or Visible), kept in step with
+/// tile visibility. Unlike VisibleTiles it only grows, until the tilemap is rebuilt for another map
+/// or TileVisibilityControl hides tiles again
+#[derive(Resource, Default)]
+pub struct ExploredTiles(pub std::collections::HashSet<(u32, u32)>);
+
+impl ExploredTiles {
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        self.0.contains(&(x, y))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Explored tiles split into (remembered only, currently visible), each in row-major order,
+    /// e.g. for drawing a minimap
+    pub fn split(&self, visible: &VisibleTiles) -> (Vec<(u32, u32)>, Vec<(u32, u32)>) {
+        let (mut in_view, mut seen): (Vec<_>, Vec<_>) = self.0.iter().partition(|&&(x, y)| visible.contains(x, y));
+        let row_major = |&(x, y): &(u32, u32)| (y, x);
+        seen.sort_unstable_by_key(row_major);
+        in_view.sort_unstable_by_key(row_major);
+        (seen, in_view)
+    }
+}
+
+// Outline of the FOV area, applied before the LOS check
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum FovShape {
+    #[default]
+    Circle,  // Euclidean distance
+    Square,  // Chebyshev distance: the full radius box
+    Diamond, // Manhattan distance
+}
+
+impl FovShape {
+    #[inline]
+    pub fn in_range(self, dx: i32, dy: i32, radius: i32) -> bool {
+        match self {
+            FovShape::Circle => dx * dx + dy * dy <= radius * radius,
+            FovShape::Square => dx.abs().max(dy.abs()) <= radius,
+            FovShape::Diamond => dx.abs() + dy.abs() <= radius,
+        }
+    }
+
+    // Distance from the center under this shape's metric, so `in_range` is `distance <= radius`
+    pub fn distance(self, dx: i32, dy: i32) -> f32 {
+        match self {
+            FovShape::Circle => ((dx * dx + dy * dy) as f32).sqrt(),
+            FovShape::Square => dx.abs().max(dy.abs()) as f32,
+            FovShape::Diamond => (dx.abs() + dy.abs()) as f32,
+        }
+    }
+}
+
+// Tunable FOV parameters; everything the player or a debug key can change
+#[derive(Resource, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct FovConfig {
     pub radius: u32,
+    pub shape: FovShape,
+    // Dark-vision floor on the radius so the player is never fully blind
+    pub min_radius: u32,
+    // Always light the player's tile and its 8 neighbors, regardless of LOS
+    pub always_reveal_adjacent: bool,
+    // Outer fraction of the radius over which lit tiles fade out; 0 keeps the crisp edge
+    pub edge_falloff: f32,
     pub debug_reveal_all: bool,
+}
+
+impl Default for FovConfig {
+    fn default() -> Self {
+        Self {
+            radius: 20, // 2.5x the original radius of 8
+            shape: FovShape::Circle,
+            min_radius: 1,
+            always_reveal_adjacent: true,
+            edge_falloff: 0.0,
+            debug_reveal_all: false,
+        }
+    }
+}
+
+impl FovConfig {
+    // Lit tiles at the very edge of the radius keep this much alpha when edge falloff is on
+    pub const MIN_EDGE_ALPHA: f32 = 0.35;
+
+    // Radius actually used, after the dark-vision floor
+    pub fn effective_radius(&self) -> u32 {
+        self.radius.max(self.min_radius)
+    }
+
+    // Alpha for a lit tile (dx, dy) from the player: 1.0 inside the falloff band, easing to
+    // MIN_EDGE_ALPHA at the radius
+    pub fn edge_alpha(&self, dx: i32, dy: i32) -> f32 {
+        let band = self.edge_falloff.clamp(0.0, 1.0);
+        if band <= 0.0 {
+            return 1.0;
+        }
+        let t = self.shape.distance(dx, dy) / self.effective_radius().max(1) as f32;
+        let into_band = ((t - (1.0 - band)) / band).clamp(0.0, 1.0);
+        1.0 - into_band * (1.0 - Self::MIN_EDGE_ALPHA)
+    }
+}
+
+// Runtime bookkeeping for deciding when and how much of the FOV to recompute
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct FovState {
     pub needs_recalculation: bool,
     pub debug_mode_applied: bool,
     // Dirty tracking for incremental FOV updates
     pub last_player_pos: Option<(u32, u32)>,
+    #[reflect(ignore)]
     pub dirty_tiles: std::collections::HashSet<(u32, u32)>,
-    // LOS caching for symmetric line-of-sight calculations
-    pub los_cache: std::collections::HashMap<(u32, u32, u32, u32), bool>,
-    pub cache_hits: usize,
-    pub cache_misses: usize,
 }
 
-impl Default for FovSettings {
+impl Default for FovState {
     fn default() -> Self {
         Self {
-            radius: 20, // 2.5x the original radius of 8
-            debug_reveal_all: false,
             needs_recalculation: true,
             debug_mode_applied: false,
             last_player_pos: None,
             dirty_tiles: std::collections::HashSet::new(),
-            los_cache: std::collections::HashMap::new(),
-            cache_hits: 0,
-            cache_misses: 0,
         }
     }
 }
 
+impl FovState {
+    // Drop everything tied to the previous map and force a full update
+    pub fn reset_for_new_map(&mut self) {
+        self.needs_recalculation = true;
+        self.last_player_pos = None;
+        self.dirty_tiles.clear();
+    }
+}
+
+pub type LosEntries = std::collections::HashMap<(u32, u32, u32, u32), bool>;
+
+// Symmetric line-of-sight results for the current map, keyed smaller endpoint first
+#[derive(Resource, Default)]
+pub struct LosCache {
+    pub entries: LosEntries,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl LosCache {
+    pub fn hit_rate(&self) -> Option<f32> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f32 / total as f32 * 100.0)
+    }
+
+    // Print and zero the hit statistics, e.g. when leaving a map
+    pub fn report_and_reset_stats(&mut self) {
+        if let Some(hit_rate) = self.hit_rate() {
+            println!("LOS cache stats - Hits: {}, Misses: {}, Hit rate: {:.1}%", self.hits, self.misses, hit_rate);
+        }
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+/// Builds the three FOV resources from one place so a config and fresh runtime state always
+/// start out together
+#[derive(Default)]
+pub struct FovBuilder {
+    config: FovConfig,
+}
+
+impl FovBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn radius(mut self, radius: u32) -> Self {
+        self.config.radius = radius;
+        self
+    }
+
+    pub fn shape(mut self, shape: FovShape) -> Self {
+        self.config.shape = shape;
+        self
+    }
+
+    pub fn min_radius(mut self, min_radius: u32) -> Self {
+        self.config.min_radius = min_radius;
+        self
+    }
+
+    pub fn always_reveal_adjacent(mut self, enabled: bool) -> Self {
+        self.config.always_reveal_adjacent = enabled;
+        self
+    }
+
+    pub fn edge_falloff(mut self, fraction: f32) -> Self {
+        self.config.edge_falloff = fraction;
+        self
+    }
+
+    pub fn debug_reveal_all(mut self, enabled: bool) -> Self {
+        self.config.debug_reveal_all = enabled;
+        self
+    }
+
+    pub fn config(self) -> FovConfig {
+        self.config
+    }
+
+    pub fn build(self) -> (FovConfig, FovState, LosCache) {
+        (self.config, FovState::default(), LosCache::default())
+    }
+}
+
+/// LOS caches of levels the player has left, handed back on revisit since saved levels keep their
+/// geometry. Once the total passes `max_total_entries` the least recently left levels are dropped first
+#[derive(Resource)]
+pub struct SavedLosCaches {
+    pub enabled: bool,
+    pub max_total_entries: usize,
+    caches: std::collections::HashMap<u32, LosEntries>,
+    order: Vec<u32>, // Least recently stored first
+}
+
+impl Default for SavedLosCaches {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_total_entries: 100_000, // A few MB across all levels
+            caches: std::collections::HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl SavedLosCaches {
+    pub fn store(&mut self, level: u32, cache: LosEntries) {
+        self.forget(level);
+        if !self.enabled || cache.is_empty() {
+            return;
+        }
+        self.caches.insert(level, cache);
+        self.order.push(level);
+
+        while self.total_entries() > self.max_total_entries && !self.order.is_empty() {
+            let evicted = self.order.remove(0);
+            self.caches.remove(&evicted);
+            println!("Evicted LOS cache for level {} ({} entries cached across levels)", evicted, self.total_entries());
+        }
+    }
+
+    pub fn take(&mut self, level: u32) -> Option<LosEntries> {
+        self.order.retain(|&stored| stored != level);
+        self.caches.remove(&level)
+    }
+
+    // Drop a level's cache when its layout is rebuilt
+    pub fn forget(&mut self, level: u32) {
+        self.take(level);
+    }
+
+    pub fn total_entries(&self) -> usize {
+        self.caches.values().map(|cache| cache.len()).sum()
+    }
+
+    // Drop every level's cache, keeping the settings
+    pub fn clear(&mut self) {
+        self.caches.clear();
+        self.order.clear();
+    }
+}
+
+/// Debug tile inspector: shows details of the tile under the mouse cursor
+#[derive(Resource, Default)]
+pub struct TileInspector {
+    pub enabled: bool,
+}
+
+/// Look mode: the movement keys drive a cursor over discovered tiles instead of the player
+#[derive(Resource, Default)]
+pub struct ExamineMode {
+    pub active: bool,
+    pub cursor: (u32, u32),
+}
+
+/// In-game settings overlay state; gameplay sits in Paused while it's open
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+}
+
+/// Keyboard focus within one menu screen. Lives on the screen's root entity, so every screen keeps
+/// its own and it goes away with the screen
+#[derive(Component, Default)]
+pub struct MenuFocus {
+    pub index: usize,
+}
+
+impl MenuFocus {
+    // Move one row in the direction of `step`, wrapping at either end and skipping rows that can't
+    // take focus; stays put when nothing can
+    pub fn step(&mut self, step: isize, focusable: &[bool]) {
+        let count = focusable.len();
+        if step == 0 || !focusable.iter().any(|&f| f) {
+            return;
+        }
+        let mut index = self.index.min(count - 1);
+        loop {
+            index = (index as isize + step.signum()).rem_euclid(count as isize) as usize;
+            if focusable[index] {
+                break;
+            }
+        }
+        self.index = index;
+    }
+}
+
+/// Accessibility options, set from the main menu
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct AccessibilitySettings {
+    // Snap moves instead of hopping and calm particle motion
+    pub reduce_motion: bool,
+    // Strongly separated Unseen/Seen/Visible colors in place of the biome tint
+    pub high_contrast: bool,
+    // A manual step into an Unseen tile needs a second press to confirm
+    pub careful_movement: bool,
+}
+
+impl AccessibilitySettings {
+    pub fn reduce_motion_label(&self) -> String {
+        format!("Reduce Motion: {}", if self.reduce_motion { "On" } else { "Off" })
+    }
+}
+
+/// Debug camera mode: Follow tracks the player, Free pans independently for inspecting maps
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub enum CameraMode {
+    #[default]
+    Follow,
+    Free,
+}
+
+// How autoexplore chooses its next target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum ExploreStrategy {
+    #[default]
+    NearestUnseen,   // Closest unseen floor tile by walking distance
+    NearestFrontier, // Closest seen tile bordering unseen space, which avoids zig-zagging
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct AutoexploreSettings {
+    pub speed_multiplier: f32,
+    pub strategy: ExploreStrategy,
+    // Skip the hop animation and jump straight to each explore target
+    pub instant: bool,
+    // Take the stairs as soon as auto-movement reaches them; off keeps the press-again step
+    pub auto_use_stairs_on_arrival: bool,
+}
+
+impl Default for AutoexploreSettings {
+    fn default() -> Self {
+        Self {
+            speed_multiplier: 1.0,
+            strategy: ExploreStrategy::default(),
+            instant: false,
+            auto_use_stairs_on_arrival: false,
+        }
+    }
+}
+
+impl AutoexploreSettings {
+    pub const MIN_SPEED: f32 = 0.25;
+    pub const MAX_SPEED: f32 = 8.0;
+}
+
+/// Fast diving for testing deep levels: keep the descend key held and, once `hold_seconds` have
+/// passed, each arrival immediately travels on to that level's down stairs (once discovered and
+/// reachable) until the key is released, `max_levels` have been descended, or the bottom is reached
 #[derive(Resource)]
+pub struct StairDive {
+    pub hold_seconds: f32,
+    pub max_levels: u32,
+    pressed: Option<(f32, u32)>, // (time the key went down, level at that moment)
+    active: bool,
+    awaiting_level: Option<u32>, // Descent requested, transition not finished yet
+}
+
+impl Default for StairDive {
+    fn default() -> Self {
+        Self {
+            hold_seconds: 0.6,
+            max_levels: 10,
+            pressed: None,
+            active: false,
+            awaiting_level: None,
+        }
+    }
+}
+
+impl StairDive {
+    pub fn press(&mut self, time: f32, level: u32) {
+        self.pressed = Some((time, level));
+    }
+
+    // Level the dive started from, once the key has been held long enough
+    pub fn held_start(&self, time: f32) -> Option<u32> {
+        self.pressed
+            .filter(|&(pressed_at, _)| time - pressed_at >= self.hold_seconds)
+            .map(|(_, level)| level)
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed.is_some()
+    }
+
+    // True while a requested descent hasn't landed yet
+    pub fn waiting_for(&mut self, level: u32) -> bool {
+        match self.awaiting_level {
+            Some(target) if target != level => true,
+            _ => {
+                self.awaiting_level = None;
+                false
+            }
+        }
+    }
+
+    pub fn descend_to(&mut self, level: u32) {
+        self.active = true;
+        self.awaiting_level = Some(level);
+    }
+
+    // End the dive, summarising it if it got going at all
+    pub fn finish(&mut self, level: u32, reason: &str) {
+        if self.active {
+            if let Some((_, start)) = self.pressed {
+                println!("Dive ended ({}): descended {} level(s), depth {} -> {}",
+                    reason, level.saturating_sub(start), start, level);
+            }
+        }
+        self.cancel();
+    }
+
+    // Drop any dive in progress without a summary, keeping the tuning
+    pub fn cancel(&mut self) {
+        self.pressed = None;
+        self.active = false;
+        self.awaiting_level = None;
+    }
+}
+
+/// Tiles the player walked recently, so autoexplore can break ties toward ground it hasn't just
+/// covered instead of shuffling between two equally distant frontiers. Cleared on every map swap
+#[derive(Resource, Default)]
+pub struct VisitTrail {
+    step: u32,
+    last_visit: std::collections::HashMap<(u32, u32), u32>,
+}
+
+impl VisitTrail {
+    // Steps before a visit stops counting
+    pub const LENGTH: u32 = 200;
+
+    pub fn visit(&mut self, pos: (u32, u32)) {
+        self.step += 1;
+        self.last_visit.insert(pos, self.step);
+        if self.last_visit.len() > Self::LENGTH as usize * 2 {
+            let step = self.step;
+            self.last_visit.retain(|_, &mut visited| step - visited < Self::LENGTH);
+        }
+    }
+
+    // How recently a tile was walked: LENGTH for the last step, falling to 0 once it's LENGTH steps old
+    pub fn recency(&self, pos: (u32, u32)) -> u32 {
+        self.last_visit.get(&pos)
+            .map_or(0, |&visited| Self::LENGTH.saturating_sub(self.step - visited))
+    }
+
+    pub fn clear(&mut self) {
+        self.step = 0;
+        self.last_visit.clear();
+    }
+}
+
+// Distance-based darkening of remembered (Seen) tiles
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MemoryFadeSettings {
+    pub enabled: bool,
+    pub fade_distance: f32,   // Tiles this far from the player reach min_brightness
+    pub min_brightness: f32,
+    // Throttle for re-evaluating every Seen tile as the player moves
+    pub refresh_timer: Timer,
+    pub needs_refresh: bool,
+    pub last_refresh_pos: Option<(u32, u32)>,
+}
+
+impl Default for MemoryFadeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fade_distance: 40.0,
+            min_brightness: 0.35,
+            refresh_timer: Timer::from_seconds(0.25, TimerMode::Repeating),
+            needs_refresh: false,
+            last_refresh_pos: None,
+        }
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct ParticleSpawner {
     pub primary_timer: Timer,
     pub secondary_timer: Timer,
@@ -304,7 +1165,8 @@ pub struct ParticleSpawner {
     pub initial_spawn_complete: bool,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct WindState {
     pub timer: Timer,
     pub strength: f32,
@@ -312,11 +1174,17 @@ pub struct WindState {
     pub base_multiplier: f32,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct ParticleSettings {
     pub density_multiplier: f32,
     pub enabled: bool,
     pub debug_mode: bool,
+    pub spawn_near_player: bool, // Only spawn within the fade radius of the player instead of map-wide
+    pub max_total_particles: usize, // Budget shared by both layers, on top of each biome's per-layer caps
+    pub fade_outside_fov: bool, // Also hide particles over tiles the player can't currently see
+    pub initial_fill: f32, // Fraction of each layer's max spawned at once on entering a biome
+    pub warmup_seconds: f32, // Entry particles start up to this far into their drift and lifetime; 0 spawns them fresh
 }
 
 impl Default for ParticleSettings {
@@ -325,6 +1193,11 @@ impl Default for ParticleSettings {
             density_multiplier: 6.0, // 6x density for very rich atmospheric effects
             enabled: true,
             debug_mode: false,
+            spawn_near_player: false,
+            max_total_particles: 500,
+            fade_outside_fov: true,
+            initial_fill: 0.67,
+            warmup_seconds: 3.0,
         }
     }
 }
@@ -347,6 +1220,31 @@ impl TileIndex {
     pub fn clear(&mut self) {
         self.tiles.clear();
     }
+
+    // Visibility of the tile at (x, y); anything not indexed (off-map, mid-rebuild) counts as Unseen
+    pub fn visibility(&self, x: u32, y: u32, tile_query: &Query<&TileVisibilityState>) -> TileVisibility {
+        self.tiles.get(&(x, y))
+            .and_then(|&entity| tile_query.get(entity).ok())
+            .map_or(TileVisibility::Unseen, |state| state.visibility)
+    }
+
+    // Positions whose indexed entity is gone or no longer carries a matching TilePos,
+    // e.g. a pooled tile that was released but never re-initialized
+    pub fn validate(&self, tile_query: &Query<&bevy_ecs_tilemap::prelude::TilePos>) -> Result<(), Vec<(u32, u32)>> {
+        let mut stale: Vec<(u32, u32)> = self.tiles.iter()
+            .filter(|&(&(x, y), &entity)| {
+                !matches!(tile_query.get(entity), Ok(tile_pos) if tile_pos.x == x && tile_pos.y == y)
+            })
+            .map(|(&pos, _)| pos)
+            .collect();
+
+        if stale.is_empty() {
+            Ok(())
+        } else {
+            stale.sort();
+            Err(stale)
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -371,12 +1269,15 @@ impl TilePool {
         self.available.pop()
     }
 
-    // Return a tile entity to the pool for reuse
-    pub fn release(&mut self, entity: Entity) {
+    // Return a tile entity to the pool for reuse; false if the pool is full and the caller must despawn it
+    #[must_use]
+    pub fn release(&mut self, entity: Entity) -> bool {
         if self.available.len() < self.max_pool_size {
             self.available.push(entity);
+            true
+        } else {
+            false
         }
-        // If pool is full, entity will be despawned by caller
     }
 
     pub fn len(&self) -> usize {
@@ -402,8 +1303,9 @@ impl EllipseMask {
         let center_y = height as f32 / 2.0;
         
         // Create an ellipse that fits within the map bounds with some padding
-        let a = (width as f32 / 2.0) - 2.0; // Semi-major axis (horizontal)
-        let b = (height as f32 / 2.0) - 2.0; // Semi-minor axis (vertical)
+        // Clamped so tiny maps get an empty mask instead of dividing by zero or a negative axis
+        let a = ((width as f32 / 2.0) - 2.0).max(0.5); // Semi-major axis (horizontal)
+        let b = ((height as f32 / 2.0) - 2.0).max(0.5); // Semi-minor axis (vertical)
         
         // Pre-calculate all positions
         for y in 0..height {
@@ -433,6 +1335,12 @@ impl EllipseMask {
         self.mask[(y * self.width + x) as usize]
     }
     
+    /// Dimensions the mask was built for
+    #[inline]
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
     /// Update the mask for new dimensions (used when map size changes)
     pub fn resize(&mut self, width: u32, height: u32) {
         *self = Self::new(width, height);
@@ -443,14 +1351,81 @@ impl EllipseMask {
 // DATA STRUCTURES
 // ============================================================================
 
+// Run-length encoded tile grid in row-major order; saved levels are mostly long runs of wall
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RleTiles {
+    runs: Vec<(TileType, u32)>,
+}
+
+impl RleTiles {
+    pub fn encode(tiles: &[TileType]) -> Self {
+        let mut runs: Vec<(TileType, u32)> = Vec::new();
+        for &tile in tiles {
+            match runs.last_mut() {
+                Some((last, count)) if *last == tile => *count += 1,
+                _ => runs.push((tile, 1)),
+            }
+        }
+        Self { runs }
+    }
+
+    pub fn decode(&self) -> Vec<TileType> {
+        let mut tiles = Vec::with_capacity(self.len());
+        for &(tile, count) in &self.runs {
+            tiles.extend(std::iter::repeat_n(tile, count as usize));
+        }
+        tiles
+    }
+
+    // Tiles covered by the runs
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|&(_, count)| count as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    // Runs stored, which is what the saved copy actually costs
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SavedMapData {
     pub width: u32,
     pub height: u32,
-    pub tiles: Vec<TileType>,
+    pub tiles: RleTiles, // GameMap keeps tiles uncompressed; only the saved copy is packed
     pub stair_up_pos: Option<(u32, u32)>,
     pub stair_down_pos: Option<(u32, u32)>,
+    #[serde(default)] // Saves from before the altar was recorded
+    pub altar_pos: Option<(u32, u32)>,
     pub biome: BiomeType,
     // Sparse storage: only store non-Unseen tiles (HashMap: position -> visibility state)
     pub tile_visibility: std::collections::HashMap<(u32, u32), TileVisibility>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_tiles_round_trip_every_tile() {
+        let mut tiles = vec![TileType::Wall; 200];
+        tiles[10..40].fill(TileType::Floor);
+        tiles[41] = TileType::StairDown;
+        tiles[42..60].fill(TileType::Water);
+        tiles[199] = TileType::Item(ItemKind::Gold);
+
+        let packed = RleTiles::encode(&tiles);
+        assert_eq!(packed.decode(), tiles);
+        assert_eq!(packed.len(), tiles.len());
+        // Wall, Floor, Wall, StairDown, Water, Wall, Item
+        assert_eq!(packed.run_count(), 7);
+
+        let empty = RleTiles::encode(&[]);
+        assert!(empty.is_empty());
+        assert!(empty.decode().is_empty());
+    }
+}
@@ -0,0 +1,7 @@
+// Shared numeric constants
+
+// World-space size of one map tile in pixels; tilemap tile/grid size and all grid <-> world math derive from it
+pub const TILE_SIZE: f32 = 32.0;
+
+// Default deepest level (see DungeonConfig); it holds the altar instead of down stairs. Level 0 is the top and has no up stairs
+pub const MAX_DEPTH: u32 = 50;
@@ -1,101 +1,274 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
+use std::collections::hash_map::Entry;
 
-use crate::components::{Player, TileType, CurrentLevel, TileVisibility, TileVisibilityState, FovSettings};
+use crate::components::{Player, TileType, AccessibilitySettings, CurrentLevel, MapTile, TileIndex, TileVisibility, TileVisibilityState, FovBuilder, FovConfig, FovState, LosCache, MemoryFadeSettings, VisibleTiles, ExploredTiles, RememberedFeatures};
 use crate::map::GameMap;
-use crate::biome::BiomeType;
+use crate::biome::FovTint;
+use crate::metrics::{PerfMetrics, PerfStat};
+use crate::level_manager::map_ready;
+
+// Fired once per tile per level, on the first Unseen -> Visible transition
+#[derive(Event)]
+pub struct TileDiscovered {
+    pub pos: (u32, u32),
+    pub tile_type: TileType,
+}
+
+// A tile changed outside of player movement (doors, carving, pickups); report_map_edits sends one
+// for every tile written on the live map, so mutating systems only have to call GameMap::set
+#[derive(Event)]
+pub struct MapChanged {
+    pub pos: (u32, u32),
+}
+
+/// Set tile visibility directly, outside the FOV calculation (cutscenes, debugging). Changed tiles
+/// are recolored by update_tile_visibility like any FOV update; the next FOV pass may overwrite them
+#[derive(SystemParam)]
+pub struct TileVisibilityControl<'w, 's> {
+    tile_index: Res<'w, TileIndex>,
+    tile_query: Query<'w, 's, &'static mut TileVisibilityState>,
+    visible_tiles: ResMut<'w, VisibleTiles>,
+    explored: ResMut<'w, ExploredTiles>,
+    remembered: ResMut<'w, RememberedFeatures>,
+}
+
+impl TileVisibilityControl<'_, '_> {
+    /// Set one tile's visibility; false if no tile is indexed at `pos`
+    pub fn set_tile_visibility(&mut self, pos: (u32, u32), visibility: TileVisibility) -> bool {
+        let Some(&entity) = self.tile_index.tiles.get(&pos) else { return false; };
+        let Ok(mut state) = self.tile_query.get_mut(entity) else { return false; };
+        if state.visibility != visibility {
+            state.visibility = visibility;
+        }
+        self.visible_tiles.sync(pos.0, pos.1, visibility);
+        // Forgetting a tile also drops it from exploration and landmark memory
+        if visibility == TileVisibility::Unseen {
+            self.explored.0.remove(&pos);
+            self.remembered.features.remove(&pos);
+        }
+        true
+    }
+
+    /// Make every tile Visible
+    pub fn reveal_all(&mut self) {
+        self.set_all(TileVisibility::Visible);
+    }
+
+    /// Reset every tile to Unseen, as on a level nobody has entered
+    pub fn hide_all(&mut self) {
+        self.set_all(TileVisibility::Unseen);
+        self.explored.0.clear();
+        self.remembered.features.clear();
+    }
+
+    fn set_all(&mut self, visibility: TileVisibility) {
+        for (&(x, y), &entity) in self.tile_index.tiles.iter() {
+            if let Ok(mut state) = self.tile_query.get_mut(entity) {
+                if state.visibility != visibility {
+                    state.visibility = visibility;
+                }
+                self.visible_tiles.sync(x, y, visibility);
+            }
+        }
+    }
+}
 
 pub struct FovPlugin;
 
 impl Plugin for FovPlugin {
     fn build(&self, app: &mut App) {
+        let (fov_config, fov_state, los_cache) = FovBuilder::new().build();
         app
-            .init_resource::<FovSettings>()
+            .insert_resource(fov_config)
+            .insert_resource(fov_state)
+            .insert_resource(los_cache)
+            .init_resource::<MemoryFadeSettings>()
+            .init_resource::<VisibleTiles>()
+            .init_resource::<ExploredTiles>()
+            .init_resource::<RememberedFeatures>()
+            .add_event::<TileDiscovered>()
+            .add_event::<MapChanged>()
             .add_systems(Update, (
                 detect_player_movement,
+                report_map_edits,
+                handle_map_changes,
+                apply_debug_reveal,
                 calculate_fov.run_if(should_recalculate_fov),
                 update_tile_visibility,
+                update_explored_tiles,
+                update_remembered_features,
+                announce_discoveries,
                 handle_fov_debug_controls,
-            ).chain());
+            ).chain().run_if(map_ready)); // A tilemap still streaming in has no tiles to light yet
     }
 }
 
 // System to detect when player has moved to trigger FOV recalculation
 pub fn detect_player_movement(
     player_query: Query<&Player, Changed<Player>>,
-    mut fov_settings: ResMut<FovSettings>,
+    mut fov_state: ResMut<FovState>,
 ) {
     if let Ok(player) = player_query.single() {
         let current_pos = (player.x, player.y);
 
         // Only trigger recalculation if position actually changed
-        if fov_settings.last_player_pos != Some(current_pos) {
-            fov_settings.needs_recalculation = true;
+        if fov_state.last_player_pos != Some(current_pos) {
+            fov_state.needs_recalculation = true;
             // Note: last_player_pos is updated in calculate_fov after processing
         }
     }
 }
 
+// Turn the live map's tile edits into MapChanged events; reads the map without marking it changed
+pub fn report_map_edits(
+    map: Option<ResMut<GameMap>>,
+    mut map_changed_events: EventWriter<MapChanged>,
+) {
+    let Some(mut map) = map else { return; };
+    if !map.is_changed() {
+        return;
+    }
+    for pos in map.bypass_change_detection().take_edits() {
+        map_changed_events.write(MapChanged { pos });
+    }
+}
+
+// Recalculate FOV when a tile changes in place, dropping only the LOS entries it could affect
+pub fn handle_map_changes(
+    mut map_changed_events: EventReader<MapChanged>,
+    mut fov_state: ResMut<FovState>,
+    mut los_cache: ResMut<LosCache>,
+) {
+    let changed: Vec<(u32, u32)> = map_changed_events.read().map(|event| event.pos).collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    let before = los_cache.entries.len();
+    los_cache.entries.retain(|&(x0, y0, x1, y1), _| {
+        !changed.iter().any(|&pos| line_passes_near(x0 as i32, y0 as i32, x1 as i32, y1 as i32, pos))
+    });
+    println!("Map changed at {} tile(s): invalidated {} LOS cache entries", changed.len(), before - los_cache.entries.len());
+
+    fov_state.needs_recalculation = true;
+}
+
+// Whether the Bresenham line from (x0, y0) to (x1, y1) passes within one tile of `pos`
+fn line_passes_near(x0: i32, y0: i32, x1: i32, y1: i32, pos: (u32, u32)) -> bool {
+    let (px, py) = (pos.0 as i32, pos.1 as i32);
+
+    // Cheap reject: pos is outside the line's padded bounding box
+    if px < x0.min(x1) - 1 || px > x0.max(x1) + 1 || py < y0.min(y1) - 1 || py > y0.max(y1) + 1 {
+        return false;
+    }
+
+    let mut x = x0;
+    let mut y = y0;
+
+    let dx = (x1 - x0).abs();
+    let dy = (y1 - y0).abs();
+
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+
+    let mut err = dx - dy;
+
+    loop {
+        if (x - px).abs() <= 1 && (y - py).abs() <= 1 {
+            return true;
+        }
+
+        if x == x1 && y == y1 { break; }
+
+        let e2 = 2 * err;
+        if e2 > -dy {
+            err -= dy;
+            x += sx;
+        }
+        if e2 < dx {
+            err += dx;
+            y += sy;
+        }
+    }
+
+    false
+}
+
 // Condition function to check if FOV needs recalculation
 pub fn should_recalculate_fov(
-    fov_settings: Res<FovSettings>,
+    fov_state: Res<FovState>,
     map: Option<Res<GameMap>>,
 ) -> bool {
-    map.is_some() && (
-        fov_settings.needs_recalculation || 
-        (fov_settings.debug_reveal_all && !fov_settings.debug_mode_applied)
-    )
+    map.is_some() && fov_state.needs_recalculation
+}
+
+// Debug reveal-all: light every tile once when turned on; calculate_fov takes over again when it's off
+pub fn apply_debug_reveal(
+    fov_config: Res<FovConfig>,
+    mut fov_state: ResMut<FovState>,
+    mut control: TileVisibilityControl,
+) {
+    if fov_config.debug_reveal_all && !fov_state.debug_mode_applied {
+        control.reveal_all();
+        fov_state.debug_mode_applied = true;
+        fov_state.needs_recalculation = false;
+    }
 }
 
 // Simple FOV calculation using basic line-of-sight
 pub fn calculate_fov(
     player_query: Query<&Player>,
     map: Res<GameMap>,
-    mut fov_settings: ResMut<FovSettings>,
+    fov_config: Res<FovConfig>,
+    mut fov_state: ResMut<FovState>,
+    mut los_cache: ResMut<LosCache>,
     mut tile_query: Query<(&TilePos, &mut TileVisibilityState)>,
+    mut discovered_events: EventWriter<TileDiscovered>,
+    mut visible_tiles: ResMut<VisibleTiles>,
+    mut metrics: ResMut<PerfMetrics>,
 ) {
     let Ok(player) = player_query.single() else { return; };
+    let started = PerfMetrics::now();
 
-    // If debug mode is on, reveal all tiles (only once)
-    if fov_settings.debug_reveal_all {
-        if !fov_settings.debug_mode_applied {
-            for (_, mut visibility_state) in tile_query.iter_mut() {
-                visibility_state.visibility = TileVisibility::Visible;
-            }
-            fov_settings.debug_mode_applied = true;
-        }
-        fov_settings.needs_recalculation = false;
-        fov_settings.last_player_pos = Some((player.x, player.y));
+    // Debug reveal owns visibility while it's on (apply_debug_reveal lit everything)
+    if fov_config.debug_reveal_all {
+        fov_state.needs_recalculation = false;
+        fov_state.last_player_pos = Some((player.x, player.y));
         return;
     }
 
     // Reset debug mode tracking when not in debug mode
-    if fov_settings.debug_mode_applied {
-        fov_settings.debug_mode_applied = false;
+    if fov_state.debug_mode_applied {
+        fov_state.debug_mode_applied = false;
     }
 
     let player_x = player.x as i32;
     let player_y = player.y as i32;
     let current_pos = (player.x, player.y);
-    let radius = fov_settings.radius as i32;
-    let radius_squared = radius.pow(2);
+    let radius = fov_config.effective_radius() as i32;
+    let shape = fov_config.shape;
+    let always_reveal_adjacent = fov_config.always_reveal_adjacent;
+    // Adjacent tiles can be revealed even when the configured radius is smaller than 1
+    let reach = if always_reveal_adjacent { radius.max(1) } else { radius };
 
     // Check if we can use incremental update (player moved, not initial/forced recalc)
-    let use_incremental = fov_settings.last_player_pos.is_some()
-        && fov_settings.last_player_pos != Some(current_pos);
+    let use_incremental = fov_state.last_player_pos.is_some()
+        && fov_state.last_player_pos != Some(current_pos);
 
     if use_incremental {
         // INCREMENTAL UPDATE: Only process tiles in union of old and new visible regions
-        let (old_x, old_y) = fov_settings.last_player_pos.unwrap();
-        fov_settings.dirty_tiles.clear();
+        let (old_x, old_y) = fov_state.last_player_pos.unwrap();
+        fov_state.dirty_tiles.clear();
 
         // Calculate bounding box of union region
-        let min_x = (old_x as i32 - radius).max(0).min((player_x - radius).max(0));
-        let max_x = ((old_x as i32 + radius).min(map.width as i32 - 1))
-            .max((player_x + radius).min(map.width as i32 - 1));
-        let min_y = (old_y as i32 - radius).max(0).min((player_y - radius).max(0));
-        let max_y = ((old_y as i32 + radius).min(map.height as i32 - 1))
-            .max((player_y + radius).min(map.height as i32 - 1));
+        let min_x = (old_x as i32 - reach).max(0).min((player_x - reach).max(0));
+        let max_x = ((old_x as i32 + reach).min(map.width as i32 - 1))
+            .max((player_x + reach).min(map.width as i32 - 1));
+        let min_y = (old_y as i32 - reach).max(0).min((player_y - reach).max(0));
+        let max_y = ((old_y as i32 + reach).min(map.height as i32 - 1))
+            .max((player_y + reach).min(map.height as i32 - 1));
 
         // Only update tiles in the dirty region
         for (tile_pos, mut visibility_state) in tile_query.iter_mut() {
@@ -107,13 +280,16 @@ pub fn calculate_fov(
                 continue;
             }
 
-            // Calculate distance squared from current player position
-            let distance_squared = (tile_x - player_x).pow(2) + (tile_y - player_y).pow(2);
+            // Distance from current player position, measured per the configured shape
+            let in_range = shape.in_range(tile_x - player_x, tile_y - player_y, radius);
 
-            if distance_squared <= radius_squared {
+            if always_reveal_adjacent && is_adjacent(player_x, player_y, tile_x, tile_y) {
+                // The player's own tile and its 8 neighbors are always visible
+                reveal_tile(&mut visibility_state, tile_pos, &map, &mut discovered_events);
+            } else if in_range {
                 // Check line of sight from player to tile (cached)
-                if has_line_of_sight_cached(&map, player_x, player_y, tile_x, tile_y, &mut fov_settings) {
-                    visibility_state.visibility = TileVisibility::Visible;
+                if has_line_of_sight_cached(&map, player_x, player_y, tile_x, tile_y, &mut los_cache) {
+                    reveal_tile(&mut visibility_state, tile_pos, &map, &mut discovered_events);
                 } else {
                     // If tile was visible, make it seen; don't change unseen tiles
                     if visibility_state.visibility == TileVisibility::Visible {
@@ -126,20 +302,26 @@ pub fn calculate_fov(
                     visibility_state.visibility = TileVisibility::Seen;
                 }
             }
+
+            visible_tiles.sync(tile_pos.x, tile_pos.y, visibility_state.visibility);
         }
     } else {
         // FULL UPDATE: Initial calculation or forced recalculation
+        visible_tiles.0.clear();
         for (tile_pos, mut visibility_state) in tile_query.iter_mut() {
             let tile_x = tile_pos.x as i32;
             let tile_y = tile_pos.y as i32;
 
-            // Calculate distance squared (avoid sqrt for performance)
-            let distance_squared = (tile_x - player_x).pow(2) + (tile_y - player_y).pow(2);
+            // Distance check per the configured shape (squared for circles, no sqrt)
+            let in_range = shape.in_range(tile_x - player_x, tile_y - player_y, radius);
 
-            if distance_squared <= radius_squared {
+            if always_reveal_adjacent && is_adjacent(player_x, player_y, tile_x, tile_y) {
+                // The player's own tile and its 8 neighbors are always visible
+                reveal_tile(&mut visibility_state, tile_pos, &map, &mut discovered_events);
+            } else if in_range {
                 // Check line of sight from player to tile (cached)
-                if has_line_of_sight_cached(&map, player_x, player_y, tile_x, tile_y, &mut fov_settings) {
-                    visibility_state.visibility = TileVisibility::Visible;
+                if has_line_of_sight_cached(&map, player_x, player_y, tile_x, tile_y, &mut los_cache) {
+                    reveal_tile(&mut visibility_state, tile_pos, &map, &mut discovered_events);
                 } else {
                     // If tile was visible, make it seen; don't change unseen tiles
                     if visibility_state.visibility == TileVisibility::Visible {
@@ -152,12 +334,59 @@ pub fn calculate_fov(
                     visibility_state.visibility = TileVisibility::Seen;
                 }
             }
+
+            visible_tiles.sync(tile_pos.x, tile_pos.y, visibility_state.visibility);
         }
     }
 
     // Update last player position and mark recalculation complete
-    fov_settings.last_player_pos = Some(current_pos);
-    fov_settings.needs_recalculation = false;
+    fov_state.last_player_pos = Some(current_pos);
+    fov_state.needs_recalculation = false;
+    metrics.record(if use_incremental { PerfStat::FovIncremental } else { PerfStat::FovFull }, started);
+}
+
+// ECS-free FOV query: every tile visible from `origin` under the given settings (bypasses the LOS cache).
+// The game itself goes through calculate_fov; this serves the headless simulation and benches
+pub fn visible_tiles_from(map: &GameMap, origin: (u32, u32), fov_config: &FovConfig) -> Vec<(u32, u32)> {
+    let origin_x = origin.0 as i32;
+    let origin_y = origin.1 as i32;
+    let radius = fov_config.effective_radius() as i32;
+    let reach = if fov_config.always_reveal_adjacent { radius.max(1) } else { radius };
+
+    let mut visible = Vec::new();
+    for y in (origin_y - reach).max(0)..=(origin_y + reach).min(map.height as i32 - 1) {
+        for x in (origin_x - reach).max(0)..=(origin_x + reach).min(map.width as i32 - 1) {
+            let adjacent = fov_config.always_reveal_adjacent && is_adjacent(origin_x, origin_y, x, y);
+            let in_range = fov_config.shape.in_range(x - origin_x, y - origin_y, radius);
+
+            if adjacent || (in_range && has_line_of_sight(map, origin_x, origin_y, x, y)) {
+                visible.push((x as u32, y as u32));
+            }
+        }
+    }
+    visible
+}
+
+// Whether a tile is the player's own tile or one of its 8 neighbors
+#[inline]
+fn is_adjacent(player_x: i32, player_y: i32, tile_x: i32, tile_y: i32) -> bool {
+    (tile_x - player_x).abs() <= 1 && (tile_y - player_y).abs() <= 1
+}
+
+// Mark a tile visible, reporting it as discovered if it had never been seen before
+fn reveal_tile(
+    visibility_state: &mut TileVisibilityState,
+    tile_pos: &TilePos,
+    map: &GameMap,
+    discovered_events: &mut EventWriter<TileDiscovered>,
+) {
+    if visibility_state.visibility == TileVisibility::Unseen {
+        discovered_events.write(TileDiscovered {
+            pos: (tile_pos.x, tile_pos.y),
+            tile_type: map.get(tile_pos.x, tile_pos.y),
+        });
+    }
+    visibility_state.visibility = TileVisibility::Visible;
 }
 
 // Cached line-of-sight check with symmetric caching (A→B = B→A)
@@ -167,7 +396,7 @@ fn has_line_of_sight_cached(
     y0: i32,
     x1: i32,
     y1: i32,
-    fov_settings: &mut FovSettings,
+    los_cache: &mut LosCache,
 ) -> bool {
     // Create normalized cache key (smaller coords first for symmetry)
     let cache_key = if (x0, y0) < (x1, y1) {
@@ -177,17 +406,17 @@ fn has_line_of_sight_cached(
     };
 
     // Check cache first
-    if let Some(&result) = fov_settings.los_cache.get(&cache_key) {
-        fov_settings.cache_hits += 1;
+    if let Some(&result) = los_cache.entries.get(&cache_key) {
+        los_cache.hits += 1;
         return result;
     }
 
     // Cache miss - calculate LOS
-    fov_settings.cache_misses += 1;
+    los_cache.misses += 1;
     let result = has_line_of_sight(map, x0, y0, x1, y1);
 
     // Store in cache
-    fov_settings.los_cache.insert(cache_key, result);
+    los_cache.entries.insert(cache_key, result);
 
     result
 }
@@ -233,41 +462,209 @@ fn has_line_of_sight(map: &GameMap, x0: i32, y0: i32, x1: i32, y1: i32) -> bool
 }
 
 pub fn update_tile_visibility(
-    mut tile_query: Query<(&mut TileColor, &TileVisibilityState), Changed<TileVisibilityState>>,
+    time: Res<Time>,
+    mut tile_query: Query<(&mut TileColor, &TilePos, &MapTile, Ref<TileVisibilityState>)>,
+    changed_tiles: Query<(), Changed<TileVisibilityState>>,
+    player_query: Query<&Player>,
     current_level: Res<CurrentLevel>,
+    accessibility: Res<AccessibilitySettings>,
+    fov_config: Res<FovConfig>,
+    mut memory_fade: ResMut<MemoryFadeSettings>,
+    mut last_falloff_pos: Local<Option<(u32, u32)>>,
 ) {
-    // Get biome-specific color tint
-    let biome_tint = get_biome_color_tint(current_level.biome);
-    
-    for (mut tile_color, visibility_state) in tile_query.iter_mut() {
-        match visibility_state.visibility {
-            TileVisibility::Unseen => {
-                // Completely dark/invisible
-                tile_color.0 = Color::srgb(0.0, 0.0, 0.0);
-            },
+    // The biome's tint; every tile in a state shares one color unless memory fade or edge falloff varies it
+    let biome_tint = current_level.biome.get_config().tint();
+    let colors = VisibilityColors::for_settings(biome_tint, &accessibility);
+    let player_pos = player_query.single().ok().map(|player| (player.x, player.y));
+
+    // Changed tiles always recolor; all Seen tiles re-fade on a throttle once the player has moved
+    memory_fade.refresh_timer.tick(time.delta());
+    let moved = memory_fade.enabled && player_pos != memory_fade.last_refresh_pos;
+    let refresh_all = memory_fade.needs_refresh || (moved && memory_fade.refresh_timer.just_finished());
+    if refresh_all {
+        memory_fade.needs_refresh = false;
+        memory_fade.last_refresh_pos = player_pos;
+    }
+
+    // Switching palettes recolors every tile
+    let restyle = accessibility.is_changed();
+    // Edge falloff depends on the player's position, so every lit tile re-fades when they move
+    let falloff = fov_config.edge_falloff > 0.0;
+    let refresh_visible = fov_config.is_changed() || (falloff && player_pos != *last_falloff_pos);
+    *last_falloff_pos = player_pos;
+
+    // Most frames nothing changed and no refresh is due, so skip the walk over every tile
+    if !refresh_all && !refresh_visible && !restyle && changed_tiles.is_empty() {
+        return;
+    }
+
+    for (mut tile_color, tile_pos, map_tile, visibility_state) in tile_query.iter_mut() {
+        let refresh_seen = refresh_all && visibility_state.visibility == TileVisibility::Seen;
+        let refresh_lit = refresh_visible && visibility_state.visibility == TileVisibility::Visible;
+        if !visibility_state.is_changed() && !refresh_seen && !refresh_lit && !restyle {
+            continue;
+        }
+
+        let is_stair = matches!(map_tile.tile_type, TileType::StairUp | TileType::StairDown);
+        tile_color.0 = match visibility_state.visibility {
+            TileVisibility::Unseen => colors.unseen,
+            TileVisibility::Seen if is_stair && colors.high_contrast => HIGH_CONTRAST_STAIR_SEEN,
             TileVisibility::Seen => {
-                // Darkened/grayed out for memory, with biome tint
-                let base_color = Color::srgb(0.3, 0.3, 0.4);
-                tile_color.0 = apply_color_tint(base_color, biome_tint, 0.4);
+                // Only a faded tile needs its own blend; high contrast never fades so Seen stays distinct
+                let fade = memory_fade_factor(&memory_fade, player_pos, tile_pos);
+                if fade >= 1.0 || colors.high_contrast { colors.seen } else { seen_color(biome_tint, fade) }
             },
-            TileVisibility::Visible => {
-                // Full visibility with biome tint
-                tile_color.0 = apply_color_tint(Color::WHITE, biome_tint, 0.6);
+            TileVisibility::Visible if is_stair && colors.high_contrast => HIGH_CONTRAST_STAIR_VISIBLE,
+            TileVisibility::Visible => match player_pos {
+                Some((px, py)) if falloff => {
+                    let alpha = fov_config.edge_alpha(tile_pos.x as i32 - px as i32, tile_pos.y as i32 - py as i32);
+                    colors.visible.with_alpha(alpha)
+                }
+                _ => colors.visible,
             },
+        };
+    }
+}
+
+// High-contrast palette: black, a saturated blue well clear of black, and near-white with a warm cast
+const HIGH_CONTRAST_SEEN: Color = Color::srgb(0.25, 0.55, 1.0);
+const HIGH_CONTRAST_VISIBLE: Color = Color::srgb(1.0, 1.0, 0.85);
+// Stairs keep a hue no other tile uses, so they stand out from both floor states
+const HIGH_CONTRAST_STAIR_SEEN: Color = Color::srgb(1.0, 0.45, 0.0);
+const HIGH_CONTRAST_STAIR_VISIBLE: Color = Color::srgb(1.0, 0.75, 0.2);
+
+// Tile colors for each visibility state under one biome tint, blended once per frame instead of per tile
+struct VisibilityColors {
+    unseen: Color,
+    seen: Color,
+    visible: Color,
+    high_contrast: bool,
+}
+
+impl VisibilityColors {
+    fn for_tint(biome_tint: FovTint) -> Self {
+        Self {
+            // Completely dark/invisible
+            unseen: Color::srgb(0.0, 0.0, 0.0),
+            seen: seen_color(biome_tint, 1.0),
+            // Full visibility with biome tint
+            visible: apply_color_tint(Color::WHITE, biome_tint.color, biome_tint.visible_intensity),
+            high_contrast: false,
+        }
+    }
+
+    // The high-contrast palette overrides the biome tint entirely
+    fn for_settings(biome_tint: FovTint, accessibility: &AccessibilitySettings) -> Self {
+        if !accessibility.high_contrast {
+            return Self::for_tint(biome_tint);
+        }
+        Self {
+            unseen: Color::srgb(0.0, 0.0, 0.0),
+            seen: HIGH_CONTRAST_SEEN,
+            visible: HIGH_CONTRAST_VISIBLE,
+            high_contrast: true,
+        }
+    }
+}
+
+// Darkened/grayed out for memory, with biome tint; `fade` below 1.0 darkens further with distance
+fn seen_color(biome_tint: FovTint, fade: f32) -> Color {
+    let base_color = Color::srgb(0.3 * fade, 0.3 * fade, 0.4 * fade);
+    apply_color_tint(base_color, biome_tint.color, biome_tint.seen_intensity)
+}
+
+// Accumulate seen tiles; rebuilt from scratch whenever the tilemap is respawned for a new or restored level,
+// otherwise only the tiles whose visibility changed this frame are looked at
+pub fn update_explored_tiles(
+    mut explored: ResMut<ExploredTiles>,
+    tile_index: Res<TileIndex>,
+    tile_query: Query<(&TilePos, &TileVisibilityState)>,
+    changed_query: Query<(&TilePos, &TileVisibilityState), Changed<TileVisibilityState>>,
+) {
+    let seen = |(tile_pos, visibility_state): (&TilePos, &TileVisibilityState)| {
+        (visibility_state.visibility != TileVisibility::Unseen).then_some((tile_pos.x, tile_pos.y))
+    };
+    if tile_index.is_changed() {
+        explored.0.clear();
+        explored.0.extend(tile_query.iter().filter_map(seen));
+    } else {
+        let newly_seen: Vec<(u32, u32)> = changed_query.iter().filter_map(seen).filter(|&(x, y)| !explored.contains(x, y)).collect();
+        // Leave the resource untouched on quiet frames so readers can rely on change detection
+        if !newly_seen.is_empty() {
+            explored.0.extend(newly_seen);
+        }
+    }
+}
+
+// Keep the landmark memory in step with tile memory: rebuilt whenever the tilemap is respawned
+// (so levels restored from LevelMaps keep their remembered stairs), then updated from discoveries and edits
+pub fn update_remembered_features(
+    mut remembered: ResMut<RememberedFeatures>,
+    mut discovered_events: EventReader<TileDiscovered>,
+    mut map_changed_events: EventReader<MapChanged>,
+    tile_index: Res<TileIndex>,
+    tile_query: Query<(&MapTile, &TileVisibilityState)>,
+    map: Option<Res<GameMap>>,
+) {
+    if tile_index.is_changed() {
+        remembered.features.clear();
+        for (&pos, &entity) in tile_index.tiles.iter() {
+            if let Ok((map_tile, visibility_state)) = tile_query.get(entity) {
+                if map_tile.tile_type.is_landmark() && visibility_state.visibility != TileVisibility::Unseen {
+                    remembered.features.insert(pos, map_tile.tile_type);
+                }
+            }
+        }
+    }
+
+    for event in discovered_events.read() {
+        if event.tile_type.is_landmark() {
+            remembered.features.insert(event.pos, event.tile_type);
+        }
+    }
+
+    // A picked-up item (or any other edit) replaces what the player remembers there
+    let Some(map) = map else { return; };
+    for event in map_changed_events.read() {
+        let (x, y) = event.pos;
+        if let Entry::Occupied(mut feature) = remembered.features.entry((x, y)) {
+            let tile_type = map.get(x, y);
+            if tile_type.is_landmark() {
+                feature.insert(tile_type);
+            } else {
+                feature.remove();
+            }
         }
     }
 }
 
-// Helper function to get biome-specific color tint
-fn get_biome_color_tint(biome: BiomeType) -> Color {
-    match biome {
-        BiomeType::CinderGaol => Color::srgb(1.3, 0.7, 0.7), // Red tint for fire/prison theme
-        BiomeType::NetherGrange => Color::srgb(1.4, 0.6, 0.4), // Orange-red for hellish landscape
-        BiomeType::Underglade => Color::srgb(0.8, 1.2, 0.9), // Green tint for lush biome
-        BiomeType::FungalDeep => Color::srgb(0.9, 0.8, 1.3), // Purple tint for spores
-        BiomeType::AbyssalHold => Color::srgb(0.7, 0.7, 1.2), // Blue tint for dark waters
-        BiomeType::StygianPool => Color::srgb(0.6, 0.8, 1.3), // Cyan tint for underground lake
-        _ => Color::WHITE, // No tint for other biomes
+// Brightness multiplier for a remembered tile: 1.0 next to the player down to min_brightness at fade_distance
+fn memory_fade_factor(settings: &MemoryFadeSettings, player_pos: Option<(u32, u32)>, tile_pos: &TilePos) -> f32 {
+    let Some((px, py)) = player_pos else {
+        return 1.0;
+    };
+    if !settings.enabled {
+        return 1.0;
+    }
+
+    let dx = tile_pos.x as f32 - px as f32;
+    let dy = tile_pos.y as f32 - py as f32;
+    let t = ((dx * dx + dy * dy).sqrt() / settings.fade_distance).clamp(0.0, 1.0);
+    1.0 - t * (1.0 - settings.min_brightness)
+}
+
+// Announce notable tiles the first time they come into view
+pub fn announce_discoveries(
+    mut discovered_events: EventReader<TileDiscovered>,
+) {
+    for event in discovered_events.read() {
+        match event.tile_type {
+            TileType::StairUp => println!("You discover the stairs up at ({}, {})!", event.pos.0, event.pos.1),
+            TileType::StairDown => println!("You discover the stairs down at ({}, {})!", event.pos.0, event.pos.1),
+            TileType::Altar => println!("You discover the altar at ({}, {})!", event.pos.0, event.pos.1),
+            TileType::Trapdoor => println!("You discover a trapdoor at ({}, {})", event.pos.0, event.pos.1),
+            _ => {}
+        }
     }
 }
 
@@ -286,29 +683,568 @@ fn apply_color_tint(base_color: Color, tint: Color, intensity: f32) -> Color {
 
 pub fn handle_fov_debug_controls(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut fov_settings: ResMut<FovSettings>,
+    mut fov_config: ResMut<FovConfig>,
+    mut fov_state: ResMut<FovState>,
+    los_cache: Res<LosCache>,
+    mut memory_fade: ResMut<MemoryFadeSettings>,
 ) {
     if keyboard_input.just_pressed(KeyCode::KeyO) &&
        (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
-        fov_settings.debug_reveal_all = !fov_settings.debug_reveal_all;
-        fov_settings.debug_mode_applied = false; // Reset flag to trigger recalculation
-        fov_settings.needs_recalculation = true;
-        println!("FOV debug reveal: {}", if fov_settings.debug_reveal_all { "ON" } else { "OFF" });
+        fov_config.debug_reveal_all = !fov_config.debug_reveal_all;
+        fov_state.debug_mode_applied = false; // Reset flag to trigger recalculation
+        fov_state.needs_recalculation = true;
+        println!("FOV debug reveal: {}", if fov_config.debug_reveal_all { "ON" } else { "OFF" });
+    }
+
+    // Toggle the soft fade at the edge of the lit area
+    if keyboard_input.just_pressed(KeyCode::KeyF) &&
+       (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
+        fov_config.edge_falloff = if fov_config.edge_falloff > 0.0 { 0.0 } else { 0.3 };
+        println!("FOV edge falloff: {}", if fov_config.edge_falloff > 0.0 { "ON" } else { "OFF" });
+    }
+
+    // Toggle distance fade on remembered tiles
+    if keyboard_input.just_pressed(KeyCode::KeyM) &&
+       (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
+        memory_fade.enabled = !memory_fade.enabled;
+        memory_fade.needs_refresh = true; // Recolor every Seen tile with the new setting
+        println!("Memory fade: {}", if memory_fade.enabled { "ON" } else { "OFF" });
     }
 
     // Show LOS cache statistics
     if keyboard_input.just_pressed(KeyCode::KeyL) &&
        (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
-        let total = fov_settings.cache_hits + fov_settings.cache_misses;
-        if total > 0 {
-            let hit_rate = fov_settings.cache_hits as f32 / total as f32 * 100.0;
+        if let Some(hit_rate) = los_cache.hit_rate() {
             println!("LOS Cache Stats:");
-            println!("  Cache size: {} entries", fov_settings.los_cache.len());
-            println!("  Hits: {}, Misses: {}", fov_settings.cache_hits, fov_settings.cache_misses);
+            println!("  Cache size: {} entries", los_cache.entries.len());
+            println!("  Hits: {}, Misses: {}", los_cache.hits, los_cache.misses);
             println!("  Hit rate: {:.1}%", hit_rate);
-            println!("  Memory usage: ~{} KB", fov_settings.los_cache.len() * std::mem::size_of::<((u32, u32, u32, u32), bool)>() / 1024);
+            println!("  Memory usage: ~{} KB", los_cache.entries.len() * std::mem::size_of::<((u32, u32, u32, u32), bool)>() / 1024);
         } else {
             println!("No LOS cache statistics available yet");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biome::BiomeType;
+    use crate::metrics::PerfMetrics;
+    use crate::components::{LosEntries, SavedLosCaches};
+    use crate::components::FovShape;
+
+    // A 3x1 strip of Unseen floor tiles with everything update_tile_visibility reads
+    fn tile_world() -> World {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<AccessibilitySettings>();
+        world.init_resource::<FovConfig>();
+        world.init_resource::<MemoryFadeSettings>();
+        world.init_resource::<VisibleTiles>();
+        world.init_resource::<ExploredTiles>();
+        world.init_resource::<RememberedFeatures>();
+
+        let mut index = TileIndex::default();
+        for x in 0..3 {
+            let entity = world.spawn((
+                TileColor::default(),
+                TilePos { x, y: 0 },
+                MapTile { tile_type: TileType::Floor },
+                TileVisibilityState { visibility: TileVisibility::Unseen },
+            )).id();
+            index.insert(x, 0, entity);
+        }
+        world.insert_resource(index);
+        world
+    }
+
+    fn tile_color(world: &mut World, x: u32) -> Color {
+        let entity = world.resource::<TileIndex>().tiles[&(x, 0)];
+        world.get::<TileColor>(entity).unwrap().0
+    }
+
+    #[test]
+    fn forcing_a_tile_visible_recolors_it() {
+        let mut world = tile_world();
+        let recolor = world.register_system(update_tile_visibility);
+        world.run_system(recolor).unwrap();
+        let unseen = tile_color(&mut world, 1);
+
+        let reveal = world.register_system(|mut control: TileVisibilityControl| {
+            assert!(control.set_tile_visibility((1, 0), TileVisibility::Visible));
+            assert!(!control.set_tile_visibility((9, 9), TileVisibility::Visible), "no tile there");
+        });
+        world.run_system(reveal).unwrap();
+        world.run_system(recolor).unwrap();
+
+        let tint = BiomeType::Caverns.get_config().tint();
+        assert_eq!(tile_color(&mut world, 1), VisibilityColors::for_tint(tint).visible);
+        assert_eq!(tile_color(&mut world, 0), unseen, "untouched tiles keep their color");
+        assert!(world.resource::<VisibleTiles>().contains(1, 0));
+    }
+
+    #[test]
+    fn hide_all_resets_every_tile_to_unseen() {
+        let mut world = tile_world();
+        world.resource_mut::<ExploredTiles>().0.insert((2, 0));
+        world.resource_mut::<RememberedFeatures>().features.insert((2, 0), TileType::StairDown);
+
+        let reveal_then_hide = world.register_system(|mut control: TileVisibilityControl| {
+            control.reveal_all();
+            control.hide_all();
+        });
+        world.run_system(reveal_then_hide).unwrap();
+
+        let mut states = world.query::<&TileVisibilityState>();
+        assert!(states.iter(&world).all(|state| state.visibility == TileVisibility::Unseen));
+        assert!(world.resource::<VisibleTiles>().0.is_empty());
+        assert!(world.resource::<ExploredTiles>().0.is_empty());
+        assert!(world.resource::<RememberedFeatures>().features.is_empty());
+    }
+
+    #[test]
+    fn explored_set_only_grows_as_the_player_walks() {
+        let mut map = GameMap::new(40, 7);
+        map.fill_rect(URect::new(1, 1, 39, 6), TileType::Floor);
+
+        let mut world = World::new();
+        world.init_resource::<FovConfig>();
+        world.init_resource::<FovState>();
+        world.init_resource::<LosCache>();
+        world.init_resource::<PerfMetrics>();
+        world.init_resource::<VisibleTiles>();
+        world.init_resource::<ExploredTiles>();
+        world.init_resource::<Events<TileDiscovered>>();
+        let mut index = TileIndex::default();
+        for (x, y, _) in map.iter_tiles() {
+            let entity = world.spawn((TilePos { x, y }, TileVisibilityState { visibility: TileVisibility::Unseen })).id();
+            index.insert(x, y, entity);
+        }
+        world.insert_resource(index);
+        world.insert_resource(map);
+        let player = world.spawn(Player { x: 2, y: 3 }).id();
+
+        let step = world.register_system(|world: &mut World| {
+            let _ = world.run_system_cached(detect_player_movement);
+            let _ = world.run_system_cached(calculate_fov);
+            let _ = world.run_system_cached(update_explored_tiles);
+        });
+
+        let mut previous = std::collections::HashSet::new();
+        for x in 2..38 {
+            world.get_mut::<Player>(player).unwrap().x = x;
+            world.run_system(step).unwrap();
+
+            let explored = world.resource::<ExploredTiles>();
+            assert!(previous.is_subset(&explored.0), "a tile dropped out of the explored set at x = {}", x);
+            let (seen, in_view) = explored.split(world.resource::<VisibleTiles>());
+            assert_eq!(seen.len() + in_view.len(), explored.len());
+            assert!(in_view.iter().all(|&(x, y)| world.resource::<VisibleTiles>().contains(x, y)));
+            previous = explored.0.clone();
+        }
+        assert!(previous.contains(&(38, 3)), "walking the room explores its far end");
+    }
+
+    #[test]
+    fn discovered_stairs_stay_remembered_after_the_player_walks_away() {
+        let mut map = GameMap::new(60, 7);
+        map.fill_rect(URect::new(1, 1, 59, 6), TileType::Floor);
+        map.set(3, 3, TileType::StairDown);
+
+        let mut world = World::new();
+        world.init_resource::<FovConfig>();
+        world.init_resource::<FovState>();
+        world.init_resource::<LosCache>();
+        world.init_resource::<PerfMetrics>();
+        world.init_resource::<VisibleTiles>();
+        world.init_resource::<RememberedFeatures>();
+        world.init_resource::<Events<TileDiscovered>>();
+        world.init_resource::<Events<MapChanged>>();
+        let mut index = TileIndex::default();
+        for (x, y, tile_type) in map.iter_tiles() {
+            let entity = world.spawn((
+                TilePos { x, y },
+                MapTile { tile_type },
+                TileVisibilityState { visibility: TileVisibility::Unseen },
+            )).id();
+            index.insert(x, y, entity);
+        }
+        world.insert_resource(index);
+        world.insert_resource(map);
+        let player = world.spawn(Player { x: 4, y: 3 }).id();
+
+        let step = world.register_system(|world: &mut World| {
+            let _ = world.run_system_cached(detect_player_movement);
+            let _ = world.run_system_cached(calculate_fov);
+            let _ = world.run_system_cached(update_remembered_features);
+        });
+        let stair_visibility = |world: &mut World| {
+            let entity = world.resource::<TileIndex>().tiles[&(3, 3)];
+            world.get::<TileVisibilityState>(entity).unwrap().visibility
+        };
+
+        world.run_system(step).unwrap();
+        assert_eq!(stair_visibility(&mut world), TileVisibility::Visible);
+        assert_eq!(world.resource::<RememberedFeatures>().get(3, 3), Some(TileType::StairDown));
+
+        for x in 5..57 {
+            world.get_mut::<Player>(player).unwrap().x = x;
+            world.run_system(step).unwrap();
+        }
+        assert_eq!(stair_visibility(&mut world), TileVisibility::Seen, "the stairs are out of view");
+        let remembered = world.resource::<RememberedFeatures>();
+        assert_eq!(remembered.get(3, 3), Some(TileType::StairDown));
+        assert_eq!(remembered.positions_of(TileType::StairDown).collect::<Vec<_>>(), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn recoloring_skips_frames_where_nothing_changed() {
+        let mut world = tile_world();
+        let recolor = world.register_system(update_tile_visibility);
+        world.run_system(recolor).unwrap();
+
+        // A frame with no visibility change and no refresh due leaves colors alone
+        let entity = world.resource::<TileIndex>().tiles[&(0, 0)];
+        world.get_mut::<TileColor>(entity).unwrap().0 = Color::WHITE;
+        world.run_system(recolor).unwrap();
+        assert_eq!(tile_color(&mut world, 0), Color::WHITE);
+
+        // A changed tile is picked up on the next frame
+        world.get_mut::<TileVisibilityState>(entity).unwrap().visibility = TileVisibility::Unseen;
+        world.run_system(recolor).unwrap();
+        assert_eq!(tile_color(&mut world, 0), Color::srgb(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn opening_a_wall_reveals_the_room_behind_it_without_moving() {
+        // Two rooms split by a wall at x = 10; the player stands in the left one
+        let mut map = GameMap::new(20, 7);
+        map.fill_rect(URect::new(1, 1, 19, 6), TileType::Floor);
+        map.fill_rect(URect::new(10, 1, 11, 6), TileType::Wall);
+        map.take_edits();
+
+        let mut world = World::new();
+        world.init_resource::<FovConfig>();
+        world.init_resource::<FovState>();
+        world.init_resource::<LosCache>();
+        world.init_resource::<PerfMetrics>();
+        world.init_resource::<VisibleTiles>();
+        world.init_resource::<Events<TileDiscovered>>();
+        world.init_resource::<Events<MapChanged>>();
+        let mut index = TileIndex::default();
+        for (x, y, _) in map.iter_tiles() {
+            let entity = world.spawn((TilePos { x, y }, TileVisibilityState { visibility: TileVisibility::Unseen })).id();
+            index.insert(x, y, entity);
+        }
+        world.insert_resource(index);
+        world.insert_resource(map);
+        world.spawn(Player { x: 7, y: 3 });
+
+        let step = world.register_system(|world: &mut World| {
+            let _ = world.run_system_cached(detect_player_movement);
+            let _ = world.run_system_cached(report_map_edits);
+            let _ = world.run_system_cached(handle_map_changes);
+            if world.run_system_cached(should_recalculate_fov).unwrap() {
+                let _ = world.run_system_cached(calculate_fov);
+            }
+        });
+        let behind_visible = |world: &World| world.resource::<VisibleTiles>().contains(12, 3);
+
+        world.run_system(step).unwrap();
+        assert!(!behind_visible(&world), "the wall blocks the view");
+        world.run_system(step).unwrap();
+        assert!(!world.resource::<FovState>().needs_recalculation, "nothing moved or changed");
+
+        world.resource_mut::<GameMap>().set(10, 3, TileType::Floor);
+        world.run_system(step).unwrap();
+        assert!(behind_visible(&world), "the opened doorway lights the tiles behind it");
+        assert!(world.resource_mut::<GameMap>().take_edits().is_empty(), "edits are reported once");
+    }
+
+    // The resources and tiles calculate_fov needs, with the player at `player`
+    fn fov_world(map: GameMap, fov_config: FovConfig, player: (u32, u32)) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(fov_config);
+        world.init_resource::<FovState>();
+        world.init_resource::<LosCache>();
+        world.init_resource::<PerfMetrics>();
+        world.init_resource::<VisibleTiles>();
+        world.init_resource::<Events<TileDiscovered>>();
+        let mut index = TileIndex::default();
+        for (x, y, _) in map.iter_tiles() {
+            let entity = world.spawn((TilePos { x, y }, TileVisibilityState { visibility: TileVisibility::Unseen })).id();
+            index.insert(x, y, entity);
+        }
+        world.insert_resource(index);
+        world.insert_resource(map);
+        let player = world.spawn(Player { x: player.0, y: player.1 }).id();
+        (world, player)
+    }
+
+    fn visibility_at(world: &mut World, x: u32, y: u32) -> TileVisibility {
+        let entity = world.resource::<TileIndex>().tiles[&(x, y)];
+        world.get::<TileVisibilityState>(entity).unwrap().visibility
+    }
+
+    #[test]
+    fn every_neighbor_of_the_player_is_visible_even_with_no_sight_radius() {
+        let mut map = GameMap::new(9, 9);
+        map.fill_rect(URect::new(1, 1, 8, 8), TileType::Floor);
+        let blind = FovConfig { radius: 0, min_radius: 0, ..FovConfig::default() };
+        let (mut world, player) = fov_world(map, blind, (3, 4));
+
+        // First pass is a full update, the second (after a step) the incremental one
+        for (x, y) in [(3, 4), (4, 4)] {
+            world.get_mut::<Player>(player).unwrap().x = x;
+            world.run_system_cached(calculate_fov).unwrap();
+            for ny in y - 1..=y + 1 {
+                for nx in x - 1..=x + 1 {
+                    assert_eq!(visibility_at(&mut world, nx, ny), TileVisibility::Visible, "({}, {}) next to the player at ({}, {})", nx, ny, x, y);
+                }
+            }
+            assert_ne!(visibility_at(&mut world, x + 2, y), TileVisibility::Visible, "two tiles away is out of reach");
+            assert_eq!(world.resource::<VisibleTiles>().0.len(), 9);
+        }
+    }
+
+    #[test]
+    fn visible_tiles_match_a_brute_force_recount_as_the_player_walks() {
+        let mut map = GameMap::new(30, 12);
+        map.fill_rect(URect::new(1, 1, 29, 11), TileType::Floor);
+        for pillar in [(6, 4), (10, 7), (15, 3), (15, 8), (21, 6)] {
+            map.set(pillar.0, pillar.1, TileType::Wall);
+        }
+        let fov_config = FovConfig { radius: 6, ..FovConfig::default() };
+        let (mut world, player) = fov_world(map, fov_config, (2, 5));
+
+        for x in 2..28 {
+            world.get_mut::<Player>(player).unwrap().x = x;
+            world.run_system_cached(calculate_fov).unwrap();
+
+            let expected: std::collections::HashSet<(u32, u32)> = world
+                .query::<(&TilePos, &TileVisibilityState)>()
+                .iter(&world)
+                .filter(|(_, state)| state.visibility == TileVisibility::Visible)
+                .map(|(pos, _)| (pos.x, pos.y))
+                .collect();
+            assert_eq!(world.resource::<VisibleTiles>().0, expected, "player at x = {}", x);
+
+            let from_scratch: std::collections::HashSet<(u32, u32)> =
+                visible_tiles_from(world.resource::<GameMap>(), (x, 5), world.resource::<FovConfig>()).into_iter().collect();
+            assert_eq!(expected, from_scratch, "player at x = {}", x);
+        }
+    }
+
+    #[test]
+    fn min_radius_lights_the_ring_around_the_player_when_the_radius_is_zero() {
+        let room = || {
+            let mut map = GameMap::new(9, 9);
+            map.fill_rect(URect::new(1, 1, 8, 8), TileType::Floor);
+            map
+        };
+        let dark = FovConfig { radius: 0, min_radius: 1, ..FovConfig::default() };
+        let (mut world, _) = fov_world(room(), dark, (4, 4));
+        world.run_system_cached(calculate_fov).unwrap();
+        for y in 3..=5 {
+            for x in 3..=5 {
+                assert_eq!(visibility_at(&mut world, x, y), TileVisibility::Visible, "({}, {})", x, y);
+            }
+        }
+
+        // A wider floor reaches past the neighbors
+        let darker = FovConfig { radius: 0, min_radius: 2, ..FovConfig::default() };
+        let (mut world, _) = fov_world(room(), darker, (4, 4));
+        world.run_system_cached(calculate_fov).unwrap();
+        assert_eq!(visibility_at(&mut world, 6, 4), TileVisibility::Visible);
+        assert_eq!(visibility_at(&mut world, 4, 2), TileVisibility::Visible);
+    }
+
+    #[test]
+    fn revisiting_an_unchanged_level_reuses_its_saved_los_results() {
+        let level = || {
+            let mut map = GameMap::new(24, 14);
+            map.fill_rect(URect::new(1, 1, 23, 13), TileType::Floor);
+            for pillar in [(6, 5), (12, 8), (17, 4)] {
+                map.set(pillar.0, pillar.1, TileType::Wall);
+            }
+            map
+        };
+        let fov_config = FovConfig { radius: 8, ..FovConfig::default() };
+
+        // First visit: take a step, then leave the way handle_level_transitions does
+        let (mut world, player) = fov_world(level(), fov_config.clone(), (5, 6));
+        world.run_system_cached(calculate_fov).unwrap();
+        world.get_mut::<Player>(player).unwrap().x = 6;
+        world.run_system_cached(calculate_fov).unwrap();
+        let mut saved = SavedLosCaches::default();
+        saved.store(3, std::mem::take(&mut world.resource_mut::<LosCache>().entries));
+        assert!(saved.total_entries() > 0);
+
+        // Arrive again and retrace that step: hit rates for the arrival pass and the move
+        let hit_rates = |entries| {
+            let (mut world, player) = fov_world(level(), fov_config.clone(), (5, 6));
+            world.resource_mut::<LosCache>().entries = entries;
+            world.run_system_cached(calculate_fov).unwrap();
+            let arrival = world.resource::<LosCache>().hit_rate().unwrap();
+            world.resource_mut::<LosCache>().report_and_reset_stats();
+            world.get_mut::<Player>(player).unwrap().x = 6;
+            world.run_system_cached(calculate_fov).unwrap();
+            (arrival, world.resource::<LosCache>().hit_rate().unwrap())
+        };
+        let (arrival, first_move) = hit_rates(saved.take(3).unwrap());
+        assert_eq!(arrival, 100.0, "nothing on the arrival tile needs rechecking");
+        assert_eq!(first_move, 100.0);
+        assert_eq!(saved.total_entries(), 0, "the cache moves back into play rather than being copied");
+
+        let (cold_arrival, _) = hit_rates(LosEntries::default());
+        assert_eq!(cold_arrival, 0.0, "a cold cache starts with misses");
+    }
+
+    #[test]
+    fn square_fov_reaches_the_corners_of_its_box_and_circle_does_not() {
+        for shape in [FovShape::Circle, FovShape::Square, FovShape::Diamond] {
+            let mut map = GameMap::new(21, 21);
+            map.fill_rect(URect::new(1, 1, 20, 20), TileType::Floor);
+            let (mut world, player) = fov_world(map, FovConfig { radius: 4, shape, ..FovConfig::default() }, (8, 10));
+
+            // The full pass first, then the incremental one after a step
+            for x in [8, 9] {
+                world.get_mut::<Player>(player).unwrap().x = x;
+                world.run_system_cached(calculate_fov).unwrap();
+                let visible = |world: &mut World, dx: i32, dy: i32| {
+                    visibility_at(world, (x as i32 + dx) as u32, (10 + dy) as u32) == TileVisibility::Visible
+                };
+
+                for (dx, dy) in [(4, 4), (-4, 4), (4, -4), (-4, -4)] {
+                    assert_eq!(visible(&mut world, dx, dy), shape == FovShape::Square, "{:?} corner ({}, {}) from x = {}", shape, dx, dy, x);
+                }
+                assert!(visible(&mut world, 4, 0) && visible(&mut world, 0, -4), "{:?} reaches straight out", shape);
+                assert!(visible(&mut world, 2, 2), "{:?}", shape);
+                assert_eq!(visible(&mut world, 3, 2), shape != FovShape::Diamond, "{:?}", shape);
+            }
+        }
+    }
+
+    #[test]
+    fn cached_state_colors_match_the_per_tile_blend() {
+        for &biome in BiomeType::all() {
+            let tint = biome.get_config().tint();
+            let mut world = World::new();
+            world.init_resource::<Time>();
+            world.init_resource::<AccessibilitySettings>();
+            world.init_resource::<FovConfig>();
+            world.insert_resource(MemoryFadeSettings { enabled: false, ..default() });
+            world.insert_resource(CurrentLevel { level: 1, biome });
+            let tiles: Vec<(Entity, TileVisibility)> = [TileVisibility::Unseen, TileVisibility::Seen, TileVisibility::Visible]
+                .into_iter()
+                .enumerate()
+                .map(|(x, visibility)| (world.spawn((
+                    TileColor::default(),
+                    TilePos { x: x as u32, y: 0 },
+                    MapTile { tile_type: TileType::Floor },
+                    TileVisibilityState { visibility },
+                )).id(), visibility))
+                .collect();
+            world.run_system_cached(update_tile_visibility).unwrap();
+
+            for (entity, visibility) in tiles {
+                // The blend each tile used to compute for itself
+                let expected = match visibility {
+                    TileVisibility::Unseen => Color::srgb(0.0, 0.0, 0.0),
+                    TileVisibility::Seen => apply_color_tint(Color::srgb(0.3, 0.3, 0.4), tint.color, tint.seen_intensity),
+                    TileVisibility::Visible => apply_color_tint(Color::WHITE, tint.color, tint.visible_intensity),
+                };
+                assert_eq!(world.get::<TileColor>(entity).unwrap().0, expected, "{:?} {:?}", biome, visibility);
+            }
+        }
+    }
+
+    #[test]
+    fn a_biome_tint_blends_by_its_configured_intensity() {
+        let tint = BiomeType::CinderGaol.get_config().tint();
+        assert_eq!((tint.seen_intensity, tint.visible_intensity), (0.4, 0.6));
+        let red = tint.color.to_linear();
+        let colors = VisibilityColors::for_tint(tint);
+
+        // Visible starts from white: each channel moves 60% of the way toward the tint
+        let visible = colors.visible.to_linear();
+        for (got, t) in [(visible.red, red.red), (visible.green, red.green), (visible.blue, red.blue)] {
+            assert!((got - (0.4 + 0.6 * t)).abs() < 1e-5);
+        }
+        assert!(visible.red > 1.0 && visible.green < 1.0, "the red tint warms live tiles");
+
+        // Seen starts from the memory gray and moves 40%
+        let seen = colors.seen.to_linear();
+        let gray = Color::srgb(0.3, 0.3, 0.4).to_linear();
+        assert!((seen.blue - gray.blue * (0.6 + 0.4 * red.blue)).abs() < 1e-5);
+
+        // No configured tint leaves both states untouched
+        let plain = VisibilityColors::for_tint(BiomeType::Caverns.get_config().tint());
+        assert_eq!(plain.visible.to_linear(), Color::WHITE.to_linear());
+        assert_eq!(plain.seen.to_linear(), gray);
+    }
+
+    #[test]
+    fn high_contrast_states_are_far_apart_in_luminance() {
+        // WCAG relative luminance and contrast ratio
+        let luminance = |color: Color| {
+            let c = color.to_linear();
+            0.2126 * c.red + 0.7152 * c.green + 0.0722 * c.blue
+        };
+        let contrast = |a: Color, b: Color| {
+            let (hi, lo) = (luminance(a).max(luminance(b)), luminance(a).min(luminance(b)));
+            (hi + 0.05) / (lo + 0.05)
+        };
+
+        let accessibility = AccessibilitySettings { high_contrast: true, ..default() };
+        for &biome in BiomeType::all() {
+            let colors = VisibilityColors::for_settings(biome.get_config().tint(), &accessibility);
+            assert!(colors.high_contrast);
+            assert!(contrast(colors.seen, colors.unseen) >= 3.0, "{:?} Seen vs Unseen", biome);
+            assert!(contrast(colors.visible, colors.seen) >= 3.0, "{:?} Visible vs Seen", biome);
+            assert!(contrast(colors.visible, colors.unseen) >= 7.0, "{:?} Visible vs Unseen", biome);
+        }
+
+        // Stairs keep their own hue against the floor in the same state
+        let hue_gap = |a: Color, b: Color| {
+            let (a, b) = (a.to_srgba(), b.to_srgba());
+            (a.red - b.red).abs() + (a.green - b.green).abs() + (a.blue - b.blue).abs()
+        };
+        assert!(hue_gap(HIGH_CONTRAST_STAIR_SEEN, HIGH_CONTRAST_SEEN) > 1.0);
+        assert!(hue_gap(HIGH_CONTRAST_STAIR_VISIBLE, HIGH_CONTRAST_VISIBLE) > 0.5);
+    }
+
+    #[test]
+    fn edge_falloff_dims_lit_tiles_toward_the_radius() {
+        let alpha_at = |falloff: f32| {
+            let mut world = World::new();
+            world.init_resource::<Time>();
+            world.init_resource::<AccessibilitySettings>();
+            world.insert_resource(FovConfig { radius: 8, edge_falloff: falloff, ..FovConfig::default() });
+            world.insert_resource(MemoryFadeSettings { enabled: false, ..default() });
+            world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+            world.spawn(Player { x: 0, y: 0 });
+            let tiles: Vec<Entity> = (0..=8)
+                .map(|x| world.spawn((
+                    TileColor::default(),
+                    TilePos { x, y: 0 },
+                    MapTile { tile_type: TileType::Floor },
+                    TileVisibilityState { visibility: TileVisibility::Visible },
+                )).id())
+                .collect();
+            world.run_system_cached(update_tile_visibility).unwrap();
+            tiles.iter().map(|&entity| world.get::<TileColor>(entity).unwrap().0.alpha()).collect::<Vec<f32>>()
+        };
+
+        let crisp = alpha_at(0.0);
+        assert!(crisp.iter().all(|&alpha| alpha == 1.0), "no falloff keeps every lit tile opaque");
+
+        // With the outer half fading, the center stays opaque and alpha drops steadily to the minimum at the edge
+        let faded = alpha_at(0.5);
+        assert!(faded[..=4].iter().all(|&alpha| alpha == 1.0), "{:?}", faded);
+        assert!(faded[4..].windows(2).all(|pair| pair[1] < pair[0]), "{:?}", faded);
+        assert!((faded[8] - FovConfig::MIN_EDGE_ALPHA).abs() < 1e-5);
+    }
+}
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
+use bevy::window::WindowFocused;
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::components::{Player, MovementInput, MovementAnimation, Autoexplore, AutoMoveToStair, TileVisibilityState, TileVisibility, TileType, CurrentLevel, LevelMaps};
+use crate::components::{AccessibilitySettings, ExamineCursor, ExamineMode, SettingsMenu, Player, TileIndex, Facing, MovementInput, MovementAnimation, Autoexplore, AutoMoveToStair, AutoexploreSettings, PlayerSpriteConfig, TileVisibilityState, TileVisibility, TileType, CurrentLevel, LevelMaps, StairMode, StairDive, ExploredTiles, RememberedFeatures, StartPolicy, DungeonConfig};
 use crate::map::GameMap;
-use crate::biome::BiomeType;
+use crate::biome::{BiomeType, move_cost};
 use crate::level_manager::capture_tile_visibility;
-use crate::player::{count_unexplored_tiles, find_path};
+use crate::player::{count_unexplored_tiles, face_step, find_path};
+use crate::states::GameState;
+use crate::constants::TILE_SIZE;
 
 // ============================================================================
 // INPUT EVENTS
@@ -16,7 +19,7 @@ pub struct PlayerMoveIntent {
     pub direction: MoveDirection,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MoveDirection {
     Up,
     Down,
@@ -27,23 +30,74 @@ pub enum MoveDirection {
 #[derive(Event)]
 pub struct LevelChangeEvent {
     pub new_level: u32,
-    pub spawn_position: SpawnPosition,
+    pub spawn_position: StartPolicy,
 }
 
+// Ask for every auto action on the player (autoexplore, stair travel) to stop; see cancel_auto_actions
 #[derive(Event)]
-pub struct RegenerateMapEvent;
+pub struct CancelAutoActions {
+    pub reason: &'static str,
+}
+
+// Sent when stair auto-movement ends on its stairwell and the stairs should be taken
+#[derive(Event)]
+pub struct StairArrivalEvent {
+    pub stair_type: TileType,
+}
+
+// Sent when a move (or a level arrival) leaves the player standing on stairs, for contextual prompts
+#[derive(Event)]
+pub struct PlayerOnStairs {
+    pub stair_type: TileType,
+}
+
+// Sent when the player is no longer on the stairs a PlayerOnStairs was sent for
+#[derive(Event)]
+pub struct PlayerLeftStairs {
+    pub stair_type: TileType,
+}
+
+// Moves handle_movement_input carries between frames; they refer to the current layout, so every
+// map swap clears them
+#[derive(Resource, Default)]
+pub struct MoveBuffer {
+    pub buffered_move: Option<MoveDirection>,      // Pressed mid-hop, taken when the hop ends
+    pub pending_unseen_step: Option<(u32, u32)>,   // Careful movement's first press toward an Unseen tile
+}
+
+impl MoveBuffer {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
 
-#[derive(Clone, Copy)]
-pub enum SpawnPosition {
-    StairUp,
-    StairDown,
-    Center,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegenerateSeed {
+    Replay, // Rebuild from the level's stored seed (determinism check)
+    Next,   // Stored seed + 1, for stepping through seeds
+    Random, // Fresh random seed
+}
+
+#[derive(Event)]
+pub struct RegenerateMapEvent {
+    pub seed: RegenerateSeed,
 }
 
 // ============================================================================
 // KEY BINDINGS RESOURCE
 // ============================================================================
 
+// What a menu key press means, whichever screen is showing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
 #[derive(Resource)]
 pub struct KeyBindings {
     // Movement keys
@@ -53,12 +107,26 @@ pub struct KeyBindings {
     pub move_right: Vec<KeyCode>,
     
     // Level transition keys
-    pub stair_up: Vec<KeyCode>,      // S key - go up stairs
-    pub stair_down: Vec<KeyCode>,    // X key - go down stairs
+    pub stair_up: Vec<KeyCode>,      // Go up stairs
+    pub stair_down: Vec<KeyCode>,    // Go down stairs
     
     // Autoexplore keys
     pub toggle_autoexplore: Vec<KeyCode>,
     pub cancel_autoexplore: Vec<KeyCode>,
+    pub autoexplore_faster: Vec<KeyCode>,
+    pub autoexplore_slower: Vec<KeyCode>,
+    pub toggle_instant_explore: Vec<KeyCode>,
+
+    // Look around without moving
+    pub toggle_examine: Vec<KeyCode>,
+
+    // Menu navigation, shared by every UI screen
+    pub menu_up: Vec<KeyCode>,
+    pub menu_down: Vec<KeyCode>,
+    pub menu_left: Vec<KeyCode>,
+    pub menu_right: Vec<KeyCode>,
+    pub menu_confirm: Vec<KeyCode>,
+    pub menu_back: Vec<KeyCode>,
     
     // Debug keys
     pub regenerate_map: Vec<KeyCode>,
@@ -83,6 +151,20 @@ impl Default for KeyBindings {
             // Autoexplore
             toggle_autoexplore: vec![KeyCode::KeyA],
             cancel_autoexplore: vec![KeyCode::Escape, KeyCode::Space],
+            autoexplore_faster: vec![KeyCode::BracketRight],
+            autoexplore_slower: vec![KeyCode::BracketLeft],
+            toggle_instant_explore: vec![KeyCode::Backslash],
+
+            // Examine
+            toggle_examine: vec![KeyCode::KeyX],
+
+            // Menus
+            menu_up: vec![KeyCode::ArrowUp],
+            menu_down: vec![KeyCode::ArrowDown],
+            menu_left: vec![KeyCode::ArrowLeft],
+            menu_right: vec![KeyCode::ArrowRight],
+            menu_confirm: vec![KeyCode::Enter, KeyCode::Space],
+            menu_back: vec![KeyCode::Escape],
             
             // Debug
             regenerate_map: vec![KeyCode::KeyR],
@@ -101,6 +183,40 @@ impl KeyBindings {
     fn is_just_pressed(&self, keys: &[KeyCode], input: &ButtonInput<KeyCode>) -> bool {
         keys.iter().any(|key| input.just_pressed(*key))
     }
+
+    // This frame's menu action, if any; when several are pressed at once the first listed wins
+    pub fn menu_action(&self, input: &ButtonInput<KeyCode>) -> Option<MenuAction> {
+        [
+            (&self.menu_up, MenuAction::Up),
+            (&self.menu_down, MenuAction::Down),
+            (&self.menu_left, MenuAction::Left),
+            (&self.menu_right, MenuAction::Right),
+            (&self.menu_confirm, MenuAction::Confirm),
+            (&self.menu_back, MenuAction::Back),
+        ]
+        .into_iter()
+        .find(|(keys, _)| self.is_just_pressed(keys, input))
+        .map(|(_, action)| action)
+    }
+
+    // Human-readable key names for messages, e.g. [KeyD] -> "D"
+    pub fn label(keys: &[KeyCode]) -> String {
+        keys.iter()
+            .map(|key| {
+                let name = format!("{:?}", key);
+                name.strip_prefix("Key").map(str::to_string).unwrap_or(name)
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    pub fn stair_key_label(&self, stair_type: TileType) -> String {
+        if stair_type == TileType::StairUp {
+            Self::label(&self.stair_up)
+        } else {
+            Self::label(&self.stair_down)
+        }
+    }
 }
 
 // ============================================================================
@@ -165,29 +281,184 @@ pub fn detect_movement_input(
     }
 }
 
-/// Process movement intent events
+// ============================================================================
+// EXAMINE MODE
+// ============================================================================
+
+/// Run condition for player input; while examining, the movement keys belong to the cursor
+pub fn examine_inactive(examine: Res<ExamineMode>) -> bool {
+    !examine.active
+}
+
+/// X enters or leaves examine mode (Escape also leaves), spawning the cursor on the player
+pub fn toggle_examine_mode(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut examine: ResMut<ExamineMode>,
+    player_query: Query<&Player>,
+    cursor_query: Query<Entity, With<ExamineCursor>>,
+    map: Option<Res<GameMap>>,
+    mut cancel_events: EventWriter<CancelAutoActions>,
+) {
+    let toggled = key_bindings.is_just_pressed(&key_bindings.toggle_examine, &keyboard_input)
+        || (examine.active && keyboard_input.just_pressed(KeyCode::Escape));
+    if !toggled {
+        return;
+    }
+
+    if examine.active {
+        examine.active = false;
+        for entity in cursor_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        println!("Examine mode: OFF");
+        return;
+    }
+
+    let Some(map) = map else { return; };
+    let Ok(player) = player_query.single() else { return; };
+    cancel_events.write(CancelAutoActions { reason: "examine mode" });
+    examine.active = true;
+    examine.cursor = (player.x, player.y);
+    commands.spawn((
+        Sprite {
+            color: Color::srgba(1.0, 0.9, 0.3, 0.35),
+            custom_size: Some(Vec2::splat(TILE_SIZE)),
+            ..default()
+        },
+        // From the grid, not the player Transform, which carries the sprite anchor offset
+        Transform::from_xyz(
+            (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE,
+            (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE,
+            3.0,
+        ),
+        ExamineCursor,
+    ));
+    println!("Examine mode: ON");
+}
+
+/// Step the cursor with the movement keys, staying on the map and off Unseen tiles
+pub fn move_examine_cursor(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut examine: ResMut<ExamineMode>,
+    mut cursor_query: Query<&mut Transform, With<ExamineCursor>>,
+    tile_query: Query<&TileVisibilityState>,
+    tile_index: Res<TileIndex>,
+    map: Option<Res<GameMap>>,
+) {
+    let Some(map) = map else { return; };
+    if !examine.active {
+        return;
+    }
+
+    let (x, y) = examine.cursor;
+    let target = if key_bindings.is_just_pressed(&key_bindings.move_up, &keyboard_input) {
+        (x, (y + 1).min(map.height - 1))
+    } else if key_bindings.is_just_pressed(&key_bindings.move_down, &keyboard_input) {
+        (x, y.saturating_sub(1))
+    } else if key_bindings.is_just_pressed(&key_bindings.move_left, &keyboard_input) {
+        (x.saturating_sub(1), y)
+    } else if key_bindings.is_just_pressed(&key_bindings.move_right, &keyboard_input) {
+        ((x + 1).min(map.width - 1), y)
+    } else {
+        return;
+    };
+
+    let discovered = tile_index.tiles.get(&target)
+        .and_then(|&entity| tile_query.get(entity).ok())
+        .is_some_and(|state| state.visibility != TileVisibility::Unseen);
+    if target == examine.cursor || !discovered {
+        return;
+    }
+
+    examine.cursor = target;
+    for mut transform in cursor_query.iter_mut() {
+        transform.translation.x = (target.0 as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        transform.translation.y = (target.1 as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
+    }
+}
+
+/// Pause gameplay while the window is unfocused so held keys can't ghost-walk the player
+pub fn handle_window_focus(
+    mut focus_events: EventReader<WindowFocused>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut input_query: Query<&mut MovementInput>,
+    settings_menu: Res<SettingsMenu>,
+) {
+    // Only the most recent focus change matters this frame
+    let Some(focused) = focus_events.read().last().map(|event| event.focused) else {
+        return;
+    };
+
+    match (state.get(), focused) {
+        (GameState::Playing, false) => {
+            next_state.set(GameState::Paused);
+            println!("Window unfocused - game paused");
+        }
+        // The settings overlay keeps its own pause until it's closed
+        (GameState::Paused, true) if !settings_menu.open => {
+            next_state.set(GameState::Playing);
+            println!("Window focused - game resumed");
+        }
+        _ => return,
+    }
+
+    // Drop any hold state captured before the focus change; a key still held on
+    // return starts a fresh hold instead of continuing a stale repeat timer
+    for mut movement_input in input_query.iter_mut() {
+        movement_input.is_holding = false;
+        movement_input.move_timer.reset();
+    }
+}
+
+/// Process movement intent events. A move pressed mid-hop is buffered (at most one, the latest
+/// wins) and taken as soon as the animation ends, so quick taps aren't lost
 pub fn handle_movement_input(
     mut commands: Commands,
-    mut player_query: Query<(Entity, &mut Player, &mut Sprite, Option<&Autoexplore>), Without<MovementAnimation>>,
+    mut player_query: Query<(Entity, &mut Player, &mut Sprite, &mut Facing, Has<MovementAnimation>)>,
     mut move_events: EventReader<PlayerMoveIntent>,
-    map: Res<GameMap>,
+    mut cancel_events: EventWriter<CancelAutoActions>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    accessibility: Res<AccessibilitySettings>,
+    sprite_config: Res<PlayerSpriteConfig>,
+    tile_index: Res<TileIndex>,
+    tile_visibility_query: Query<&TileVisibilityState>,
+    mut move_buffer: ResMut<MoveBuffer>,
 ) {
-    if let Ok((entity, mut player, mut sprite, autoexplore_opt)) = player_query.single_mut() {
-        // Cancel autoexplore if player manually moves
-        if move_events.len() > 0 && autoexplore_opt.is_some() {
-            commands.entity(entity).remove::<Autoexplore>();
-            println!("Autoexplore cancelled by manual input");
+    let Some(map) = map else { return; };
+    if let Ok((entity, mut player, mut sprite, mut facing, animating)) = player_query.single_mut() {
+        if animating {
+            if let Some(event) = move_events.read().last() {
+                move_buffer.buffered_move = Some(event.direction);
+            }
+            return;
         }
 
-        // Process all movement events for this frame
-        for event in move_events.read() {
+        // This frame's moves, or the one held over from the last animation
+        let mut directions: Vec<MoveDirection> = move_events.read().map(|event| event.direction).collect();
+        if let Some(direction) = move_buffer.buffered_move.take() {
+            if directions.is_empty() {
+                directions.push(direction);
+            }
+        }
+
+        // Manual movement takes over from any auto action
+        if !directions.is_empty() {
+            cancel_events.write(CancelAutoActions { reason: "manual input" });
+        }
+
+        // Process all movement for this frame
+        for direction in directions {
             let mut movement_attempted = false;
             let mut new_x = player.x;
             let mut new_y = player.y;
-            let mut flip_sprite_opt: Option<bool> = None;
 
             // Apply movement based on direction
-            match event.direction {
+            match direction {
                 MoveDirection::Up => {
                     if new_y < map.height - 1 {
                         new_y += 1;
@@ -204,45 +475,59 @@ pub fn handle_movement_input(
                     if new_x > 0 {
                         new_x -= 1;
                         movement_attempted = true;
-                        flip_sprite_opt = Some(false); // No flip for left (natural direction)
                     }
                 }
                 MoveDirection::Right => {
                     if new_x < map.width - 1 {
                         new_x += 1;
                         movement_attempted = true;
-                        flip_sprite_opt = Some(true); // Flip for right (face right)
                     }
                 }
             }
 
-            // Check collision with walls and apply movement
-            if movement_attempted && map.get(new_x, new_y) != crate::components::TileType::Wall {
+            // Careful movement: the first step toward an Unseen tile only warns, pressing again steps in
+            if movement_attempted && accessibility.careful_movement
+                && tile_index.visibility(new_x, new_y, &tile_visibility_query) == TileVisibility::Unseen
+                && move_buffer.pending_unseen_step.take() != Some((new_x, new_y)) {
+                move_buffer.pending_unseen_step = Some((new_x, new_y));
+                println!("({}, {}) is unexplored - press {:?} again to step in", new_x, new_y, direction);
+                continue;
+            }
+            move_buffer.pending_unseen_step = None;
+
+            // Same entry rule as pathfinding, so manual steps can't reach tiles autoexplore wouldn't
+            let step_cost = if movement_attempted { move_cost(map.get(new_x, new_y), current_level.biome) } else { None };
+            if let Some(step_cost) = step_cost {
                 // Calculate start and end positions for animation
-                let start_world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let start_world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_x = (new_x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_y = (new_y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
+                let start_world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+                let start_world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
+                let end_world_x = (new_x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+                let end_world_y = (new_y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
+
+                // Face the step direction (only left/right flip the sprite)
+                face_step(&mut facing, &mut sprite, (player.x, player.y), (new_x, new_y));
 
                 // Update player grid position
                 player.x = new_x;
                 player.y = new_y;
 
-                // Handle sprite flipping
-                if let Some(flip) = flip_sprite_opt {
-                    sprite.flip_x = flip;
+                // Add movement animation component; with reduced motion move_player snaps the transform instead
+                if !accessibility.reduce_motion {
+                    let offset = sprite_config.world_offset().extend(0.0);
+                    commands.entity(entity).insert(MovementAnimation {
+                        start_pos: Vec3::new(start_world_x, start_world_y, 1.0) + offset,
+                        end_pos: Vec3::new(end_world_x, end_world_y, 1.0) + offset,
+                        timer: Timer::from_seconds(0.1 * step_cost as f32, TimerMode::Once), // 100ms hop animation, longer when swimming
+                    });
                 }
 
-                // Add movement animation component
-                commands.entity(entity).insert(MovementAnimation {
-                    start_pos: Vec3::new(start_world_x, start_world_y, 1.0),
-                    end_pos: Vec3::new(end_world_x, end_world_y, 1.0),
-                    timer: Timer::from_seconds(0.1, TimerMode::Once), // 100ms hop animation
-                });
-
-                println!("Player moved to ({}, {})", new_x, new_y);
+                if map.get(new_x, new_y) == TileType::Water {
+                    println!("Player swims to ({}, {})", new_x, new_y);
+                } else {
+                    println!("Player moved to ({}, {})", new_x, new_y);
+                }
             } else if movement_attempted {
-                println!("Cannot move to ({}, {}) - wall detected", new_x, new_y);
+                println!("Cannot move to ({}, {}) - {:?} blocks the way", new_x, new_y, map.get(new_x, new_y));
             }
         }
     }
@@ -256,20 +541,57 @@ pub fn handle_stair_interaction(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     key_bindings: Res<KeyBindings>,
-    player_query: Query<(Entity, &Player, Option<&Autoexplore>, Option<&AutoMoveToStair>)>,
+    player_query: Query<(Entity, &Player)>,
     tile_visibility_query: Query<(&TilePos, &TileVisibilityState)>,
-    map: Res<GameMap>,
+    map: Option<Res<GameMap>>,
     current_level: Res<CurrentLevel>,
     mut level_maps: ResMut<LevelMaps>,
     mut level_change_events: EventWriter<LevelChangeEvent>,
+    mut arrival_events: EventReader<StairArrivalEvent>,
+    stair_mode: Res<StairMode>,
+    remembered: Res<RememberedFeatures>,
+    autoexplore_settings: Res<AutoexploreSettings>,
+    dungeon: Res<DungeonConfig>,
+    mut cancel_events: EventWriter<CancelAutoActions>,
+    mut pending_travel: Local<Option<(u32, (u32, u32), TileType)>>, // (level, stairwell, stair type)
 ) {
-    if let Ok((entity, player, autoexplore_opt, auto_move_opt)) = player_query.single() {
+    let Some(map) = map else { return; };
+    if let Ok((entity, player)) = player_query.single() {
         let tile_type = map.get(player.x, player.y);
+
+        // Travel asked for last frame; cancel_auto_actions has run since, so nothing strips it
+        if let Some((_, stairwell, stair_type)) = pending_travel.take().filter(|&(level, ..)| level == current_level.level) {
+            let path = find_path((player.x, player.y), stairwell, &map, current_level.biome);
+            let direction = if stair_type == TileType::StairUp { "up" } else { "down" };
+            if !path.is_empty() || (player.x, player.y) == stairwell {
+                println!("Auto-moving to discovered {} stairwell at ({}, {})", direction, stairwell.0, stairwell.1);
+                commands.entity(entity).insert(AutoMoveToStair::new(
+                    stairwell,
+                    path,
+                    stair_type,
+                ).with_take_on_arrival(autoexplore_settings.auto_use_stairs_on_arrival));
+            } else {
+                println!("No path to {} stairwell!", direction);
+            }
+        }
+
+        // Arriving by auto-move counts as pressing the matching stair key
+        let mut arrived_up = false;
+        let mut arrived_down = false;
+        for event in arrival_events.read() {
+            match event.stair_type {
+                TileType::StairUp => arrived_up = true,
+                TileType::StairDown => arrived_down = true,
+                _ => {}
+            }
+        }
         
-        // Check for move up (S key)
-        if key_bindings.is_just_pressed(&key_bindings.stair_up, &keyboard_input) {
+        // Check for move up
+        if arrived_up || key_bindings.is_just_pressed(&key_bindings.stair_up, &keyboard_input) {
             // If standing on up stairs, use them
-            if tile_type == TileType::StairUp {
+            if !stair_mode.allows_ascent() {
+                println!("This is a descend-only run - there's no way back up");
+            } else if tile_type == TileType::StairUp {
                 if current_level.level > 0 {
                     println!("Going up to level {}", current_level.level - 1);
                     // Save current map with tile visibility
@@ -278,7 +600,7 @@ pub fn handle_stair_interaction(
                     // Trigger level change
                     level_change_events.write(LevelChangeEvent {
                         new_level: current_level.level - 1,
-                        spawn_position: SpawnPosition::StairDown,
+                        spawn_position: StartPolicy::NearDownStair,
                     });
                 } else {
                     println!("Cannot go up from the surface!");
@@ -288,25 +610,14 @@ pub fn handle_stair_interaction(
                 if let Some(nearest_stair) = find_nearest_discovered_stairwell(
                     &player,
                     TileType::StairUp,
-                    &tile_visibility_query,
+                    &remembered,
                     &map,
+                    current_level.biome,
                 ) {
-                    let path = find_path((player.x, player.y), nearest_stair, &map);
-                    if !path.is_empty() {
-                        // Cancel any existing auto-movement
-                        if autoexplore_opt.is_some() {
-                            commands.entity(entity).remove::<Autoexplore>();
-                        }
-                        if auto_move_opt.is_some() {
-                            commands.entity(entity).remove::<AutoMoveToStair>();
-                        }
-                        
-                        println!("Auto-moving to discovered up stairwell at ({}, {})", nearest_stair.0, nearest_stair.1);
-                        commands.entity(entity).insert(AutoMoveToStair::new(
-                            nearest_stair,
-                            path,
-                            TileType::StairUp,
-                        ));
+                    if !find_path((player.x, player.y), nearest_stair, &map, current_level.biome).is_empty() {
+                        // Stop whatever auto action is running first; the travel starts next frame
+                        cancel_events.write(CancelAutoActions { reason: "stair travel" });
+                        *pending_travel = Some((current_level.level, nearest_stair, TileType::StairUp));
                     } else {
                         println!("No path to up stairwell!");
                     }
@@ -316,11 +627,11 @@ pub fn handle_stair_interaction(
             }
         }
         
-        // Check for move down (X key)
-        if key_bindings.is_just_pressed(&key_bindings.stair_down, &keyboard_input) {
+        // Check for move down
+        if arrived_down || key_bindings.is_just_pressed(&key_bindings.stair_down, &keyboard_input) {
             // If standing on down stairs, use them
             if tile_type == TileType::StairDown {
-                if current_level.level < 50 {
+                if current_level.level < dungeon.max_depth {
                     println!("Going down to level {}", current_level.level + 1);
                     // Save current map with tile visibility
                     let current_visibility = capture_tile_visibility(&tile_visibility_query, map.width, map.height);
@@ -328,72 +639,162 @@ pub fn handle_stair_interaction(
                     // Trigger level change
                     level_change_events.write(LevelChangeEvent {
                         new_level: current_level.level + 1,
-                        spawn_position: SpawnPosition::StairUp,
+                        spawn_position: StartPolicy::OnUpStair,
                     });
                 } else {
                     println!("Cannot go deeper - you've reached the bottom!");
                 }
             } else {
-                // Not on stairs - try to auto-move to nearest discovered down stairwell
-                if let Some(nearest_stair) = find_nearest_discovered_stairwell(
-                    &player,
-                    TileType::StairDown,
-                    &tile_visibility_query,
-                    &map,
-                ) {
-                    let path = find_path((player.x, player.y), nearest_stair, &map);
-                    if !path.is_empty() {
-                        // Cancel any existing auto-movement
-                        if autoexplore_opt.is_some() {
-                            commands.entity(entity).remove::<Autoexplore>();
-                        }
-                        if auto_move_opt.is_some() {
-                            commands.entity(entity).remove::<AutoMoveToStair>();
-                        }
-                        
-                        println!("Auto-moving to discovered down stairwell at ({}, {})", nearest_stair.0, nearest_stair.1);
-                        commands.entity(entity).insert(AutoMoveToStair::new(
-                            nearest_stair,
-                            path,
-                            TileType::StairDown,
-                        ));
+                // Not on stairs - try to auto-move to nearest discovered down stairwell,
+                // which on the bottom level is the altar
+                let down_target = if current_level.level < dungeon.max_depth { TileType::StairDown } else { TileType::Altar };
+                let target = if down_target == TileType::Altar {
+                    // There's only the one altar and the map records where it is
+                    map.altar_pos.filter(|&(x, y)| remembered.get(x, y) == Some(TileType::Altar))
+                } else {
+                    find_nearest_discovered_stairwell(
+                        player,
+                        down_target,
+                        &remembered,
+                        &map,
+                        current_level.biome,
+                    )
+                };
+                if let Some(nearest_stair) = target {
+                    if !find_path((player.x, player.y), nearest_stair, &map, current_level.biome).is_empty() {
+                        // Stop whatever auto action is running first; the travel starts next frame
+                        cancel_events.write(CancelAutoActions { reason: "stair travel" });
+                        *pending_travel = Some((current_level.level, nearest_stair, down_target));
                     } else {
                         println!("No path to down stairwell!");
                     }
                 } else {
-                    println!("No discovered down stairwell found. Explore to find stairs.");
+                    if down_target == TileType::Altar {
+                        println!("The altar has not been found yet. Explore the depths.");
+                    } else {
+                        println!("No discovered down stairwell found. Explore to find stairs.");
+                    }
                 }
             }
         }
     }
 }
 
+// Holding the descend key chains through the discovered down stairs of each new level; see StairDive
+pub fn handle_stair_dive(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    time: Res<Time>,
+    mut dive: ResMut<StairDive>,
+    player_query: Query<(Entity, &Player, Has<AutoMoveToStair>)>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    remembered: Res<RememberedFeatures>,
+    dungeon: Res<DungeonConfig>,
+) {
+    let Some(map) = map else { return; };
+    let Ok((entity, player, traveling)) = player_query.single() else { return; };
+    let level = current_level.level;
+
+    // The first press is a normal stair press (or auto-travel); it only starts the hold timer
+    if key_bindings.is_just_pressed(&key_bindings.stair_down, &keyboard_input) {
+        dive.press(time.elapsed_secs(), level);
+        return;
+    }
+    if !dive.is_pressed() {
+        return;
+    }
+    let held = key_bindings.is_pressed(&key_bindings.stair_down, &keyboard_input);
+    if dive.waiting_for(level) {
+        // Travel that was blocked or cancelled never lands, so letting go ends the dive here
+        if !held && !traveling {
+            dive.finish(level, "stair travel stopped");
+        }
+        return;
+    }
+    if !held {
+        dive.finish(level, "key released");
+        return;
+    }
+    let Some(start_level) = dive.held_start(time.elapsed_secs()) else { return; };
+
+    if level.saturating_sub(start_level) >= dive.max_levels {
+        dive.finish(level, "dive limit reached");
+        return;
+    }
+    if level >= dungeon.max_depth {
+        dive.finish(level, "reached the bottom");
+        return;
+    }
+    // Only stairs the player has seen, walked to like any other stair travel
+    let Some(stair) = find_nearest_discovered_stairwell(player, TileType::StairDown, &remembered, &map, current_level.biome) else {
+        dive.finish(level, "no discovered down stairs");
+        return;
+    };
+    let path = find_path((player.x, player.y), stair, &map, current_level.biome);
+    if path.is_empty() && (player.x, player.y) != stair {
+        dive.finish(level, "down stairs unreachable");
+        return;
+    }
+
+    // Arriving sends a StairArrivalEvent, so handle_stair_interaction saves the level and takes the
+    // stairs exactly as a key press would; regenerated levels come from the stored seed
+    println!("Diving to level {}", level + 1);
+    commands.entity(entity).insert(AutoMoveToStair::new(stair, path, TileType::StairDown).with_take_on_arrival(true));
+    dive.descend_to(level + 1);
+}
+
 // ============================================================================
 // AUTOEXPLORE INPUT SYSTEMS
 // ============================================================================
 
+// Every auto-action component; new ones (travel, resting) belong here so one cancel stops them all
+pub fn remove_auto_actions(entity: &mut EntityCommands) {
+    entity.remove::<(Autoexplore, AutoMoveToStair)>();
+}
+
+/// Strip all auto actions from the player when any system sends CancelAutoActions
+pub fn cancel_auto_actions(
+    mut commands: Commands,
+    mut cancel_events: EventReader<CancelAutoActions>,
+    player_query: Query<(Entity, Has<Autoexplore>, Has<AutoMoveToStair>), With<Player>>,
+) {
+    let Some(event) = cancel_events.read().last() else { return; };
+    let Ok((entity, exploring, moving_to_stair)) = player_query.single() else { return; };
+    if exploring || moving_to_stair {
+        remove_auto_actions(&mut commands.entity(entity));
+        println!("Auto actions cancelled ({})", event.reason);
+    }
+}
+
 pub fn toggle_autoexplore(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     key_bindings: Res<KeyBindings>,
     mut commands: Commands,
     mut player_query: Query<(Entity, &Player, Option<&Autoexplore>)>,
-    tile_visibility_query: Query<(&TilePos, &TileVisibilityState)>,
-    map: Res<GameMap>,
+    explored: Res<ExploredTiles>,
+    map: Option<Res<GameMap>>,
+    mut cancel_events: EventWriter<CancelAutoActions>,
 ) {
+    let Some(map) = map else { return; };
     // Check for A to toggle, or ESC/Space to cancel
     let toggle_pressed = key_bindings.is_just_pressed(&key_bindings.toggle_autoexplore, &keyboard_input);
     let cancel_pressed = key_bindings.is_just_pressed(&key_bindings.cancel_autoexplore, &keyboard_input);
 
     if toggle_pressed || cancel_pressed {
         if let Ok((entity, _player, autoexplore_opt)) = player_query.single_mut() {
-            if autoexplore_opt.is_some() {
-                // Remove component entirely to stop autoexplore
-                commands.entity(entity).remove::<Autoexplore>();
-                println!("Autoexplore disabled");
+            if autoexplore_opt.is_some() || cancel_pressed {
+                // ESC/Space stops every auto action, A only stops a running autoexplore
+                let reason = if cancel_pressed { "cancel key" } else { "autoexplore toggled off" };
+                cancel_events.write(CancelAutoActions { reason });
+                if autoexplore_opt.is_some() {
+                    println!("Autoexplore disabled");
+                }
             } else if toggle_pressed {
                 // Only enable on A, not on ESC
                 // Check if there are unexplored tiles
-                let unexplored_count = count_unexplored_tiles(&tile_visibility_query, &map);
+                let unexplored_count = count_unexplored_tiles(&explored, &map);
                 if unexplored_count > 0 {
                     commands.entity(entity).insert(Autoexplore::default());
                     println!("Autoexplore enabled - {} tiles to explore", unexplored_count);
@@ -405,6 +806,32 @@ pub fn toggle_autoexplore(
     }
 }
 
+pub fn adjust_autoexplore_speed(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut settings: ResMut<AutoexploreSettings>,
+) {
+    // ] doubles, [ halves the autoexplore step speed
+    let mut new_speed = settings.speed_multiplier;
+    if key_bindings.is_just_pressed(&key_bindings.autoexplore_faster, &keyboard_input) {
+        new_speed *= 2.0;
+    }
+    if key_bindings.is_just_pressed(&key_bindings.autoexplore_slower, &keyboard_input) {
+        new_speed /= 2.0;
+    }
+    let new_speed = new_speed.clamp(AutoexploreSettings::MIN_SPEED, AutoexploreSettings::MAX_SPEED);
+    if new_speed != settings.speed_multiplier {
+        settings.speed_multiplier = new_speed;
+        println!("Autoexplore speed: {}x", new_speed);
+    }
+
+    // \ toggles teleporting straight to each explore target
+    if key_bindings.is_just_pressed(&key_bindings.toggle_instant_explore, &keyboard_input) {
+        settings.instant = !settings.instant;
+        println!("Instant autoexplore: {}", if settings.instant { "ON" } else { "OFF" });
+    }
+}
+
 // ============================================================================
 // DEBUG INPUT SYSTEMS
 // ============================================================================
@@ -416,9 +843,21 @@ pub fn debug_map_regeneration(
 ) {
     let shift_held = keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
     
+    let ctrl_held = keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight);
+    let alt_held = keyboard_input.pressed(KeyCode::AltLeft) || keyboard_input.pressed(KeyCode::AltRight);
+
+    // Shift+R rolls a new seed for this level, Ctrl+Shift+R rebuilds it from the stored one,
+    // Alt+Shift+R steps to the next seed
     if key_bindings.is_just_pressed(&key_bindings.regenerate_map, &keyboard_input) && shift_held {
+        let seed = if ctrl_held {
+            RegenerateSeed::Replay
+        } else if alt_held {
+            RegenerateSeed::Next
+        } else {
+            RegenerateSeed::Random
+        };
         println!("Regenerating current level map...");
-        regenerate_events.write(RegenerateMapEvent);
+        regenerate_events.write(RegenerateMapEvent { seed });
     }
 }
 
@@ -431,133 +870,347 @@ pub fn debug_biome_cycling(
     let shift_held = keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
     
     if key_bindings.is_just_pressed(&key_bindings.cycle_biome, &keyboard_input) && shift_held {
-        // Cycle between implemented biomes: Caverns -> Cinder Gaol -> Underglade -> back to Caverns
-        current_level.biome = match current_level.biome {
-            BiomeType::Caverns => {
-                println!("Cycling from Caverns to Cinder Gaol");
-                BiomeType::CinderGaol
-            },
-            BiomeType::CinderGaol => {
-                println!("Cycling from Cinder Gaol to Underglade");
-                BiomeType::Underglade
-            },
-            BiomeType::Underglade => {
-                println!("Cycling from Underglade back to Caverns");
-                BiomeType::Caverns
-            },
-            _ => BiomeType::Caverns, // Fallback to Caverns for other biomes
-        };
+        // Cycle through implemented biomes in order, wrapping around; unimplemented ones jump to the first
+        let implemented: Vec<BiomeType> = BiomeType::all().iter()
+            .copied()
+            .filter(|biome| biome.is_implemented())
+            .collect();
+        let next_biome = implemented.iter()
+            .position(|&biome| biome == current_level.biome)
+            .map_or(implemented[0], |i| implemented[(i + 1) % implemented.len()]);
+
+        println!("Cycling from {} to {}", current_level.biome.display_name(), next_biome.display_name());
+        current_level.biome = next_biome;
         
         println!("Current biome: {:?}", current_level.biome);
         println!("Regenerating map with new biome...");
-        regenerate_events.write(RegenerateMapEvent);
+        regenerate_events.write(RegenerateMapEvent { seed: RegenerateSeed::Replay });
     }
 }
-// Helper function to find nearest discovered stairwell of a specific type
+// Helper function to find the nearest discovered stairwell of a specific type by walking distance,
+// so a stair just behind a wall doesn't win over one the player can actually reach
 fn find_nearest_discovered_stairwell(
     player: &Player,
     stair_type: TileType,
-    tile_visibility_query: &Query<(&TilePos, &TileVisibilityState)>,
+    remembered: &RememberedFeatures,
     map: &GameMap,
+    biome: BiomeType,
 ) -> Option<(u32, u32)> {
-    let mut nearest_stair: Option<(u32, u32)> = None;
-    let mut min_distance = f32::INFINITY;
-
-    // Search all tiles for discovered stairs of the requested type
-    for y in 0..map.height {
-        for x in 0..map.width {
-            // Check if this tile is the stair type we're looking for
-            if map.get(x, y) != stair_type {
-                continue;
-            }
+    // Discovered stairs are kept in landmark memory, so there's no need to scan every tile
+    let discovered: std::collections::HashSet<(u32, u32)> = remembered.positions_of(stair_type).collect();
+    if discovered.is_empty() {
+        return None;
+    }
 
-            // Check if this stairwell has been discovered (Visible or Seen)
-            let mut is_discovered = false;
-            for (tile_pos, visibility_state) in tile_visibility_query.iter() {
-                if tile_pos.x == x && tile_pos.y == y {
-                    is_discovered = visibility_state.visibility == TileVisibility::Visible
-                        || visibility_state.visibility == TileVisibility::Seen;
-                    break;
-                }
-            }
+    map.nearest_walkable_tile_of_type((player.x, player.y), stair_type, biome, |x, y| discovered.contains(&(x, y)))
+}
 
-            if !is_discovered {
-                continue;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::animate_movement;
 
-            // Calculate Manhattan distance
-            let distance = ((x as i32 - player.x as i32).abs() + (y as i32 - player.y as i32).abs()) as f32;
+    #[test]
+    fn moving_onto_water_is_blocked() {
+        let mut map = GameMap::new(5, 3);
+        map.set(1, 1, TileType::Floor);
+        map.set(2, 1, TileType::Water);
+        map.set(1, 2, TileType::Floor);
 
-            if distance < min_distance {
-                min_distance = distance;
-                nearest_stair = Some((x, y));
-            }
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns }); // Caverns water can't be swum
+        world.init_resource::<AccessibilitySettings>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<TileIndex>();
+        world.init_resource::<MoveBuffer>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        let player = world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default())).id();
+        let handle = world.register_system(handle_movement_input);
+
+        world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+        world.run_system(handle).unwrap();
+        let at = world.get::<Player>(player).map(|player| (player.x, player.y));
+        assert_eq!(at, Some((1, 1)), "water stopped the step");
+        assert!(world.get::<MovementAnimation>(player).is_none());
+
+        // The same system still lets the player onto floor
+        world.send_event(PlayerMoveIntent { direction: MoveDirection::Up });
+        world.run_system(handle).unwrap();
+        let at = world.get::<Player>(player).map(|player| (player.x, player.y));
+        assert_eq!(at, Some((1, 2)));
+    }
+
+    #[test]
+    fn a_move_pressed_mid_hop_applies_when_the_hop_ends() {
+        let mut map = GameMap::new(8, 3);
+        map.fill_rect(URect::new(1, 1, 7, 2), TileType::Floor);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<AccessibilitySettings>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<TileIndex>();
+        world.init_resource::<MoveBuffer>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        world.init_resource::<Time>();
+        let player = world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default(), Transform::default())).id();
+        let handle = world.register_system(handle_movement_input);
+        let animate = world.register_system(animate_movement);
+        let frame = |world: &mut World, millis: u64| {
+            world.resource_mut::<Time>().advance_by(std::time::Duration::from_millis(millis));
+            world.run_system(animate).unwrap();
+            world.run_system(handle).unwrap();
+            world.flush();
+        };
+        let at = |world: &World| world.get::<Player>(player).map(|player| player.x);
+
+        world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+        frame(&mut world, 0);
+        assert_eq!(at(&world), Some(2));
+        assert!(world.get::<MovementAnimation>(player).is_some());
+
+        // Two presses during the hop: only one is held, and nothing moves yet
+        for _ in 0..2 {
+            world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+            frame(&mut world, 20);
+        }
+        assert_eq!(at(&world), Some(2));
+        assert_eq!(world.resource::<MoveBuffer>().buffered_move, Some(MoveDirection::Right));
+
+        // The hop finishes and the held move goes through with no new input
+        frame(&mut world, 100);
+        frame(&mut world, 0);
+        assert_eq!(at(&world), Some(3));
+        assert_eq!(world.resource::<MoveBuffer>().buffered_move, None);
+        for _ in 0..10 {
+            frame(&mut world, 100);
         }
+        assert_eq!(at(&world), Some(3), "the buffer holds a single move");
     }
 
-    nearest_stair
-}
+    #[test]
+    fn stair_travel_picks_the_reachable_stair_over_the_one_behind_a_wall() {
+        // Two rooms split by a solid wall at x = 6; the player is in the left one
+        let mut map = GameMap::new(20, 5);
+        map.fill_rect(URect::new(1, 1, 19, 4), TileType::Floor);
+        map.fill_rect(URect::new(6, 0, 7, 5), TileType::Wall);
+        map.set(8, 2, TileType::StairDown); // Three tiles away as the crow flies, but sealed off
+        map.set(1, 1, TileType::StairDown); // Further in a straight line, but walkable
+        let player = Player { x: 5, y: 2 };
 
-// System to handle auto-movement to discovered stairwells
-pub fn run_auto_move_to_stair(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut player_query: Query<(Entity, &mut Player, &mut AutoMoveToStair, &mut Sprite), Without<MovementAnimation>>,
-    map: Res<GameMap>,
-) {
-    if let Ok((entity, mut player, mut auto_move, mut sprite)) = player_query.single_mut() {
-        // Tick timer
-        auto_move.move_timer.tick(time.delta());
+        let mut remembered = RememberedFeatures::default();
+        remembered.features.insert((8, 2), TileType::StairDown);
+        remembered.features.insert((1, 1), TileType::StairDown);
+        assert_eq!(find_nearest_discovered_stairwell(&player, TileType::StairDown, &remembered, &map, BiomeType::Caverns), Some((1, 1)));
 
-        if !auto_move.move_timer.just_finished() {
-            return;
+        // Undiscovered stairs are never chosen, and none reachable means nothing to travel to
+        remembered.features.remove(&(1, 1));
+        assert_eq!(find_nearest_discovered_stairwell(&player, TileType::StairDown, &remembered, &map, BiomeType::Caverns), None);
+        assert_eq!(map.nearest_walkable_tile_of_type((5, 2), TileType::StairDown, BiomeType::Caverns, |_, _| true), Some((1, 1)));
+    }
+
+    #[test]
+    fn descend_only_runs_refuse_to_go_up() {
+        let level_changes = |stair_mode: StairMode| {
+            let mut map = GameMap::new(5, 3);
+            map.set(2, 1, TileType::StairUp);
+
+            let mut world = World::new();
+            world.insert_resource(map);
+            world.insert_resource(CurrentLevel { level: 3, biome: BiomeType::Caverns });
+            world.insert_resource(stair_mode);
+            world.init_resource::<ButtonInput<KeyCode>>();
+            world.init_resource::<KeyBindings>();
+            world.init_resource::<LevelMaps>();
+            world.init_resource::<RememberedFeatures>();
+            world.init_resource::<AutoexploreSettings>();
+            world.init_resource::<DungeonConfig>();
+            world.init_resource::<Events<LevelChangeEvent>>();
+            world.init_resource::<Events<StairArrivalEvent>>();
+            world.init_resource::<Events<CancelAutoActions>>();
+            world.spawn(Player { x: 2, y: 1 });
+
+            world.send_event(StairArrivalEvent { stair_type: TileType::StairUp });
+            world.run_system_cached(handle_stair_interaction).unwrap();
+            world.resource_mut::<Events<LevelChangeEvent>>().drain().map(|event| event.new_level).collect::<Vec<_>>()
+        };
+
+        assert_eq!(level_changes(StairMode::BothWays), vec![2]);
+        assert!(level_changes(StairMode::DescendOnly).is_empty(), "standing on an up stair doesn't lead back up");
+    }
+
+    #[test]
+    fn cancelling_strips_every_auto_action_whoever_asks() {
+        let mut map = GameMap::new(5, 3);
+        map.fill_rect(URect::new(1, 1, 4, 2), TileType::Floor);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<AccessibilitySettings>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<TileIndex>();
+        world.init_resource::<MoveBuffer>();
+        world.init_resource::<ExploredTiles>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        let player = world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default())).id();
+        let automate = |world: &mut World| {
+            world.entity_mut(player).insert((Autoexplore::default(), AutoMoveToStair::new((3, 1), Default::default(), TileType::StairDown)));
+        };
+        let cancelled = |world: &mut World| {
+            world.run_system_cached(cancel_auto_actions).unwrap();
+            world.flush();
+            !world.entity(player).contains::<Autoexplore>() && !world.entity(player).contains::<AutoMoveToStair>()
+        };
+
+        // Sent directly
+        automate(&mut world);
+        world.send_event(CancelAutoActions { reason: "test" });
+        assert!(cancelled(&mut world));
+
+        // From a manual step
+        automate(&mut world);
+        world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+        world.run_system_cached(handle_movement_input).unwrap();
+        assert!(cancelled(&mut world));
+
+        // From the cancel key
+        automate(&mut world);
+        let cancel_key = world.resource::<KeyBindings>().cancel_autoexplore[0];
+        world.resource_mut::<ButtonInput<KeyCode>>().press(cancel_key);
+        world.run_system_cached(toggle_autoexplore).unwrap();
+        assert!(cancelled(&mut world));
+
+        // Without a request nothing is removed
+        automate(&mut world);
+        assert!(!cancelled(&mut world));
+    }
+
+    #[test]
+    fn careful_movement_stops_the_first_step_into_the_unseen() {
+        let mut map = GameMap::new(6, 3);
+        map.fill_rect(URect::new(1, 1, 5, 2), TileType::Floor);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.insert_resource(AccessibilitySettings { careful_movement: true, reduce_motion: true, ..default() });
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<MoveBuffer>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        let mut index = TileIndex::default();
+        for x in 0..6 {
+            let visibility = if x <= 2 { TileVisibility::Visible } else { TileVisibility::Unseen };
+            index.insert(x, 1, world.spawn(TileVisibilityState { visibility }).id());
         }
+        world.insert_resource(index);
+        let player = world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default())).id();
+        let step = |world: &mut World, direction| {
+            world.send_event(PlayerMoveIntent { direction });
+            world.run_system_cached(handle_movement_input).unwrap();
+            world.get::<Player>(player).unwrap().x
+        };
 
-        // Get next step in path
-        if let Some(next_pos) = auto_move.path.first().copied() {
-            // Check if we can move to next position
-            if map.get(next_pos.0, next_pos.1) != TileType::Wall {
-                // Calculate animation positions
-                let start_world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let start_world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_x = (next_pos.0 as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_y = (next_pos.1 as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
-
-                // Update sprite facing
-                if next_pos.0 < player.x {
-                    sprite.flip_x = false; // Moving left
-                } else if next_pos.0 > player.x {
-                    sprite.flip_x = true; // Moving right
-                }
+        assert_eq!(step(&mut world, MoveDirection::Right), 2, "seen ground needs no confirmation");
+        assert_eq!(step(&mut world, MoveDirection::Right), 2, "the first press toward an Unseen tile only warns");
+        assert_eq!(step(&mut world, MoveDirection::Right), 3, "pressing again steps in");
 
-                // Move player
-                player.x = next_pos.0;
-                player.y = next_pos.1;
+        // A move elsewhere in between drops the pending confirmation
+        assert_eq!(step(&mut world, MoveDirection::Right), 3, "(4, 1) is Unseen too");
+        assert_eq!(step(&mut world, MoveDirection::Left), 2);
+        assert_eq!(step(&mut world, MoveDirection::Right), 2, "(3, 1) asks again");
+        assert_eq!(step(&mut world, MoveDirection::Right), 3);
 
-                // Add animation
-                commands.entity(entity).insert(MovementAnimation {
-                    start_pos: Vec3::new(start_world_x, start_world_y, 1.0),
-                    end_pos: Vec3::new(end_world_x, end_world_y, 1.0),
-                    timer: Timer::from_seconds(0.05, TimerMode::Once), // Fast movement
-                });
+        world.resource_mut::<AccessibilitySettings>().careful_movement = false;
+        assert_eq!(step(&mut world, MoveDirection::Right), 4, "with the setting off it walks straight in");
+    }
 
-                // Remove this step from path
-                auto_move.path.remove(0);
-            } else {
-                // Path blocked, cancel auto-move
-                println!("Path to stairwell blocked!");
-                commands.entity(entity).remove::<AutoMoveToStair>();
-            }
-        } else {
-            // Reached destination
-            let current_tile = map.get(player.x, player.y);
-            if current_tile == auto_move.stair_type {
-                println!("Reached {} stairwell! Press {} to use it.", 
-                    if auto_move.stair_type == TileType::StairUp { "up" } else { "down" },
-                    if auto_move.stair_type == TileType::StairUp { "S" } else { "X" }
-                );
+    #[test]
+    fn stair_travel_stops_autoexplore_through_the_cancel_event() {
+        let mut map = GameMap::new(6, 3);
+        map.fill_rect(URect::new(1, 1, 5, 2), TileType::Floor);
+        map.set(4, 1, TileType::StairDown);
+        let mut remembered = RememberedFeatures::default();
+        remembered.features.insert((4, 1), TileType::StairDown);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(remembered);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<StairMode>();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<LevelMaps>();
+        world.init_resource::<AutoexploreSettings>();
+        world.init_resource::<DungeonConfig>();
+        world.init_resource::<Events<LevelChangeEvent>>();
+        world.init_resource::<Events<StairArrivalEvent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        let player = world.spawn((Player { x: 1, y: 1 }, Autoexplore::default())).id();
+        // cancel_auto_actions runs just before the stair system each frame
+        let frame = |world: &mut World| {
+            world.run_system_cached(cancel_auto_actions).unwrap();
+            world.flush();
+            world.run_system_cached(handle_stair_interaction).unwrap();
+            world.flush();
+            world.resource_mut::<ButtonInput<KeyCode>>().clear();
+        };
+
+        let stair_key = world.resource::<KeyBindings>().stair_down[0];
+        world.resource_mut::<ButtonInput<KeyCode>>().press(stair_key);
+        frame(&mut world);
+        assert!(world.entity(player).contains::<Autoexplore>(), "the request only asks for a cancel");
+        frame(&mut world);
+        assert!(!world.entity(player).contains::<Autoexplore>());
+        assert_eq!(world.get::<AutoMoveToStair>(player).map(|travel| travel.target), Some((4, 1)));
+        frame(&mut world);
+        assert!(world.entity(player).contains::<AutoMoveToStair>(), "the cancel doesn't catch the travel it made way for");
+    }
+
+    #[test]
+    fn a_held_dive_walks_to_discovered_down_stairs_instead_of_teleporting() {
+        let dive_from = |discovered: bool| {
+            let mut map = GameMap::new(8, 3);
+            map.fill_rect(URect::new(1, 1, 7, 2), TileType::Floor);
+            map.set(6, 1, TileType::StairDown);
+            let mut remembered = RememberedFeatures::default();
+            if discovered {
+                remembered.features.insert((6, 1), TileType::StairDown);
             }
-            commands.entity(entity).remove::<AutoMoveToStair>();
-        }
+
+            let mut world = World::new();
+            world.insert_resource(map);
+            world.insert_resource(remembered);
+            world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+            world.init_resource::<ButtonInput<KeyCode>>();
+            world.init_resource::<KeyBindings>();
+            world.init_resource::<Time>();
+            world.init_resource::<StairDive>();
+            world.init_resource::<DungeonConfig>();
+            let player = world.spawn(Player { x: 1, y: 1 }).id();
+
+            let stair_key = world.resource::<KeyBindings>().stair_down[0];
+            world.resource_mut::<ButtonInput<KeyCode>>().press(stair_key);
+            world.run_system_cached(handle_stair_dive).unwrap();
+            world.resource_mut::<ButtonInput<KeyCode>>().clear();
+            world.resource_mut::<Time>().advance_by(std::time::Duration::from_secs(1));
+            world.run_system_cached(handle_stair_dive).unwrap();
+            world.flush();
+
+            assert_eq!(world.get::<Player>(player).map(|player| (player.x, player.y)), Some((1, 1)), "the dive never moves the player itself");
+            let travel = world.get::<AutoMoveToStair>(player).map(|travel| (travel.target, travel.path.len(), travel.take_on_arrival));
+            (travel, world.resource::<StairDive>().is_pressed())
+        };
+
+        assert_eq!(dive_from(true), (Some(((6, 1), 5, true)), true));
+        assert_eq!(dive_from(false), (None, false), "unseen stairs end the dive");
     }
 }
@@ -1,26 +1,101 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use bevy_ecs_tilemap::prelude::*;
-use rand::Rng;
 
-use crate::assets::{GameAssets, SpriteDatabase, sprite_position_to_index};
+use crate::assets::{GameAssets, SpriteDatabase};
 use crate::components::*;
-use crate::map::{GameMap, select_biome_asset};
-use crate::input_handler::{LevelChangeEvent, RegenerateMapEvent, SpawnPosition};
+use crate::map::{GameMap, get_tile_texture_index, tile_variant_rng};
+use crate::input_handler::{LevelChangeEvent, MoveBuffer, RegenerateMapEvent, RegenerateSeed};
+use crate::map_generation::MapGenParams;
+use crate::player::retile;
 use crate::states::GameState;
 use crate::biome::BiomeType;
+use crate::constants::TILE_SIZE;
+use crate::metrics::{PerfMetrics, PerfStat};
 
 pub struct LevelManagerPlugin;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FadePhase {
+    Out,  // Overlay darkening over the old level
+    Swap, // Fully covered; handle_level_transitions rebuilds the map this frame
+    Build, // Fully covered; stream_map_tiles spawns the new tiles a batch per frame
+    In,   // Overlay clearing over the new level
+}
+
+// Fade that covers level transitions; FOV runs underneath once the tiles are built so the new level is lit when revealed
+#[derive(Resource)]
+pub struct LevelTransitionFade {
+    pub duration: f32, // Seconds for each half of the fade
+    pub timer: Timer,
+    pub phase: FadePhase,
+    pub pending: Option<(u32, StartPolicy)>,
+}
+
+impl Default for LevelTransitionFade {
+    fn default() -> Self {
+        Self {
+            duration: 0.25,
+            timer: Timer::from_seconds(0.25, TimerMode::Once),
+            phase: FadePhase::Out,
+            pending: None,
+        }
+    }
+}
+
+// State tied to the current layout that every map swap hands over or resets together, plus the
+// run's stair rules that decide what a freshly generated layout contains
+#[derive(SystemParam)]
+pub struct MapSwapState<'w> {
+    stair_mode: Res<'w, StairMode>,
+    start_policy: Res<'w, StartPolicy>,
+    fov_state: ResMut<'w, FovState>,
+    los_cache: ResMut<'w, LosCache>,
+    visible_tiles: ResMut<'w, VisibleTiles>,
+    tile_index: ResMut<'w, TileIndex>,
+    tile_pool: ResMut<'w, TilePool>,
+    los_caches: ResMut<'w, SavedLosCaches>,
+    visit_trail: ResMut<'w, VisitTrail>,
+    tile_build_queue: ResMut<'w, TileBuildQueue>,
+    map_build: Res<'w, MapBuildSettings>,
+    move_buffer: ResMut<'w, MoveBuffer>,
+    dungeon: Res<'w, DungeonConfig>,
+}
+
 impl Plugin for LevelManagerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LevelChangeEvent>()
             .add_event::<RegenerateMapEvent>()
             .init_resource::<CurrentLevel>()
+            .init_resource::<StairMode>()
+            .init_resource::<StartPolicy>()
             .init_resource::<LevelMaps>()
+            .init_resource::<SavedLosCaches>()
+            .init_resource::<LevelSeeds>()
+            .init_resource::<LevelTransitionFade>()
+            .init_resource::<MapBuildSettings>()
+            .init_resource::<TileBuildQueue>()
             .add_systems(Update, (
-                handle_level_transitions,
+                begin_level_transition,
                 handle_map_regeneration,
-            ).run_if(in_state(GameState::Playing)));
+                check_victory,
+                dump_level_png,
+                regenerate_player_region,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (
+                handle_level_transitions,
+                snap_to_arrival.after(handle_level_transitions),
+                stream_map_tiles.after(handle_level_transitions),
+                run_level_transition_fade.after(stream_map_tiles),
+            ).run_if(in_state(GameState::LevelTransition)))
+            // Runs after the command flush so the respawned tiles are visible to the check
+            .add_systems(Update, validate_tile_index
+                .after(handle_level_transitions)
+                .after(handle_map_regeneration)
+                .run_if(|| cfg!(debug_assertions))
+                .run_if(map_ready)
+                .run_if(in_state(GameState::Playing).or(in_state(GameState::LevelTransition))))
+            .add_systems(OnExit(GameState::Victory), end_run);
     }
 }
 
@@ -30,86 +105,209 @@ impl Default for CurrentLevel {
     }
 }
 
-// Helper function to spawn map tiles with biome-aware texture selection and tile pooling
-fn spawn_map_tiles(
-    commands: &mut Commands,
-    map: &GameMap,
+// Leaving the victory screen ends the run. The world spawned on entering Playing is cleared and
+// every piece of per-run state reset, including the saved levels and their seeds, so whatever
+// starts the next run builds a fresh one instead of resuming this one
+pub fn end_run(
+    mut commands: Commands,
+    run_entities: Query<Entity, Or<(With<TileStorage>, With<TileVisibilityState>, With<Player>, With<BiomeParticle>, With<ExamineCursor>)>>,
+    mut los_caches: ResMut<SavedLosCaches>,
+    mut stair_dive: ResMut<StairDive>,
+) {
+    // Pooled tiles still carry TileVisibilityState, so the pool empties along with the map
+    for entity in run_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<GameMap>();
+    commands.insert_resource(CurrentLevel::default());
+    commands.insert_resource(LevelMaps::default());
+    commands.insert_resource(LevelSeeds::default());
+    commands.insert_resource(TilePool::default());
+    commands.insert_resource(TileIndex::default());
+    commands.insert_resource(TileBuildQueue::default());
+    commands.insert_resource(FovState::default());
+    commands.insert_resource(LosCache::default());
+    los_caches.clear();
+    commands.insert_resource(VisibleTiles::default());
+    commands.insert_resource(ExploredTiles::default());
+    commands.insert_resource(RememberedFeatures::default());
+    commands.insert_resource(VisitTrail::default());
+    commands.insert_resource(MoveBuffer::default());
+    commands.insert_resource(ExamineMode::default());
+    commands.insert_resource(ParticleSpawner::default());
+    stair_dive.cancel();
+    println!("Run ended; world cleared");
+}
+
+/// Tiles a level transition spawns per frame while the fade covers the screen; 0 spawns the whole
+/// map in the swap frame
+#[derive(Resource, Default)]
+pub struct MapBuildSettings {
+    pub tiles_per_frame: u32, // Defaults to 0; 1000 streams an 80x50 map over four frames
+}
+
+/// The tilemap currently being streamed in, if any; FOV waits on `map_ready` until it's done
+#[derive(Resource, Default)]
+pub struct TileBuildQueue {
+    job: Option<TileBuildJob>,
+}
+
+impl TileBuildQueue {
+    pub fn is_idle(&self) -> bool {
+        self.job.is_none()
+    }
+}
+
+/// Run condition: no tilemap is part-way through being built
+pub fn map_ready(queue: Option<Res<TileBuildQueue>>) -> bool {
+    queue.is_none_or(|queue| queue.is_idle())
+}
+
+// A tilemap being filled in row-major order, with biome-aware textures and tile pooling
+struct TileBuildJob {
+    tilemap_entity: Entity,
+    storage: TileStorage,
     biome: BiomeType,
-    saved_visibility: &std::collections::HashMap<(u32, u32), TileVisibility>,
-    tile_pool: &mut TilePool,
-    tile_index: &mut TileIndex,
-    assets: &GameAssets,
-    rng: &mut impl Rng,
-) -> (Entity, TileStorage) {
-    let tilemap_entity = commands.spawn_empty().id();
-    let mut tile_storage = TileStorage::empty(TilemapSize { x: map.width, y: map.height });
+    level_seed: u64,
+    saved_visibility: std::collections::HashMap<(u32, u32), TileVisibility>,
+    next: u32,
+    reused_tiles: usize,
+    new_tiles: usize,
+}
+
+impl TileBuildJob {
+    // Spawn the (empty) tilemap so tiles can join it as they're built
+    fn start(
+        commands: &mut Commands,
+        map: &GameMap,
+        biome: BiomeType,
+        saved_visibility: std::collections::HashMap<(u32, u32), TileVisibility>,
+        assets: &GameAssets,
+        level_seed: u64,
+    ) -> Self {
+        let size = TilemapSize { x: map.width, y: map.height };
+        let tile_size = TilemapTileSize { x: TILE_SIZE, y: TILE_SIZE };
+        let grid_size: TilemapGridSize = tile_size.into();
+        debug_assert_eq!(grid_size.x, TILE_SIZE, "tilemap grid must match TILE_SIZE for grid/world conversions");
 
-    let biome_config = biome.get_config();
-    let mut reused_tiles = 0;
-    let mut new_tiles = 0;
+        let tilemap_entity = commands.spawn(TilemapBundle {
+            grid_size,
+            map_type: TilemapType::default(),
+            size,
+            storage: TileStorage::empty(size),
+            texture: TilemapTexture::Single(assets.tiles.clone()),
+            tile_size,
+            anchor: TilemapAnchor::Center,
+            ..Default::default()
+        }).id();
 
-    for y in 0..map.height {
-        for x in 0..map.width {
+        Self {
+            tilemap_entity,
+            storage: TileStorage::empty(size),
+            biome,
+            level_seed,
+            saved_visibility,
+            next: 0,
+            reused_tiles: 0,
+            new_tiles: 0,
+        }
+    }
+
+    // Spawn up to `limit` more tiles (0 = all that remain); true once every tile exists
+    fn spawn_tiles(
+        &mut self,
+        commands: &mut Commands,
+        map: &GameMap,
+        tile_pool: &mut TilePool,
+        tile_index: &mut TileIndex,
+        sprite_db: &SpriteDatabase,
+        limit: u32,
+    ) -> bool {
+        let biome_config = self.biome.get_config();
+        let total = map.width * map.height;
+        let end = if limit == 0 { total } else { self.next.saturating_add(limit).min(total) };
+
+        for i in self.next..end {
+            let (x, y) = (i % map.width, i / map.width);
             let tile_type = map.get(x, y);
-            let (sprite_x, sprite_y) = select_biome_asset(&biome_config, tile_type, map, x, y, rng);
-            let texture_index = sprite_position_to_index(sprite_x, sprite_y);
+            let texture_index = get_tile_texture_index(biome_config, sprite_db, tile_type, map, x, y, &mut tile_variant_rng(self.level_seed, x, y));
 
             let tile_pos = TilePos { x, y };
-            let visibility = saved_visibility.get(&(x, y)).copied().unwrap_or(TileVisibility::Unseen);
+            let visibility = self.saved_visibility.get(&(x, y)).copied().unwrap_or(TileVisibility::Unseen);
+            let components = (
+                TileBundle {
+                    position: tile_pos,
+                    tilemap_id: TilemapId(self.tilemap_entity),
+                    texture_index: TileTextureIndex(texture_index),
+                    ..Default::default()
+                },
+                MapTile { tile_type },
+                TileVisibilityState { visibility },
+            );
 
             // Try to reuse a tile from the pool
             let tile_entity = if let Some(pooled_entity) = tile_pool.acquire() {
-                reused_tiles += 1;
-                commands.entity(pooled_entity).insert((
-                    TileBundle {
-                        position: tile_pos,
-                        tilemap_id: TilemapId(tilemap_entity),
-                        texture_index: TileTextureIndex(texture_index),
-                        ..Default::default()
-                    },
-                    MapTile { tile_type },
-                    TileVisibilityState { visibility },
-                ));
+                self.reused_tiles += 1;
+                commands.entity(pooled_entity).insert(components);
                 pooled_entity
             } else {
-                new_tiles += 1;
-                commands
-                    .spawn((
-                        TileBundle {
-                            position: tile_pos,
-                            tilemap_id: TilemapId(tilemap_entity),
-                            texture_index: TileTextureIndex(texture_index),
-                            ..Default::default()
-                        },
-                        MapTile { tile_type },
-                        TileVisibilityState { visibility },
-                    ))
-                    .id()
+                self.new_tiles += 1;
+                commands.spawn(components).id()
             };
 
-            tile_storage.set(&tile_pos, tile_entity);
+            self.storage.set(&tile_pos, tile_entity);
             tile_index.insert(x, y, tile_entity);
         }
+
+        self.next = end;
+        self.next >= total
     }
 
-    println!("Tile spawning: {} reused from pool, {} newly spawned", reused_tiles, new_tiles);
+    // Hand the filled storage to the tilemap
+    fn finish(self, commands: &mut Commands) {
+        println!("Tile spawning: {} reused from pool, {} newly spawned", self.reused_tiles, self.new_tiles);
+        commands.entity(self.tilemap_entity).insert(self.storage);
+    }
+}
 
-    let tile_size = TilemapTileSize { x: 32.0, y: 32.0 };
-    let grid_size = tile_size.into();
-    let map_type = TilemapType::default();
+// Build a level's whole tilemap this frame
+fn spawn_map_tiles(
+    commands: &mut Commands,
+    map: &GameMap,
+    biome: BiomeType,
+    saved_visibility: std::collections::HashMap<(u32, u32), TileVisibility>,
+    tile_pool: &mut TilePool,
+    tile_index: &mut TileIndex,
+    assets: &GameAssets,
+    sprite_db: &SpriteDatabase,
+    level_seed: u64,
+) {
+    let mut job = TileBuildJob::start(commands, map, biome, saved_visibility, assets, level_seed);
+    job.spawn_tiles(commands, map, tile_pool, tile_index, sprite_db, 0);
+    job.finish(commands);
+}
 
-    commands.entity(tilemap_entity).insert(TilemapBundle {
-        grid_size,
-        map_type,
-        size: TilemapSize { x: map.width, y: map.height },
-        storage: tile_storage.clone(),
-        texture: TilemapTexture::Single(assets.tiles.clone()),
-        tile_size,
-        anchor: TilemapAnchor::Center,
-        ..Default::default()
-    });
+// Continue a streamed tilemap build behind the fade, revealing the level once it's complete
+pub fn stream_map_tiles(
+    mut commands: Commands,
+    mut queue: ResMut<TileBuildQueue>,
+    settings: Res<MapBuildSettings>,
+    map: Option<Res<GameMap>>,
+    mut tile_pool: ResMut<TilePool>,
+    mut tile_index: ResMut<TileIndex>,
+    sprite_db: Res<SpriteDatabase>,
+    mut fade: ResMut<LevelTransitionFade>,
+) {
+    let Some(map) = map else { return; };
+    let Some(job) = queue.job.as_mut() else { return; };
 
-    (tilemap_entity, tile_storage)
+    if job.spawn_tiles(&mut commands, &map, &mut tile_pool, &mut tile_index, &sprite_db, settings.tiles_per_frame) {
+        if let Some(job) = queue.job.take() {
+            job.finish(&mut commands);
+        }
+        fade.phase = FadePhase::In;
+        fade.timer.reset();
+    }
 }
 
 // Helper function to capture current tile visibility states (sparse storage)
@@ -130,109 +328,262 @@ pub fn capture_tile_visibility(
     visibility_data
 }
 
-pub fn handle_level_transitions(
+// Debug-only self-check that pooled tile reuse left no stale TileIndex entries behind
+pub fn validate_tile_index(
+    tile_index: Res<TileIndex>,
+    tile_query: Query<&TilePos>,
+) {
+    if !tile_index.is_changed() {
+        return;
+    }
+
+    let result = tile_index.validate(&tile_query);
+    if let Err(stale) = &result {
+        println!("TileIndex has {} stale entries (first: {:?})", stale.len(), &stale[..stale.len().min(5)]);
+    }
+    debug_assert!(result.is_ok(), "TileIndex out of sync with tile entities");
+}
+
+// Start the fade for a requested level change; the swap itself happens once the screen is covered
+pub fn begin_level_transition(
     mut commands: Commands,
     mut level_change_events: EventReader<LevelChangeEvent>,
+    mut fade: ResMut<LevelTransitionFade>,
+    accessibility: Res<AccessibilitySettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Only the last request in a frame matters
+    let Some(event) = level_change_events.read().last() else { return; };
+
+    fade.pending = Some((event.new_level, event.spawn_position));
+    fade.phase = FadePhase::Out;
+    // Reduced motion swaps straight away with no overlay
+    let duration = if accessibility.reduce_motion { 0.0 } else { fade.duration };
+    fade.timer = Timer::from_seconds(duration, TimerMode::Once);
+
+    if !accessibility.reduce_motion {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.0)),
+            GlobalZIndex(i32::MAX),
+            LevelFadeOverlay,
+        ));
+    }
+
+    next_state.set(GameState::LevelTransition);
+}
+
+pub fn run_level_transition_fade(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fade: ResMut<LevelTransitionFade>,
+    mut overlay_query: Query<(Entity, &mut BackgroundColor), With<LevelFadeOverlay>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    fade.timer.tick(time.delta());
+    let progress = fade.timer.fraction();
+
+    let alpha = match fade.phase {
+        FadePhase::Out => {
+            if fade.timer.finished() {
+                fade.phase = FadePhase::Swap;
+            }
+            progress
+        }
+        FadePhase::Swap | FadePhase::Build => 1.0,
+        FadePhase::In => {
+            if fade.timer.finished() {
+                for (entity, _) in overlay_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+                next_state.set(GameState::Playing);
+            }
+            1.0 - progress
+        }
+    };
+
+    for (_, mut background) in overlay_query.iter_mut() {
+        background.0 = Color::srgba(0.0, 0.0, 0.0, alpha);
+    }
+}
+
+pub fn handle_level_transitions(
+    mut commands: Commands,
+    mut fade: ResMut<LevelTransitionFade>,
     mut current_level: ResMut<CurrentLevel>,
     mut level_maps: ResMut<LevelMaps>,
+    mut level_seeds: ResMut<LevelSeeds>,
     assets: Res<GameAssets>,
-    _sprite_db: Res<SpriteDatabase>,
+    sprite_db: Res<SpriteDatabase>,
     mut player_query: Query<&mut Player>,
     tilemap_query: Query<Entity, With<TileStorage>>,
     tile_visibility_query: Query<Entity, With<TileVisibilityState>>,
     tile_pos_visibility_query: Query<(&TilePos, &TileVisibilityState)>,
     map: Option<Res<GameMap>>,
-    mut fov_settings: ResMut<FovSettings>,
-    mut tile_index: ResMut<TileIndex>,
-    mut tile_pool: ResMut<TilePool>,
+    swap: MapSwapState,
     mut ellipse_mask: ResMut<EllipseMask>,
     mut rng: ResMut<GlobalRng>,
+    mut metrics: ResMut<PerfMetrics>,
 ) {
-    for event in level_change_events.read() {
-        println!("Transitioning to level {}", event.new_level);
-        
-        // Save current tile visibility states before leaving the current level
-        if let Some(current_map) = &map {
-            let current_visibility = capture_tile_visibility(&tile_pos_visibility_query, current_map.width, current_map.height);
-            if let Some(saved_data) = level_maps.maps.get_mut(&current_level.level) {
-                saved_data.tile_visibility = current_visibility;
-            }
+    let MapSwapState { stair_mode, start_policy: _, mut fov_state, mut los_cache, mut visible_tiles, mut tile_index, mut tile_pool, mut los_caches, mut visit_trail, mut tile_build_queue, map_build, mut move_buffer, dungeon } = swap;
+
+    // Only swap while the fade overlay fully covers the screen
+    if fade.phase != FadePhase::Swap {
+        return;
+    }
+    let Some((new_level, spawn_position)) = fade.pending.take() else {
+        fade.phase = FadePhase::In;
+        return;
+    };
+
+    println!("Transitioning to level {}", new_level);
+    
+    // Save current tile visibility states before leaving the current level
+    if let Some(current_map) = &map {
+        let current_visibility = capture_tile_visibility(&tile_pos_visibility_query, current_map.width, current_map.height);
+        if let Some(saved_data) = level_maps.maps.get_mut(&current_level.level) {
+            saved_data.tile_visibility = current_visibility;
         }
+        // Keep the level's LOS results for a later revisit; its geometry is saved unchanged
+        let leaving_cache = std::mem::take(&mut los_cache.entries);
+        los_caches.store(current_level.level, leaving_cache);
+    }
 
-        // Clear existing tilemap
-        for entity in tilemap_query.iter() {
+    // Clear existing tilemap
+    for entity in tilemap_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // Return tile entities to pool after removing tilemap components
+    let mut returned_tiles = 0;
+    for entity in tile_visibility_query.iter() {
+        // Remove all tilemap-specific components to prevent stale references
+        commands.entity(entity).remove::<(TilePos, TilemapId, TileTextureIndex, TileVisible, TileFlip)>();
+        if tile_pool.release(entity) {
+            returned_tiles += 1;
+        } else {
             commands.entity(entity).despawn();
         }
+    }
+    println!("Returned {} tiles to pool (pool size: {})", returned_tiles, tile_pool.len());
+    
+    // Update current level
+    current_level.level = new_level;
 
-        // Return tile entities to pool after removing tilemap components
-        let mut returned_tiles = 0;
-        for entity in tile_visibility_query.iter() {
-            // Remove all tilemap-specific components to prevent stale references
-            commands.entity(entity).remove::<(TilePos, TilemapId, TileTextureIndex, TileVisible, TileFlip)>();
-            tile_pool.release(entity);
-            returned_tiles += 1;
+    // Levels above can't be revisited on a descend-only run, so there's no point keeping them
+    if !stair_mode.allows_ascent() {
+        let before = level_maps.maps.len();
+        level_maps.maps.retain(|&level, _| level >= new_level);
+        for level in 0..new_level {
+            los_caches.forget(level);
         }
-        println!("Returned {} tiles to pool (pool size: {})", returned_tiles, tile_pool.len());
-        
-        // Update current level
-        current_level.level = event.new_level;
-        
-        // Load or generate map for new level
-        let (map, saved_visibility) = if let Some(saved_data) = level_maps.maps.get(&event.new_level) {
-            // Sync biome from saved data
-            current_level.biome = saved_data.biome;
-            let saved_visibility = saved_data.tile_visibility.clone();
-            (GameMap::from_saved_data(saved_data), saved_visibility)
-        } else {
-            let mut map = GameMap::new(80, 50);
-            // Use biome-aware generation
-            // Update ellipse mask for map dimensions
-            ellipse_mask.resize(80, 50);
-            map.generate_with_biome(current_level.biome, event.new_level, rng.as_mut(), &ellipse_mask);
-            map.place_stairs(event.new_level, rng.as_mut());
-            // Create new visibility data for new map (empty HashMap = all Unseen)
-            let new_visibility = std::collections::HashMap::new();
-            // Save new map data with biome
-            level_maps.maps.insert(event.new_level, map.to_saved_data(current_level.biome, new_visibility.clone()));
-            (map, new_visibility)
-        };
-        
-        // Position player at appropriate spawn point
-        if let Ok(mut player) = player_query.single_mut() {
-            let spawn_pos = match event.spawn_position {
-                SpawnPosition::StairUp => {
-                    map.stair_up_pos.unwrap_or((map.width / 2, map.height / 2))
-                },
-                SpawnPosition::StairDown => {
-                    map.stair_down_pos.unwrap_or((map.width / 2, map.height / 2))
-                },
-                SpawnPosition::Center => (map.width / 2, map.height / 2),
-            };
-            
-            player.x = spawn_pos.0;
-            player.y = spawn_pos.1;
-            
-            println!("Player spawned at ({}, {})", player.x, player.y);
+        if level_maps.maps.len() < before {
+            println!("Descend-only: dropped {} level(s) above depth {}", before - level_maps.maps.len(), new_level);
         }
+    }
+    
+    // Load or generate map for new level
+    let (map, saved_visibility) = if let Some(saved_data) = level_maps.maps.get(&new_level) {
+        // Sync biome from saved data
+        current_level.biome = saved_data.biome;
+        let saved_visibility = saved_data.tile_visibility.clone();
+        (GameMap::from_saved_data(saved_data), saved_visibility)
+    } else {
+        // Fresh layout: anything cached under this level number no longer applies
+        los_caches.forget(new_level);
+        let mut map = GameMap::new(80, 50);
+        // Use biome-aware generation
+        // Update ellipse mask for map dimensions
+        ellipse_mask.resize(80, 50);
+        // Generate from the level's stored seed so a dropped level rebuilds identically
+        let seed = level_seeds.seed_for(new_level, rng.as_mut());
+        println!("Generating level {} with seed {}", new_level, seed);
+        let started = PerfMetrics::now();
+        let attempts = map.generate_level_validated(current_level.biome, new_level, dungeon.max_depth, seed, *stair_mode, &ellipse_mask);
+        metrics.record(PerfStat::MapGeneration, started);
+        if attempts > 1 {
+            println!("Level {} needed {} generation attempts", new_level, attempts);
+        }
+        // Create new visibility data for new map (empty HashMap = all Unseen)
+        let new_visibility = std::collections::HashMap::new();
+        // Save new map data with biome
+        level_maps.maps.insert(new_level, map.to_saved_data(current_level.biome, new_visibility.clone()));
+        (map, new_visibility)
+    };
+    
+    // Position player at appropriate spawn point
+    if let Ok(mut player) = player_query.single_mut() {
+        let spawn_pos = map.start_position(spawn_position);
+        player.x = spawn_pos.0;
+        player.y = spawn_pos.1;
         
-        // Clear and rebuild tile index
-        tile_index.clear();
+        println!("Player spawned at ({}, {})", player.x, player.y);
+        if spawn_position == StartPolicy::OnUpStair {
+            if let Some(flavor) = current_level.biome.get_config().descent_flavor {
+                println!("{}", flavor);
+            }
+        }
+    }
+    
+    // Clear and rebuild tile index
+    tile_index.clear();
 
-        // Spawn the new map using the helper function
-        spawn_map_tiles(&mut commands, &map, current_level.biome, &saved_visibility, &mut tile_pool, &mut tile_index, &assets, rng.as_mut());
+    // Spawn the new map, either now or streamed in over the next frames while the screen stays dark
+    let level_seed = level_seeds.seed_for(new_level, rng.as_mut());
+    let streaming = map_build.tiles_per_frame > 0;
+    if streaming {
+        let job = TileBuildJob::start(&mut commands, &map, current_level.biome, saved_visibility, &assets, level_seed);
+        tile_build_queue.job = Some(job);
+        println!("Streaming {} tiles at {} per frame", map.width * map.height, map_build.tiles_per_frame);
+    } else {
+        spawn_map_tiles(&mut commands, &map, current_level.biome, saved_visibility, &mut tile_pool, &mut tile_index, &assets, &sprite_db, level_seed);
+    }
 
-        commands.insert_resource(map);
-        
-        // Trigger FOV recalculation for new level and invalidate LOS cache
-        fov_settings.needs_recalculation = true;
-        fov_settings.los_cache.clear();
-        if fov_settings.cache_hits > 0 || fov_settings.cache_misses > 0 {
-            let hit_rate = fov_settings.cache_hits as f32 / (fov_settings.cache_hits + fov_settings.cache_misses) as f32 * 100.0;
-            println!("LOS cache stats - Hits: {}, Misses: {}, Hit rate: {:.1}%",
-                    fov_settings.cache_hits, fov_settings.cache_misses, hit_rate);
-        }
-        fov_settings.cache_hits = 0;
-        fov_settings.cache_misses = 0;
+    commands.insert_resource(map);
+    
+    // Trigger FOV recalculation for new level, restoring its LOS cache from an earlier visit if there is one
+    fov_state.reset_for_new_map(); // The old position belongs to the previous map
+    los_cache.entries = los_caches.take(new_level).unwrap_or_default();
+    visit_trail.clear();
+    move_buffer.clear();
+    if !los_cache.entries.is_empty() {
+        println!("Restored {} LOS cache entries for level {}", los_cache.entries.len(), new_level);
+    }
+    visible_tiles.0.clear();
+    los_cache.report_and_reset_stats();
+
+    // Reveal the new level, or hold the overlay until stream_map_tiles finishes it
+    fade.phase = if streaming { FadePhase::Build } else { FadePhase::In };
+    fade.timer.reset();
+}
+
+// Movement and camera systems only run while Playing, so put the player and camera on the arrival
+// tile here; otherwise the fade-in shows them at the old level's coordinates
+pub fn snap_to_arrival(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &Player, &mut Transform), (Changed<Player>, Without<GameCamera>)>,
+    mut camera_query: Query<&mut Transform, (With<GameCamera>, Without<Player>)>,
+    map: Option<Res<GameMap>>,
+    sprite_config: Res<PlayerSpriteConfig>,
+) {
+    let Some(map) = map else { return; };
+    let Ok((entity, player, mut transform)) = player_query.single_mut() else { return; };
+
+    // A hop still running from the old level (e.g. onto a trapdoor) would carry on toward its old end tile
+    commands.entity(entity).remove::<MovementAnimation>();
+    let offset = sprite_config.world_offset();
+    transform.translation.x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE + offset.x;
+    transform.translation.y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE + offset.y;
+    transform.translation.z = 1.0;
+
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        camera_transform.translation.x = transform.translation.x;
+        camera_transform.translation.y = transform.translation.y;
     }
 }
 
@@ -241,18 +592,21 @@ pub fn handle_map_regeneration(
     mut regenerate_events: EventReader<RegenerateMapEvent>,
     current_level: Res<CurrentLevel>,
     mut level_maps: ResMut<LevelMaps>,
+    mut level_seeds: ResMut<LevelSeeds>,
     assets: Res<GameAssets>,
-    _sprite_db: Res<SpriteDatabase>,
+    sprite_db: Res<SpriteDatabase>,
     mut player_query: Query<&mut Player>,
     tilemap_query: Query<Entity, With<TileStorage>>,
     tile_visibility_query: Query<Entity, With<TileVisibilityState>>,
-    mut fov_settings: ResMut<FovSettings>,
-    mut tile_index: ResMut<TileIndex>,
-    mut tile_pool: ResMut<TilePool>,
+    swap: MapSwapState,
     mut ellipse_mask: ResMut<EllipseMask>,
     mut rng: ResMut<GlobalRng>,
+    mut metrics: ResMut<PerfMetrics>,
+    mut last_generated: Local<Option<(u32, BiomeType, u64, Vec<TileType>)>>,
 ) {
-    for _event in regenerate_events.read() {
+    let MapSwapState { stair_mode, start_policy, mut fov_state, mut los_cache, mut visible_tiles, mut tile_index, mut tile_pool, mut los_caches, mut visit_trail, tile_build_queue: _, map_build: _, mut move_buffer, dungeon } = swap;
+
+    for event in regenerate_events.read() {
         println!("Regenerating level {}", current_level.level);
 
         // Clear existing tilemap
@@ -260,12 +614,18 @@ pub fn handle_map_regeneration(
             commands.entity(entity).despawn();
         }
 
-        // Return tile entities to pool after removing tilemap components
+        // Return tile entities to pool after removing tilemap components; overflow is despawned so nothing leaks
+        let mut despawned_tiles = 0;
         for entity in tile_visibility_query.iter() {
             // Remove all tilemap-specific components to prevent stale references
             commands.entity(entity).remove::<(TilePos, TilemapId, TileTextureIndex, TileVisible, TileFlip)>();
-            tile_pool.release(entity);
+            if !tile_pool.release(entity) {
+                commands.entity(entity).despawn();
+                despawned_tiles += 1;
+            }
         }
+        println!("Released {} tiles (pool size: {}, despawned overflow: {})",
+            tile_visibility_query.iter().count(), tile_pool.len(), despawned_tiles);
         
         // Generate new map
         let mut map = GameMap::new(80, 50);
@@ -273,40 +633,38 @@ pub fn handle_map_regeneration(
         // Update ellipse mask for map dimensions
         ellipse_mask.resize(80, 50);
         
+        // A changed seed replaces the stored one so re-entering the level reproduces this version
+        let seed = match event.seed {
+            RegenerateSeed::Replay => level_seeds.seed_for(current_level.level, rng.as_mut()),
+            RegenerateSeed::Next => level_seeds.advance(current_level.level, rng.as_mut()),
+            RegenerateSeed::Random => level_seeds.reseed(current_level.level, rng.as_mut()),
+        };
+        println!("Generating level {} with seed {} ({:?})", current_level.level, seed, event.seed);
+
         // Use biome-aware generation
-        map.generate_with_biome(current_level.biome, current_level.level, rng.as_mut(), &ellipse_mask);
-        
-        map.place_stairs(current_level.level, rng.as_mut());
-        
-        // Position player in center of new map
-        if let Ok(mut player) = player_query.single_mut() {
-            // Find a suitable floor position near center
-            let center_x = map.width / 2;
-            let center_y = map.height / 2;
-            
-            // Look for nearby floor tile
-            let mut spawn_pos = (center_x, center_y);
-            for radius in 0..10 {
-                for dx in -(radius as i32)..=(radius as i32) {
-                    for dy in -(radius as i32)..=(radius as i32) {
-                        let x = center_x as i32 + dx;
-                        let y = center_y as i32 + dy;
-                        
-                        if x >= 0 && x < map.width as i32 && y >= 0 && y < map.height as i32 {
-                            let ux = x as u32;
-                            let uy = y as u32;
-                            if map.get(ux, uy) == TileType::Floor {
-                                spawn_pos = (ux, uy);
-                                break;
-                            }
-                        }
-                    }
-                }
-                if map.get(spawn_pos.0, spawn_pos.1) == TileType::Floor {
-                    break;
+        let started = PerfMetrics::now();
+        let attempts = map.generate_level_validated(current_level.biome, current_level.level, dungeon.max_depth, seed, *stair_mode, &ellipse_mask);
+        metrics.record(PerfStat::MapGeneration, started);
+        if attempts > 1 {
+            println!("Level {} needed {} generation attempts", current_level.level, attempts);
+        }
+
+        // Replaying a seed must rebuild the exact same layout; flag it loudly if it doesn't
+        if let Some((level, biome, last_seed, tiles)) = last_generated.as_ref() {
+            if *level == current_level.level && *biome == current_level.biome && *last_seed == seed {
+                let differing = tiles.iter().zip(map.tiles.iter()).filter(|(a, b)| a != b).count();
+                if differing == 0 {
+                    println!("Determinism check: seed {} reproduced the same layout", seed);
+                } else {
+                    println!("WARNING: seed {} produced a different layout ({} tiles differ)", seed, differing);
                 }
             }
-            
+        }
+        *last_generated = Some((current_level.level, current_level.biome, seed, map.tiles.clone()));
+        
+        // Reposition the player by the start policy, as on a new run
+        if let Ok(mut player) = player_query.single_mut() {
+            let spawn_pos = map.start_position(*start_policy);
             player.x = spawn_pos.0;
             player.y = spawn_pos.1;
             
@@ -321,19 +679,219 @@ pub fn handle_map_regeneration(
         tile_index.clear();
 
         // Spawn the new map using the helper function
-        spawn_map_tiles(&mut commands, &map, current_level.biome, &new_visibility, &mut tile_pool, &mut tile_index, &assets, rng.as_mut());
+        spawn_map_tiles(&mut commands, &map, current_level.biome, new_visibility, &mut tile_pool, &mut tile_index, &assets, &sprite_db, seed);
 
         commands.insert_resource(map);
 
         // Trigger FOV recalculation for regenerated map and invalidate LOS cache
-        fov_settings.needs_recalculation = true;
-        fov_settings.los_cache.clear();
-        if fov_settings.cache_hits > 0 || fov_settings.cache_misses > 0 {
-            let hit_rate = fov_settings.cache_hits as f32 / (fov_settings.cache_hits + fov_settings.cache_misses) as f32 * 100.0;
-            println!("LOS cache stats - Hits: {}, Misses: {}, Hit rate: {:.1}%",
-                    fov_settings.cache_hits, fov_settings.cache_misses, hit_rate);
+        fov_state.reset_for_new_map(); // The old position belongs to the previous map
+        los_cache.entries.clear();
+        los_caches.forget(current_level.level);
+        visit_trail.clear();
+        move_buffer.clear();
+        visible_tiles.0.clear();
+        los_cache.report_and_reset_stats();
+    }
+}
+
+// Stepping onto the altar on the bottom level wins the game
+pub fn check_victory(
+    player_query: Query<&Player, Changed<Player>>,
+    map: Option<Res<GameMap>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(map) = map else { return; };
+    if let Ok(player) = player_query.single() {
+        if map.altar_pos == Some((player.x, player.y)) {
+            println!("You reach the altar at the bottom of the world!");
+            next_state.set(GameState::Victory);
         }
-        fov_settings.cache_hits = 0;
-        fov_settings.cache_misses = 0;
+    }
+}
+
+// Debug: F12 writes the current level to level_dumps/ as a PNG for bug reports, named by depth and seed
+pub fn dump_level_png(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    level_seeds: Res<LevelSeeds>,
+    tile_index: Res<TileIndex>,
+    tile_query: Query<&TileVisibilityState>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Some(map) = map else { return; };
+
+    let image = map.render_debug_image(4, |x, y| {
+        tile_index.tiles.get(&(x, y))
+            .and_then(|&entity| tile_query.get(entity).ok())
+            .map_or(TileVisibility::Unseen, |state| state.visibility)
+    });
+
+    let seed = level_seeds.seeds.get(&current_level.level)
+        .map_or("unknown".to_string(), |seed| seed.to_string());
+    let dir = std::path::Path::new("level_dumps");
+    let path = dir.join(format!("depth_{}_seed_{}.png", current_level.level, seed));
+
+    // A failed dump is only worth a log line, never a crash
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        println!("Failed to create {}: {}", dir.display(), e);
+        return;
+    }
+    match image.save(&path) {
+        Ok(()) => println!("Level dumped to {}", path.display()),
+        Err(e) => println!("Failed to write level dump {}: {}", path.display(), e),
+    }
+}
+
+// Half-width of the square regenerate_player_region rebuilds around the player
+const REGION_REGEN_RADIUS: u32 = 7;
+
+// Debug: Shift+G re-rolls the area around the player, leaving the rest of the level and its explored
+// state alone; only the tiles that changed (and their neighbors' wall sprites) are updated
+pub fn regenerate_player_region(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    map: Option<ResMut<GameMap>>,
+    player_query: Query<&Player>,
+    current_level: Res<CurrentLevel>,
+    level_seeds: Res<LevelSeeds>,
+    mut level_maps: ResMut<LevelMaps>,
+    ellipse_mask: Res<EllipseMask>,
+    tile_index: Res<TileIndex>,
+    sprite_db: Res<SpriteDatabase>,
+    mut tile_query: Query<(&mut MapTile, &mut TileTextureIndex)>,
+    mut rng: ResMut<GlobalRng>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyG) ||
+       !(keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
+        return;
+    }
+    let Some(mut map) = map else { return; };
+    let Ok(player) = player_query.single() else { return; };
+
+    let rect = URect::new(
+        player.x.saturating_sub(REGION_REGEN_RADIUS),
+        player.y.saturating_sub(REGION_REGEN_RADIUS),
+        player.x + REGION_REGEN_RADIUS + 1,
+        player.y + REGION_REGEN_RADIUS + 1,
+    );
+    let params = MapGenParams::for_biome(current_level.biome, current_level.level);
+    let changes = map.regenerate_region(rect, &params, &[(player.x, player.y)], &ellipse_mask, rng.as_mut());
+    println!("Regenerated region {:?} around ({}, {}): {} tile(s) changed", rect, player.x, player.y, changes.len());
+    if changes.is_empty() {
+        return;
+    }
+
+    // Wall sprites depend on their neighbors, so refresh the ring around each edit as well
+    let mut retiled = std::collections::HashSet::new();
+    for change in &changes {
+        let (x, y) = change.pos;
+        for ny in y.saturating_sub(1)..=(y + 1).min(map.height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(map.width - 1) {
+                if retiled.insert((nx, ny)) {
+                    retile(&mut tile_query, &tile_index, &sprite_db, &map, &current_level, &level_seeds, (nx, ny));
+                }
+            }
+        }
+    }
+
+    // Keep the saved copy in step so leaving and coming back shows the edited layout
+    if let Some(saved) = level_maps.maps.get_mut(&current_level.level) {
+        saved.tiles = RleTiles::encode(&map.tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::CommandQueue;
+
+    // Build the whole tilemap through a TileBuildJob, `limit` tiles per call, and read back each tile
+    fn build_tiles(map: &GameMap, limit: u32, level_seed: u64) -> Vec<(u32, TileType, TileVisibility)> {
+        let mut world = World::new();
+        let assets = GameAssets { rogues: Handle::default(), tiles: Handle::default(), akkurat_font: Handle::default() };
+        let sprite_db = SpriteDatabase::new();
+        let mut tile_pool = TilePool::default();
+        let mut tile_index = TileIndex::default();
+        let saved_visibility = std::collections::HashMap::from([((3, 3), TileVisibility::Seen)]);
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut job = TileBuildJob::start(&mut commands, map, BiomeType::Caverns, saved_visibility, &assets, level_seed);
+        while !job.spawn_tiles(&mut commands, map, &mut tile_pool, &mut tile_index, &sprite_db, limit) {}
+        job.finish(&mut commands);
+        queue.apply(&mut world);
+
+        map.iter_tiles()
+            .map(|(x, y, _)| {
+                let tile = world.entity(tile_index.tiles[&(x, y)]);
+                (
+                    tile.get::<TileTextureIndex>().unwrap().0,
+                    tile.get::<MapTile>().unwrap().tile_type,
+                    tile.get::<TileVisibilityState>().unwrap().visibility,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn streamed_build_matches_immediate_build() {
+        let mut map = GameMap::new(12, 9);
+        map.fill_rect(URect::new(1, 1, 11, 8), TileType::Floor);
+        map.set(2, 2, TileType::StairUp);
+        map.set(9, 6, TileType::StairDown);
+
+        let immediate = build_tiles(&map, 0, 7);
+        assert_eq!(immediate.len(), (map.width * map.height) as usize);
+        // A batch size that doesn't divide the map exercises the final partial batch
+        assert_eq!(build_tiles(&map, 7, 7), immediate);
+        assert_eq!(immediate[(3 * map.width + 3) as usize].2, TileVisibility::Seen);
+    }
+
+    #[test]
+    fn ending_a_run_clears_the_world_for_the_next_one() {
+        let mut world = World::new();
+        world.insert_resource(GameMap::new(4, 4));
+        world.insert_resource(CurrentLevel { level: 5, biome: BiomeType::Underglade });
+        world.init_resource::<SavedLosCaches>();
+        world.resource_mut::<SavedLosCaches>().store(2, LosEntries::from([((0, 0, 1, 1), true)]));
+        world.init_resource::<LevelMaps>();
+        world.resource_mut::<LevelMaps>().maps.insert(2, GameMap::new(4, 4).to_saved_data(BiomeType::Underglade, Default::default()));
+        world.init_resource::<LevelSeeds>();
+        world.resource_mut::<LevelSeeds>().seeds.insert(2, 99);
+        let mut dive = StairDive::default();
+        dive.press(0.0, 2);
+        world.insert_resource(dive);
+        world.init_resource::<ExploredTiles>();
+        world.resource_mut::<ExploredTiles>().0.insert((1, 1));
+        world.spawn(TileVisibilityState { visibility: TileVisibility::Seen });
+        world.spawn(Player { x: 1, y: 1 });
+        let unrelated = world.spawn_empty().id();
+
+        world.run_system_cached(end_run).unwrap();
+
+        assert!(!world.contains_resource::<GameMap>());
+        assert_eq!(world.resource::<CurrentLevel>().level, 0);
+        assert_eq!(world.resource::<SavedLosCaches>().total_entries(), 0);
+        assert!(world.resource::<LevelMaps>().maps.is_empty());
+        assert!(world.resource::<LevelSeeds>().seeds.is_empty());
+        assert!(!world.resource::<StairDive>().is_pressed());
+        assert!(world.resource::<ExploredTiles>().0.is_empty());
+        assert_eq!(world.query::<&Player>().iter(&world).count(), 0);
+        assert_eq!(world.query::<&TileVisibilityState>().iter(&world).count(), 0);
+        assert!(world.get_entity(unrelated).is_ok());
+    }
+
+    #[test]
+    fn respawning_a_seeded_level_picks_the_same_tile_art() {
+        let mask = EllipseMask::new(40, 30);
+        let mut map = GameMap::new(40, 30);
+        map.generate_level_validated(BiomeType::Caverns, 2, crate::constants::MAX_DEPTH, 5, StairMode::BothWays, &mask);
+
+        let textures = |level_seed| build_tiles(&map, 0, level_seed).into_iter().map(|(texture, _, _)| texture).collect::<Vec<_>>();
+        let first = textures(41);
+        assert_eq!(textures(41), first, "a revisit redraws every tile the same way");
+        assert_ne!(textures(42), first, "another level seed rolls its own variants");
     }
 }
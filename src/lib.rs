@@ -0,0 +1,20 @@
+// The game as a library: generation, FOV, pathfinding and the ECS plugins that drive them.
+// main.rs wires the plugins into an App; benches and CI fuzzing use `simulation` with no App at all
+pub mod components;
+pub mod constants;
+pub mod states;
+pub mod assets;
+pub mod map;
+pub mod map_generation;
+pub mod map_generation_compact;
+pub mod player;
+pub mod input_handler;
+pub mod camera;
+pub mod level_manager;
+pub mod fov;
+pub mod biome;
+pub mod ui;
+pub mod particles;
+pub mod simulation;
+pub mod water;
+pub mod metrics;
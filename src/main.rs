@@ -6,32 +6,20 @@ use bevy::{
 use bevy_asset_loader::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
-mod components;
-mod states;
-mod assets;
-mod map;
-mod map_generation;
-mod map_generation_compact;
-mod player;
-mod input_handler;
-mod camera;
-mod level_manager;
-mod fov;
-mod biome;
-mod ui;
-mod particles;
-
-use assets::{GameAssets, SpriteDatabase};
-use states::GameState;
-use map::spawn_map;
-use player::*;
-use input_handler::*;
-use camera::*;
-use level_manager::LevelManagerPlugin;
-use fov::FovPlugin;
-use ui::UiPlugin;
-use particles::ParticlePlugin;
-use components::{*, GlobalRng, EllipseMask};
+use deepdive::assets::{GameAssets, SpriteDatabase};
+use deepdive::states::GameState;
+use deepdive::map::spawn_map;
+use deepdive::player::*;
+use deepdive::input_handler::*;
+use deepdive::camera::*;
+use deepdive::level_manager::LevelManagerPlugin;
+use deepdive::fov::FovPlugin;
+use deepdive::ui::UiPlugin;
+use deepdive::particles::ParticlePlugin;
+use deepdive::water::{self, WaterPlugin};
+use deepdive::metrics::MetricsPlugin;
+use deepdive::components::{*, GlobalRng, EllipseMask};
+use deepdive::constants::TILE_SIZE;
 
 // System sets for organizing update systems
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
@@ -58,6 +46,8 @@ fn main() {
         .add_plugins(FovPlugin)
         .add_plugins(UiPlugin)
         .add_plugins(ParticlePlugin)
+        .add_plugins(WaterPlugin)
+        .add_plugins(MetricsPlugin)
         // Add diagnostics plugins for performance monitoring
         .add_plugins(FrameTimeDiagnosticsPlugin::default())
         .add_plugins(LogDiagnosticsPlugin::default())
@@ -65,12 +55,23 @@ fn main() {
         .init_resource::<TileIndex>()
         .init_resource::<TilePool>()
         .init_resource::<KeyBindings>()
+        .init_resource::<MoveBuffer>()
+        .init_resource::<AutoexploreSettings>()
+        .init_resource::<VisitTrail>()
+        .init_resource::<StairDive>()
+        .init_resource::<DungeonConfig>()
+        .init_resource::<CameraMode>()
+        .init_resource::<ExamineMode>()
         .insert_resource(EllipseMask::new(80, 50)) // Pre-calculate ellipse boundary for 80x50 maps
         .insert_resource(GlobalRng::new())
+        .init_resource::<TrapRng>()
         // Register component types for reflection
         .register_type::<Player>()
         .register_type::<MovementAnimation>()
         .register_type::<MovementInput>()
+        .register_type::<Inventory>()
+        .register_type::<Facing>()
+        .register_type::<SpriteAnimation>()
         .register_type::<Autoexplore>()
         .register_type::<AutoMoveToStair>()
         .register_type::<MapTile>()
@@ -81,15 +82,34 @@ fn main() {
         .register_type::<CameraFollow>()
         .register_type::<GameCamera>()
         .register_type::<DepthIndicator>()
+        // Tuning resources, so they can be edited live from an inspector
+        .register_type::<CurrentLevel>()
+        .register_type::<StairMode>()
+        .register_type::<DungeonConfig>()
+        .register_type::<StartPolicy>()
+        .register_type::<FovConfig>()
+        .register_type::<FovState>()
+        .register_type::<MemoryFadeSettings>()
+        .register_type::<AccessibilitySettings>()
+        .register_type::<CameraMode>()
+        .register_type::<AutoexploreSettings>()
+        .register_type::<ParticleSettings>()
+        .register_type::<ParticleSpawner>()
+        .register_type::<WindState>()
+        .register_type::<water::WaterAnimation>()
         .insert_resource(ClearColor(Color::BLACK)) // Set background to black
-        .insert_resource(SpriteDatabase::new()) // Add sprite database resource
+        .insert_resource(SpriteDatabase::load_or_default("assets/config/sprite_database.ron")) // Sheet layout, falls back to built-in sprites
         // Cache player sprite configuration
-        .insert_resource(PlayerSpriteConfig {
-            sprite_rect: Rect::new(128.5, 128.5, 159.5, 159.5), // Player at (4,4) with 31x31 extract
-            custom_size: Vec2::new(32.0, 32.0),
-        })
+        .insert_resource(PlayerSpriteConfig::single_frame(
+            Rect::new(128.5, 128.5, 159.5, 159.5), // Player at (4,4) with 31x31 extract
+            Vec2::splat(TILE_SIZE),
+        ))
         // Add player movement event
         .add_event::<PlayerMoveIntent>()
+        .add_event::<StairArrivalEvent>()
+        .add_event::<PlayerOnStairs>()
+        .add_event::<PlayerLeftStairs>()
+        .add_event::<CancelAutoActions>()
         .add_loading_state(
             LoadingState::new(GameState::AssetLoading)
                 .continue_to_state(GameState::Playing)
@@ -102,25 +122,54 @@ fn main() {
             GameplaySet::Debug,
         ).chain().run_if(in_state(GameState::Playing)))
         .add_systems(Startup, setup_camera)
-        .add_systems(OnEnter(GameState::Playing), (
+        // Spawn the world once after loading; resuming from Paused must not respawn it
+        .add_systems(OnTransition { exited: GameState::AssetLoading, entered: GameState::Playing }, (
             spawn_map,
             spawn_player.after(spawn_map),
             setup_camera_follow.after(spawn_player)
         ))
         .add_systems(Update, (
-            detect_movement_input,
+            // Free camera mode takes over the movement keys, so player input pauses
+            detect_movement_input.run_if(camera_following),
             handle_movement_input.after(detect_movement_input),
-            handle_stair_interaction,
-            toggle_autoexplore,
-            run_autoexplore,
-            run_auto_move_to_stair,
+            handle_stair_interaction.run_if(camera_following),
+            handle_stair_dive.after(handle_stair_interaction).run_if(camera_following),
+            toggle_autoexplore.run_if(camera_following),
+            adjust_autoexplore_speed,
+            plan_autoexplore,
+            run_auto_move::<Autoexplore>.after(plan_autoexplore),
+            run_auto_move::<AutoMoveToStair>,
+        ).run_if(examine_inactive).in_set(GameplaySet::Input))
+        .add_systems(Update, (
+            toggle_examine_mode,
+            move_examine_cursor.after(toggle_examine_mode),
+            // Between the systems that request a cancel and the ones that start or step auto actions
+            cancel_auto_actions
+                .after(toggle_examine_mode)
+                .after(handle_movement_input)
+                .after(toggle_autoexplore)
+                .before(handle_stair_interaction)
+                .before(plan_autoexplore)
+                .before(run_auto_move::<Autoexplore>)
+                .before(run_auto_move::<AutoMoveToStair>),
         ).in_set(GameplaySet::Input))
+        .add_systems(Update, handle_window_focus.run_if(
+            in_state(GameState::Playing).or(in_state(GameState::Paused))
+        ))
         .add_systems(Update, (
             animate_movement,
             move_player.after(animate_movement),
+            pickup_items.after(move_player),
+            update_traps.after(move_player),
+            fall_through_trapdoors.after(move_player),
+            record_visit_trail.after(move_player),
+            track_stair_presence.after(animate_movement),
+            animate_player_sprite.after(animate_movement),
         ).in_set(GameplaySet::Movement))
         .add_systems(Update, (
-            camera_follow_system,
+            toggle_camera_mode,
+            camera_follow_system.after(toggle_camera_mode),
+            free_camera_system.after(toggle_camera_mode),
             camera_zoom_system,
         ).in_set(GameplaySet::Camera))
         .add_systems(Update, (
@@ -1,12 +1,75 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
-use rand::Rng;
+use rand::{Rng, RngCore};
 use std::collections::HashSet;
 
-use crate::assets::{GameAssets, SpriteDatabase, sprite_position_to_index};
-use crate::components::{TileType, MapTile, SavedMapData, CurrentLevel, LevelMaps, TileVisibilityState, TileVisibility, TileIndex, GlobalRng, EllipseMask};
-use crate::biome::{BiomeType, BiomeConfig};
-use crate::map_generation::{MapGenParams, get_generator};
+use crate::assets::{GameAssets, SpriteDatabase};
+use crate::components::{TileType, MapTile, SavedMapData, CurrentLevel, LevelMaps, LevelSeeds, TileVisibilityState, TileVisibility, TileIndex, GlobalRng, EllipseMask, ItemKind, StairMode, StartPolicy, RleTiles, DungeonConfig};
+use crate::biome::{BiomeType, BiomeConfig, move_cost};
+use crate::map_generation::{MapGenParams, CorridorStyle, ConnectivityStrategy, get_generator, corridor_path, brush_offsets};
+use crate::constants::TILE_SIZE;
+use crate::metrics::{PerfMetrics, PerfStat};
+
+/// A broken generation invariant reported by `GameMap::validate`
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapDefect {
+    /// The map has no walkable tiles at all
+    NoWalkableTiles,
+    /// Walkable tiles form more than one connected region
+    Disconnected { components: usize },
+    /// A recorded stair position doesn't hold the matching stair tile
+    StairMismatch { pos: (u32, u32), expected: TileType, found: TileType },
+    /// A walkable tile lies outside the map boundary
+    WalkableOutsideBoundary { pos: (u32, u32) },
+    /// The up and down stairs can't reach each other over walkable tiles
+    StairsUnreachable { up: (u32, u32), down: (u32, u32) },
+    /// Too little walkable space to make a playable level
+    TooFewWalkable { count: usize, min: usize },
+    /// The stairs are close enough to skip the level entirely
+    StairsTooClose { up: (u32, u32), down: (u32, u32), distance: u32 },
+}
+
+impl MapDefect {
+    /// How badly the defect breaks a level, 1 (cosmetic) to 4 (unplayable); choosing between
+    /// flawed layouts weighs the worst defect first, then how many there are
+    pub fn severity(&self) -> u8 {
+        match self {
+            MapDefect::NoWalkableTiles => 4,
+            MapDefect::StairMismatch { .. } | MapDefect::StairsUnreachable { .. } => 3,
+            MapDefect::Disconnected { .. } | MapDefect::TooFewWalkable { .. } => 2,
+            MapDefect::WalkableOutsideBoundary { .. } | MapDefect::StairsTooClose { .. } => 1,
+        }
+    }
+}
+
+/// Generation attempts before `generate_level_validated` settles for the best map it produced
+pub const MAX_GENERATION_ATTEMPTS: u32 = 5;
+/// Fewer walkable tiles than this counts as a degenerate level
+pub const MIN_WALKABLE_TILES: usize = 200;
+/// Stairs closer than this (Manhattan distance) count as a degenerate level
+pub const MIN_STAIR_DISTANCE: u32 = 10;
+
+// Floor at least this far from any wall counts as open chamber for stair and item placement
+pub const OPEN_CHAMBER_OPENNESS: u32 = 3;
+
+// Extra cost of routing over a known trap, so planned paths detour unless the way around is much longer
+pub const KNOWN_TRAP_COST: u32 = 20;
+
+/// A single tile edit between two versions of a map
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileChange {
+    pub pos: (u32, u32),
+    pub from: TileType,
+    pub to: TileType,
+}
+
+/// Point-in-time copy of a map's tiles, used as the baseline for `GameMap::diff`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSnapshot {
+    pub width: u32,
+    pub height: u32,
+    pub tiles: Vec<TileType>,
+}
 
 #[derive(Resource)]
 pub struct GameMap {
@@ -15,6 +78,12 @@ pub struct GameMap {
     pub tiles: Vec<TileType>,
     pub stair_up_pos: Option<(u32, u32)>,
     pub stair_down_pos: Option<(u32, u32)>,
+    // Only the bottom level has an altar, placed where its down stair would go
+    pub altar_pos: Option<(u32, u32)>,
+    // Per-tile distance to the nearest wall, filled by compute_openness
+    openness: Vec<u32>,
+    // Tiles changed through `set` since the last take_edits; report_map_edits sends them as MapChanged
+    edits: Vec<(u32, u32)>,
 }
 
 impl GameMap {
@@ -26,6 +95,9 @@ impl GameMap {
             tiles,
             stair_up_pos: None,
             stair_down_pos: None,
+            altar_pos: None,
+            openness: Vec::new(),
+            edits: Vec::new(),
         }
     }
 
@@ -41,15 +113,501 @@ impl GameMap {
         self.tiles[self.idx(x, y)]
     }
 
+    // Every tile as (x, y, type) in row-major order, the same order as nested y/x loops
+    pub fn iter_tiles(&self) -> impl Iterator<Item = (u32, u32, TileType)> + '_ {
+        let width = self.width.max(1);
+        self.tiles.iter().enumerate().map(move |(i, &tile)| (i as u32 % width, i as u32 / width, tile))
+    }
+
+    // Positions of plain floor tiles, row-major
+    pub fn iter_floor(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.iter_tiles().filter(|&(_, _, tile)| tile == TileType::Floor).map(|(x, y, _)| (x, y))
+    }
+
+    // Debug image of the level, `scale` pixels per tile, colored by type and dimmed by visibility.
+    // Rows are flipped so the image reads the same way as the screen (grid y grows upward)
+    pub fn render_debug_image(&self, scale: u32, visibility_of: impl Fn(u32, u32) -> TileVisibility) -> image::RgbImage {
+        let scale = scale.max(1);
+        let mut img = image::RgbImage::new(self.width * scale, self.height * scale);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let [r, g, b] = match self.get(x, y) {
+                    TileType::Floor => [150, 140, 120],
+                    TileType::Wall => [60, 55, 50],
+                    TileType::Water => [40, 90, 200],
+                    TileType::StairUp => [80, 220, 80],
+                    TileType::StairDown => [220, 80, 80],
+                    TileType::Altar => [240, 200, 60],
+                    TileType::Item(_) => [200, 120, 220],
+                    TileType::TrapHidden => [120, 90, 60],
+                    TileType::TrapRevealed => [230, 120, 30],
+                    TileType::Trapdoor => [140, 40, 140],
+                };
+                let brightness = match visibility_of(x, y) {
+                    TileVisibility::Visible => 1.0,
+                    TileVisibility::Seen => 0.5,
+                    TileVisibility::Unseen => 0.15,
+                };
+                let pixel = image::Rgb([r, g, b].map(|c: u8| (c as f32 * brightness) as u8));
+                let top = (self.height - 1 - y) * scale;
+                for py in top..top + scale {
+                    for px in x * scale..(x + 1) * scale {
+                        img.put_pixel(px, py, pixel);
+                    }
+                }
+            }
+        }
+        img
+    }
+
+    // Grid cell under a world-space point (tiles are centered on the origin), None outside the map
+    pub fn world_to_grid(&self, world: Vec2) -> Option<(u32, u32)> {
+        let x = (world.x / TILE_SIZE + self.width as f32 / 2.0).floor();
+        let y = (world.y / TILE_SIZE + self.height as f32 / 2.0).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+
     // Helper method to set tile at position
     #[inline]
     pub fn set(&mut self, x: u32, y: u32, tile: TileType) {
         let idx = self.idx(x, y);
-        self.tiles[idx] = tile;
+        if self.tiles[idx] != tile {
+            self.tiles[idx] = tile;
+            self.edits.push((x, y));
+        }
     }
-    
-    // Note: is_within_ellipse moved to EllipseMask resource for better performance
-    
+
+    // Positions changed through `set` since the last call, oldest first
+    pub fn take_edits(&mut self) -> Vec<(u32, u32)> {
+        std::mem::take(&mut self.edits)
+    }
+
+    // Set every tile to `tile`
+    pub fn fill(&mut self, tile: TileType) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set(x, y, tile);
+            }
+        }
+    }
+
+    // Set every tile inside `rect` (max exclusive, clamped to the map) to `tile`
+    pub fn fill_rect(&mut self, rect: URect, tile: TileType) {
+        for y in rect.min.y.min(self.height)..rect.max.y.min(self.height) {
+            for x in rect.min.x.min(self.width)..rect.max.x.min(self.width) {
+                self.set(x, y, tile);
+            }
+        }
+    }
+
+    // Set the outermost ring of tiles to `tile`, leaving the interior alone
+    pub fn border(&mut self, tile: TileType) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        for x in 0..self.width {
+            self.set(x, 0, tile);
+            self.set(x, self.height - 1, tile);
+        }
+        for y in 0..self.height {
+            self.set(0, y, tile);
+            self.set(self.width - 1, y, tile);
+        }
+    }
+
+    /// Copy of the tiles inside `rect` (max exclusive, clamped to the map), translated so
+    /// `rect.min` becomes (0, 0). Stairs outside the region are dropped.
+    pub fn cropped(&self, rect: URect) -> GameMap {
+        let min_x = rect.min.x.min(self.width);
+        let min_y = rect.min.y.min(self.height);
+        let max_x = rect.max.x.min(self.width).max(min_x);
+        let max_y = rect.max.y.min(self.height).max(min_y);
+
+        let mut cropped = GameMap::new(max_x - min_x, max_y - min_y);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                cropped.set(x - min_x, y - min_y, self.get(x, y));
+            }
+        }
+
+        let translate = |pos: Option<(u32, u32)>| pos.and_then(|(x, y)| {
+            (x >= min_x && x < max_x && y >= min_y && y < max_y).then(|| (x - min_x, y - min_y))
+        });
+        cropped.stair_up_pos = translate(self.stair_up_pos);
+        cropped.stair_down_pos = translate(self.stair_down_pos);
+        cropped.altar_pos = translate(self.altar_pos);
+        cropped
+    }
+
+    /// Copy resized to `new_width` x `new_height`, anchored at (0, 0). Overlapping tiles are
+    /// kept, new area is filled with `fill`, and stairs that fall off the edge are dropped.
+    pub fn resized(&self, new_width: u32, new_height: u32, fill: TileType) -> GameMap {
+        let mut resized = GameMap::new(new_width, new_height);
+        resized.fill(fill);
+
+        for y in 0..self.height.min(new_height) {
+            for x in 0..self.width.min(new_width) {
+                resized.set(x, y, self.get(x, y));
+            }
+        }
+
+        let keep = |pos: Option<(u32, u32)>| pos.filter(|&(x, y)| x < new_width && y < new_height);
+        resized.stair_up_pos = keep(self.stair_up_pos);
+        resized.stair_down_pos = keep(self.stair_down_pos);
+        resized.altar_pos = keep(self.altar_pos);
+        resized
+    }
+
+    /// Mirror image left to right; stairs move with their tiles
+    pub fn flipped_horizontal(&self) -> GameMap {
+        let width = self.width;
+        self.transformed(self.width, self.height, |x, y| (width - 1 - x, y))
+    }
+
+    /// Mirror image top to bottom; stairs move with their tiles
+    pub fn flipped_vertical(&self) -> GameMap {
+        let height = self.height;
+        self.transformed(self.width, self.height, |x, y| (x, height - 1 - y))
+    }
+
+    /// Quarter turn clockwise as seen on screen (grid y grows upward), swapping width and height
+    pub fn rotated_90(&self) -> GameMap {
+        let width = self.width;
+        self.transformed(self.height, self.width, |x, y| (y, width - 1 - x))
+    }
+
+    // Copy with every tile (and the stairs) moved through `to_new`, which must be a bijection onto the new size
+    fn transformed(&self, new_width: u32, new_height: u32, to_new: impl Fn(u32, u32) -> (u32, u32)) -> GameMap {
+        let mut transformed = GameMap::new(new_width, new_height);
+        for (x, y, tile) in self.iter_tiles() {
+            let (nx, ny) = to_new(x, y);
+            transformed.set(nx, ny, tile);
+        }
+        transformed.stair_up_pos = self.stair_up_pos.map(|(x, y)| to_new(x, y));
+        transformed.stair_down_pos = self.stair_down_pos.map(|(x, y)| to_new(x, y));
+        transformed.altar_pos = self.altar_pos.map(|(x, y)| to_new(x, y));
+        transformed
+    }
+
+    // Write `tile` at (x, y) if it's inside the map and the boundary; true if the tile changed
+    fn carve_tile(&mut self, x: u32, y: u32, tile: TileType, ellipse_mask: &EllipseMask) -> bool {
+        if !self.is_within_boundary(x, y, ellipse_mask) || self.get(x, y) == tile {
+            return false;
+        }
+        self.set(x, y, tile);
+        true
+    }
+
+    /// Cardinal neighbors of `pos` that can be entered in `biome`, with their step cost.
+    /// Shared traversal core for `find_path` and `bfs_distance`. Revealed traps and trapdoors
+    /// carry KNOWN_TRAP_COST on top, so autoexplore and stair travel avoid them.
+    pub fn passable_neighbors(&self, pos: (u32, u32), biome: BiomeType) -> impl Iterator<Item = ((u32, u32), u32)> + '_ {
+        let (x, y) = pos;
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|&(nx, ny)| nx < self.width && ny < self.height)
+        .filter_map(move |(nx, ny)| {
+            let tile = self.get(nx, ny);
+            let penalty = if matches!(tile, TileType::TrapRevealed | TileType::Trapdoor) { KNOWN_TRAP_COST } else { 0 };
+            move_cost(tile, biome).map(|cost| ((nx, ny), cost + penalty))
+        })
+    }
+
+    /// Number of steps on the shortest walk from `from` to `to`, or None if either end is off the map
+    /// or `to` is unreachable. This is an unweighted step count over the same passable neighbors
+    /// `find_path` uses; it ignores their step costs, so where swimming or known traps make the
+    /// cheapest path longer than the shortest one, `find_path(..).len()` can exceed it
+    pub fn bfs_distance(&self, from: (u32, u32), to: (u32, u32), biome: BiomeType) -> Option<u32> {
+        let in_bounds = |(x, y): (u32, u32)| x < self.width && y < self.height;
+        if !in_bounds(from) || !in_bounds(to) {
+            return None;
+        }
+        if from == to {
+            return Some(0);
+        }
+
+        let mut distance = vec![u32::MAX; self.tiles.len()];
+        let mut queue = std::collections::VecDeque::new();
+        distance[self.idx(from.0, from.1)] = 0;
+        queue.push_back(from);
+
+        while let Some(pos) = queue.pop_front() {
+            let next = distance[self.idx(pos.0, pos.1)] + 1;
+            for (neighbor, _) in self.passable_neighbors(pos, biome) {
+                let n_idx = self.idx(neighbor.0, neighbor.1);
+                if distance[n_idx] != u32::MAX {
+                    continue;
+                }
+                if neighbor == to {
+                    return Some(next);
+                }
+                distance[n_idx] = next;
+                queue.push_back(neighbor);
+            }
+        }
+
+        None
+    }
+
+    // Floor tile closest to the map center, or the center itself on a map with no floor
+    pub fn floor_nearest_center(&self) -> (u32, u32) {
+        let center = (self.width / 2, self.height / 2);
+        self.iter_floor()
+            .min_by_key(|&(x, y)| x.abs_diff(center.0) + y.abs_diff(center.1))
+            .unwrap_or(center)
+    }
+
+    // Where the player appears under `policy`; stair policies fall back to the center when the map lacks that stair
+    pub fn start_position(&self, policy: StartPolicy) -> (u32, u32) {
+        match policy {
+            StartPolicy::Center => None,
+            StartPolicy::OnUpStair => self.stair_up_pos,
+            StartPolicy::NearDownStair => self.stair_down_pos,
+        }.unwrap_or_else(|| self.floor_nearest_center())
+    }
+
+    /// Nearest tile of `kind` by walking distance from `from` (not straight-line), restricted to
+    /// tiles `accept` allows, e.g. ones the player has discovered. None if none is reachable
+    pub fn nearest_walkable_tile_of_type(&self, from: (u32, u32), kind: TileType, biome: BiomeType,
+                                         accept: impl Fn(u32, u32) -> bool) -> Option<(u32, u32)> {
+        let is_match = |(x, y): (u32, u32)| self.get(x, y) == kind && accept(x, y);
+        if is_match(from) {
+            return Some(from);
+        }
+
+        let mut visited = vec![false; self.tiles.len()];
+        let mut queue = std::collections::VecDeque::new();
+        visited[self.idx(from.0, from.1)] = true;
+        queue.push_back(from);
+
+        while let Some(pos) = queue.pop_front() {
+            for (neighbor, _) in self.passable_neighbors(pos, biome) {
+                let n_idx = self.idx(neighbor.0, neighbor.1);
+                if visited[n_idx] {
+                    continue;
+                }
+                if is_match(neighbor) {
+                    return Some(neighbor);
+                }
+                visited[n_idx] = true;
+                queue.push_back(neighbor);
+            }
+        }
+
+        None
+    }
+
+    /// Fill a disc of `radius` tiles around `center`, clipped to the map and boundary.
+    /// Returns how many tiles changed.
+    pub fn carve_circle(&mut self, center: (u32, u32), radius: u32, tile: TileType, ellipse_mask: &EllipseMask) -> usize {
+        let (cx, cy) = (center.0 as i64, center.1 as i64);
+        let r = radius as i64;
+        let mut changed = 0;
+
+        for y in (cy - r).max(0)..=(cy + r).min(self.height as i64 - 1) {
+            for x in (cx - r).max(0)..=(cx + r).min(self.width as i64 - 1) {
+                if (x - cx).pow(2) + (y - cy).pow(2) <= r * r && self.carve_tile(x as u32, y as u32, tile, ellipse_mask) {
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Fill `rect` (max exclusive, as in `cropped`), clipped to the map and boundary.
+    /// Returns how many tiles changed.
+    pub fn carve_rect(&mut self, rect: URect, tile: TileType, ellipse_mask: &EllipseMask) -> usize {
+        let mut changed = 0;
+        for y in rect.min.y..rect.max.y.min(self.height) {
+            for x in rect.min.x..rect.max.x.min(self.width) {
+                if self.carve_tile(x, y, tile, ellipse_mask) {
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn snapshot(&self) -> MapSnapshot {
+        MapSnapshot {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.clone(),
+        }
+    }
+
+    /// Tiles that changed since `baseline` was taken; empty when nothing changed.
+    /// Both maps must share dimensions.
+    pub fn diff(&self, baseline: &MapSnapshot) -> Vec<TileChange> {
+        debug_assert!(baseline.width == self.width && baseline.height == self.height, "diff requires matching map dimensions");
+
+        baseline.tiles.iter()
+            .zip(self.tiles.iter())
+            .enumerate()
+            .filter(|(_, (from, to))| from != to)
+            .map(|(i, (&from, &to))| TileChange {
+                pos: (i as u32 % self.width, i as u32 / self.width),
+                from,
+                to,
+            })
+            .collect()
+    }
+
+    // Replay changes forward; swap from/to before applying to undo them
+    pub fn apply_diff(&mut self, changes: &[TileChange]) {
+        for change in changes {
+            self.set(change.pos.0, change.pos.1, change.to);
+        }
+    }
+
+    /// Re-carve `rect` (max exclusive, clipped to the map and boundary) from a freshly generated
+    /// layout, then tunnel any cut-off pocket back to the rest of the map. Only terrain (floor,
+    /// wall, water) is re-rolled: stairs, the altar, items and traps stay, and so does walkable
+    /// ground at each `keep` position (e.g. under the player). Nothing outside `rect` changes.
+    /// Returns every tile that changed
+    pub fn regenerate_region(&mut self, rect: URect, params: &MapGenParams, keep: &[(u32, u32)],
+                             ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) -> Vec<TileChange> {
+        let baseline = self.snapshot();
+        let clip = URect::new(
+            rect.min.x.min(self.width),
+            rect.min.y.min(self.height),
+            rect.max.x.min(self.width),
+            rect.max.y.min(self.height),
+        );
+        if clip.is_empty() {
+            return Vec::new();
+        }
+        let fresh = get_generator().generate(self.width, self.height, params, rng);
+
+        for y in clip.min.y..clip.max.y {
+            for x in clip.min.x..clip.max.x {
+                let current = self.get(x, y);
+                if !matches!(current, TileType::Floor | TileType::Wall | TileType::Water)
+                    || !self.is_within_boundary(x, y, ellipse_mask) {
+                    continue;
+                }
+                let mut tile = fresh[self.idx(x, y)];
+                if keep.contains(&(x, y)) && !tile.is_walkable() {
+                    tile = if current.is_walkable() { current } else { TileType::Floor };
+                }
+                self.set(x, y, tile);
+            }
+        }
+
+        // Every walkable tile counts here, so kept stairs walled in by the new layout get reconnected too.
+        // Both ends of each tunnel lie in the rect, so the L between them does as well
+        let walkable: HashSet<(u32, u32)> = self.iter_tiles()
+            .filter(|&(_, _, tile)| tile.is_walkable())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let mut groups = self.find_disconnected_groups(&walkable);
+        if let Some(largest_group_idx) = groups.iter().enumerate().max_by_key(|(_, group)| group.len()).map(|(idx, _)| idx) {
+            let mut connected = groups.swap_remove(largest_group_idx);
+            for group in groups {
+                let (start, end) = self.find_closest_points(&rect_anchors(&group, clip), &rect_anchors(&connected, clip));
+                let carved = self.tunnel_between(start, end, CorridorStyle::LShaped, 1, ellipse_mask, rng);
+                connected.extend(group);
+                connected.extend(carved);
+            }
+        }
+        self.compute_openness();
+
+        self.diff(&baseline)
+    }
+
+    // The authoritative boundary check: in bounds and inside the mask generation carved against.
+    // Everything that asks "is this tile part of the level shape" should go through here.
+    #[inline]
+    pub fn is_within_boundary(&self, x: u32, y: u32, boundary: &EllipseMask) -> bool {
+        debug_assert_eq!(boundary.dimensions(), (self.width, self.height), "EllipseMask is out of sync with the map size");
+        x < self.width && y < self.height && boundary.is_within(x, y)
+    }
+
+    /// Walkable tiles inside the boundary with an orthogonal neighbor outside it (the map edge
+    /// included), in row-major order: candidates for edge exits or for stitching neighboring maps
+    pub fn boundary_floor_tiles(&self, boundary: &EllipseMask) -> Vec<(u32, u32)> {
+        let outside = |x: i32, y: i32| x < 0 || y < 0 || !self.is_within_boundary(x as u32, y as u32, boundary);
+        self.iter_tiles()
+            .filter(|&(x, y, tile)| tile.is_walkable() && self.is_within_boundary(x, y, boundary))
+            .filter(|&(x, y, _)| {
+                let (x, y) = (x as i32, y as i32);
+                outside(x + 1, y) || outside(x - 1, y) || outside(x, y + 1) || outside(x, y - 1)
+            })
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    // Generate from `seed`, retrying degenerate or invalid layouts. Attempt n uses seed + n so a
+    // level always rebuilds the same way; after MAX_GENERATION_ATTEMPTS the attempt with the least
+    // severe defects is kept. Returns how many attempts were used
+    pub fn generate_level_validated(&mut self, biome: BiomeType, level: u32, max_depth: u32, seed: u64, stair_mode: StairMode,
+                                    ellipse_mask: &EllipseMask) -> u32 {
+        let attempts = self.generate_validated_with(level, seed, ellipse_mask, |map, rng| {
+            map.generate_layout(biome, level, max_depth, stair_mode, rng, ellipse_mask);
+        });
+        // A freshly built level replaces the old map rather than editing it
+        self.edits.clear();
+        attempts
+    }
+
+    // The retry loop behind generate_level_validated, with the layout step supplied by the caller
+    fn generate_validated_with(&mut self, level: u32, seed: u64, ellipse_mask: &EllipseMask,
+                               mut generate: impl FnMut(&mut GameMap, &mut GlobalRng)) -> u32 {
+        // Worst defect first, then how many, so one unreachable stair loses to a few stray floor tiles
+        let badness = |defects: &[MapDefect]| (defects.iter().map(MapDefect::severity).max().unwrap_or(0), defects.len());
+        let mut best: Option<(GameMap, Vec<MapDefect>)> = None;
+
+        for attempt in 0..MAX_GENERATION_ATTEMPTS {
+            let mut candidate = GameMap::new(self.width, self.height);
+            // Spread the retry seeds out so seed N's retries don't replay seed N + 1's first attempt
+            let attempt_seed = seed ^ (attempt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            generate(&mut candidate, &mut GlobalRng::with_seed(attempt_seed));
+
+            match candidate.validate(ellipse_mask) {
+                Ok(()) => {
+                    *self = candidate;
+                    return attempt + 1;
+                }
+                Err(defects) => {
+                    println!("Level {} attempt {} rejected with {} defect(s)", level, attempt + 1, defects.len());
+                    #[cfg(debug_assertions)]
+                    println!("  {:?}", defects);
+                    if best.as_ref().is_none_or(|(_, best_defects)| badness(&defects) < badness(best_defects)) {
+                        best = Some((candidate, defects));
+                    }
+                }
+            }
+        }
+
+        if let Some((map, defects)) = best {
+            println!("Level {}: no valid layout in {} attempts, keeping the best with {} defect(s)",
+                level, MAX_GENERATION_ATTEMPTS, defects.len());
+            *self = map;
+        }
+        MAX_GENERATION_ATTEMPTS
+    }
+
+    // Terrain, stairs, items and traps, without validation
+    fn generate_layout(&mut self, biome: BiomeType, level: u32, max_depth: u32, stair_mode: StairMode, rng: &mut impl Rng, ellipse_mask: &EllipseMask) {
+        self.generate_with_biome(biome, level, rng, ellipse_mask);
+        self.compute_openness();
+        self.place_stairs(level, max_depth, stair_mode, rng);
+        self.place_items(rng);
+        self.place_traps(rng);
+        // Nothing to drop into below the bottom level
+        if level < max_depth && rng.random_bool(biome.get_config().trapdoor_chance) {
+            self.place_trapdoor(rng);
+        }
+    }
+
     // New modular generation method
     pub fn generate_with_biome(&mut self, biome: BiomeType, level: u32, rng: &mut impl Rng, ellipse_mask: &EllipseMask) {
         let params = MapGenParams::for_biome(biome, level);
@@ -57,37 +615,182 @@ impl GameMap {
         self.tiles = generator.generate(self.width, self.height, &params, rng);
 
         // Ensure connectivity for all generation types
-        self.ensure_connectivity(ellipse_mask);
+        self.ensure_connectivity(&params, ellipse_mask, rng);
+
+        // Smoothing runs before stairs are placed and can pinch off narrow passages, so reconnect after
+        if let Some(smoothing) = params.smoothing {
+            self.smooth(smoothing.iterations, smoothing.birth, smoothing.survive, ellipse_mask);
+            self.ensure_connectivity(&params, ellipse_mask, rng);
+        }
+
+        if params.water_bodies > 0 {
+            self.place_water_bodies(&params, rng);
+        }
     }
 
-    fn ensure_connectivity(&mut self, ellipse_mask: &EllipseMask) {
-        let carved_positions = self.get_floor_positions_set();
-        self.connect_disconnected_areas(&carved_positions, ellipse_mask);
+    // Flood a few open floor pockets with contiguous water, never splitting the walkable area
+    fn place_water_bodies(&mut self, params: &MapGenParams, rng: &mut dyn RngCore) {
+        for _ in 0..params.water_bodies {
+            // Seed each pool in an open pocket so it reads as a chamber pool rather than a blocked corridor
+            let pockets: Vec<(u32, u32)> = self.get_floor_positions()
+                .into_iter()
+                .filter(|&(x, y)| self.is_open_pocket(x, y))
+                .collect();
+            if pockets.is_empty() {
+                return;
+            }
 
-        // Final cleanup: ensure all tiles outside the ellipse are walls
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if !ellipse_mask.is_within(x, y) {
-                    self.set(x, y, TileType::Wall);
+            let seed = pockets[rng.random_range(0..pockets.len())];
+            let target_size = rng.random_range(6..=18);
+            let mut body_size = 0;
+            let mut frontier = vec![seed];
+
+            // Grow outward from accepted tiles only, so the pool stays contiguous
+            while body_size < target_size && !frontier.is_empty() {
+                let (x, y) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+                if self.get(x, y) != TileType::Floor {
+                    continue;
+                }
+
+                if !self.is_removable_walkable(x, y) {
+                    // This tile may be the only route between two areas - keep it dry
+                    continue;
+                }
+                self.set(x, y, TileType::Water);
+                body_size += 1;
+
+                for &(dx, dy) in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    if nx >= 0 && ny >= 0 && nx < self.width as i32 && ny < self.height as i32
+                        && self.get(nx as u32, ny as u32) == TileType::Floor {
+                        frontier.push((nx as u32, ny as u32));
+                    }
                 }
             }
         }
     }
 
-    fn get_floor_positions_set(&self) -> HashSet<(u32, u32)> {
-        let mut positions = HashSet::new();
+    // Whether the walkable tile at (x, y) can be blocked without cutting the map: its walkable
+    // cardinal neighbors must all join up through one unbroken run of walkable tiles in the
+    // 8-tile ring around it, so any route through it has a detour. A local cut-vertex test,
+    // constant time and conservative (it may refuse a tile whose detour runs further out)
+    fn is_removable_walkable(&self, x: u32, y: u32) -> bool {
+        // Clockwise from north; even entries are the cardinal neighbors
+        const RING: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+        let walkable = RING.map(|(dx, dy)| {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            nx >= 0 && ny >= 0 && nx < self.width as i32 && ny < self.height as i32
+                && self.get(nx as u32, ny as u32).is_walkable()
+        });
+
+        // Count the runs of walkable ring tiles that touch the tile through a cardinal neighbor
+        let mut runs_with_cardinal = 0;
+        for start in 0..RING.len() {
+            if !walkable[start] || walkable[(start + RING.len() - 1) % RING.len()] {
+                continue; // Not the first tile of a run
+            }
+            let run_has_cardinal = (0..RING.len())
+                .map(|offset| (start + offset) % RING.len())
+                .take_while(|&i| walkable[i])
+                .any(|i| i % 2 == 0);
+            if run_has_cardinal {
+                runs_with_cardinal += 1;
+            }
+        }
+        // A fully walkable ring has no run start and is trivially one run
+        runs_with_cardinal <= 1
+    }
+
+    // A floor tile whose 8 neighbors are all floor
+    fn is_open_pocket(&self, x: u32, y: u32) -> bool {
+        if x == 0 || y == 0 || x >= self.width.saturating_sub(1) || y >= self.height.saturating_sub(1) {
+            return false;
+        }
+        (-1..=1).all(|dy: i32| (-1..=1).all(|dx: i32| {
+            self.get((x as i32 + dx) as u32, (y as i32 + dy) as u32) == TileType::Floor
+        }))
+    }
+
+    /// 8-neighbor cellular automata pass over floor and wall tiles. A floor tile with at least
+    /// `birth` wall neighbors fills in, a wall with fewer than `survive` opens up. Water and
+    /// stairs are left alone, everything outside the ellipse stays wall, and iteration stops
+    /// early once the layout is stable.
+    pub fn smooth(&mut self, iterations: u32, birth: u8, survive: u8, ellipse_mask: &EllipseMask) {
+        for _ in 0..iterations {
+            let mut next = self.tiles.clone();
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let idx = self.idx(x, y);
+                    if !self.is_within_boundary(x, y, ellipse_mask) {
+                        next[idx] = TileType::Wall;
+                        continue;
+                    }
+
+                    let walls = self.wall_neighbors(x, y);
+                    next[idx] = match self.tiles[idx] {
+                        TileType::Floor if walls >= birth => TileType::Wall,
+                        TileType::Wall if walls < survive => TileType::Floor,
+                        other => other,
+                    };
+                }
+            }
+
+            if next == self.tiles {
+                break;
+            }
+            // Through `set`, so smoothing a live map reports its edits like any other tool
+            for (idx, tile) in next.into_iter().enumerate() {
+                self.set(idx as u32 % self.width, idx as u32 / self.width, tile);
+            }
+        }
+    }
+
+    // Off-map neighbors count as wall so the map edge behaves like solid rock
+    fn wall_neighbors(&self, x: u32, y: u32) -> u8 {
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32
+                    || self.get(nx as u32, ny as u32) == TileType::Wall
+                {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn ensure_connectivity(&mut self, params: &MapGenParams, ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) {
+        // Clip to the ellipse first so the connectivity pass sees the final floor layout
         for y in 0..self.height {
             for x in 0..self.width {
-                if self.get(x, y) == TileType::Floor {
-                    positions.insert((x, y));
+                if !self.is_within_boundary(x, y, ellipse_mask) {
+                    self.set(x, y, TileType::Wall);
                 }
             }
         }
-        positions
+
+        let carved_positions = self.get_floor_positions_set();
+        self.connect_disconnected_areas(&carved_positions, params, ellipse_mask, rng);
+    }
+
+    fn get_floor_positions_set(&self) -> HashSet<(u32, u32)> {
+        self.iter_floor().collect()
     }
     
-    fn connect_disconnected_areas(&mut self, carved_positions: &HashSet<(u32, u32)>, ellipse_mask: &EllipseMask) {
+    fn connect_disconnected_areas(&mut self, carved_positions: &HashSet<(u32, u32)>, params: &MapGenParams,
+                                  ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) {
         let groups = self.find_disconnected_groups(carved_positions);
+        if let ConnectivityStrategy::MergeNearest { max_wall } = params.connectivity {
+            self.merge_nearest_groups(groups, max_wall, params, ellipse_mask, rng);
+            return;
+        }
         
         // Connect all groups to the largest one
         if groups.len() > 1 {
@@ -103,17 +806,49 @@ impl GameMap {
                 if i != largest_group_idx {
                     // Find closest points between groups
                     let (start, end) = self.find_closest_points(group, largest_group);
-                    self.carve_tunnel(start, end, ellipse_mask);
+                    self.carve_tunnel(start, end, params, ellipse_mask, rng);
                 }
             }
         }
     }
     
+    // Grow outward from the largest group, each time joining whichever group is closest to
+    // everything connected so far. Thin walls are knocked through with a 1-wide opening
+    fn merge_nearest_groups(&mut self, mut groups: Vec<Vec<(u32, u32)>>, max_wall: u32, params: &MapGenParams,
+                            ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) {
+        let Some(largest_group_idx) = groups.iter().enumerate().max_by_key(|(_, group)| group.len()).map(|(idx, _)| idx) else {
+            return;
+        };
+        let mut connected = groups.swap_remove(largest_group_idx);
+
+        while !groups.is_empty() {
+            let (nearest_idx, (start, end)) = groups.iter()
+                .map(|group| self.find_closest_points(group, &connected))
+                .enumerate()
+                .min_by_key(|(_, (a, b))| a.0.abs_diff(b.0) + a.1.abs_diff(b.1))
+                .unwrap();
+
+            // A 4-connected breach between the two closest floor tiles clears one wall tile per step but the last
+            let wall_thickness = (start.0.abs_diff(end.0) + start.1.abs_diff(end.1)).saturating_sub(1);
+            let carved = if wall_thickness <= max_wall {
+                self.tunnel_between(start, end, CorridorStyle::Diagonal, 1, ellipse_mask, rng)
+            } else {
+                self.carve_tunnel(start, end, params, ellipse_mask, rng)
+            };
+
+            connected.extend(groups.swap_remove(nearest_idx));
+            connected.extend(carved);
+        }
+    }
+
     fn find_disconnected_groups(&self, carved_positions: &HashSet<(u32, u32)>) -> Vec<Vec<(u32, u32)>> {
         let mut visited = HashSet::new();
         let mut groups = Vec::new();
-        
-        for &pos in carved_positions {
+
+        // Row-major start order, so a seed rebuilds the same groups (and tunnels) on every run
+        let mut starts: Vec<(u32, u32)> = carved_positions.iter().copied().collect();
+        starts.sort_unstable_by_key(|&(x, y)| (y, x));
+        for pos in starts {
             if !visited.contains(&pos) {
                 let mut group = Vec::new();
                 let mut stack = vec![pos];
@@ -168,101 +903,373 @@ impl GameMap {
         closest_pair
     }
     
-    fn carve_tunnel(&mut self, start: (u32, u32), end: (u32, u32), ellipse_mask: &EllipseMask) {
-        let mut x = start.0 as i32;
-        let mut y = start.1 as i32;
-        let target_x = end.0 as i32;
-        let target_y = end.1 as i32;
-
-        // Simple L-shaped tunnel
-        while x != target_x {
-            if x < target_x {
-                x += 1;
-            } else {
-                x -= 1;
-            }
-            if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
-                // Only carve if within ellipse boundary
-                if ellipse_mask.is_within(x as u32, y as u32) {
-                    self.set(x as u32, y as u32, TileType::Floor);
-                }
-            }
-        }
+    fn carve_tunnel(&mut self, start: (u32, u32), end: (u32, u32), params: &MapGenParams,
+                    ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) -> Vec<(u32, u32)> {
+        self.tunnel_between(start, end, params.corridor_style, params.corridor_width, ellipse_mask, rng)
+    }
 
-        while y != target_y {
-            if y < target_y {
-                y += 1;
-            } else {
-                y -= 1;
-            }
-            if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
-                // Only carve if within ellipse boundary
-                if ellipse_mask.is_within(x as u32, y as u32) {
-                    self.set(x as u32, y as u32, TileType::Floor);
+    /// Carve a `width`-wide floor corridor from `a` to `b` in the given style, clipped to the
+    /// boundary. Returns the positions carved, in path order and without repeats.
+    pub fn tunnel_between(&mut self, a: (u32, u32), b: (u32, u32), style: CorridorStyle, width: u32,
+                          ellipse_mask: &EllipseMask, rng: &mut dyn RngCore) -> Vec<(u32, u32)> {
+        // An L-shaped tunnel turns at (b.x, a.y); bend the other way if that corner is outside the boundary
+        let (from, to) = if style == CorridorStyle::LShaped && !self.is_within_boundary(b.0, a.1, ellipse_mask) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        let mut carved = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let path = std::iter::once((from.0 as i32, from.1 as i32)).chain(corridor_path(from, to, style, rng));
+        for (path_x, path_y) in path {
+            for (dx, dy) in brush_offsets(width) {
+                let x = path_x + dx;
+                let y = path_y + dy;
+                // Only carve within the ellipse boundary (also rejects out-of-bounds)
+                if x >= 0 && y >= 0 && self.is_within_boundary(x as u32, y as u32, ellipse_mask) {
+                    // Ground that's already walkable (stairs, items, traps) is passed through, not paved over
+                    if !self.get(x as u32, y as u32).is_walkable() {
+                        self.set(x as u32, y as u32, TileType::Floor);
+                    }
+                    if seen.insert((x as u32, y as u32)) {
+                        carved.push((x as u32, y as u32));
+                    }
                 }
             }
         }
+        carved
     }
     
-    pub fn place_stairs(&mut self, level: u32, rng: &mut impl Rng) {
-        let floor_positions: Vec<(u32, u32)> = self.get_floor_positions();
+    pub fn place_stairs(&mut self, level: u32, max_depth: u32, stair_mode: StairMode, rng: &mut impl Rng) {
+        let floor_positions: Vec<(u32, u32)> = self.open_floor_positions();
 
         if floor_positions.is_empty() {
             return;
         }
 
-        // Place stairs up (except on level 0)
-        if level > 0 {
+        // Place stairs up (except on level 0, or when the run only goes down)
+        if level > 0 && stair_mode.allows_ascent() {
             let pos_idx = rng.random_range(0..floor_positions.len());
             let (x, y) = floor_positions[pos_idx];
             self.set(x, y, TileType::StairUp);
             self.stair_up_pos = Some((x, y));
         }
 
-        // Place stairs down (except on level 50)
-        if level < 50 {
-            let mut attempts = 0;
-            loop {
-                let pos_idx = rng.random_range(0..floor_positions.len());
-                let (x, y) = floor_positions[pos_idx];
+        // Place stairs down, or the altar on the bottom level (max_depth), far enough from the up
+        // stairs that validate doesn't reject the level for it
+        let up = self.stair_up_pos;
+        let distance_from_up = |(x, y): (u32, u32)| up.map_or(u32::MAX, |(ux, uy)| x.abs_diff(ux) + y.abs_diff(uy));
+        let mut attempts = 0;
+        let (x, y) = loop {
+            let pos_idx = rng.random_range(0..floor_positions.len());
+            let pos = floor_positions[pos_idx];
+            if distance_from_up(pos) >= MIN_STAIR_DISTANCE {
+                break pos;
+            }
+
+            attempts += 1;
+            if attempts > 100 {
+                // Fallback: the open floor farthest from the up stairs
+                break floor_positions.iter().copied().max_by_key(|&pos| distance_from_up(pos)).unwrap_or(pos);
+            }
+        };
+
+        if level < max_depth {
+            self.set(x, y, TileType::StairDown);
+            self.stair_down_pos = Some((x, y));
+        } else {
+            self.set(x, y, TileType::Altar);
+            self.altar_pos = Some((x, y));
+        }
+    }
+
+    // Scatter a few pickups on plain floor; runs after stairs so items never cover them
+    pub fn place_items(&mut self, rng: &mut impl Rng) {
+        let mut floor_positions = self.open_floor_positions();
+        let item_count = rng.random_range(3..=5).min(floor_positions.len());
+
+        for _ in 0..item_count {
+            let (x, y) = floor_positions.swap_remove(rng.random_range(0..floor_positions.len()));
+            let item = match rng.random_range(0..10) {
+                0..=3 => ItemKind::Gold,
+                4..=6 => ItemKind::Potion,
+                _ => ItemKind::Scroll,
+            };
+            self.set(x, y, TileType::Item(item));
+        }
+    }
+
+    // Hide a few traps in corridors, where they're hard to walk around
+    pub fn place_traps(&mut self, rng: &mut impl Rng) {
+        let mut corridors: Vec<(u32, u32)> = self.iter_floor().filter(|&(x, y)| self.is_corridor(x, y)).collect();
+        let trap_count = rng.random_range(2..=4).min(corridors.len());
+
+        for _ in 0..trap_count {
+            let (x, y) = corridors.swap_remove(rng.random_range(0..corridors.len()));
+            self.set(x, y, TileType::TrapHidden);
+        }
+    }
+
+    // One trapdoor on open floor, kept away from the stairs so arriving never drops you straight through
+    pub fn place_trapdoor(&mut self, rng: &mut impl Rng) {
+        let stairs = [self.stair_up_pos, self.stair_down_pos, self.altar_pos];
+        let candidates: Vec<(u32, u32)> = self.open_floor_positions().into_iter()
+            .filter(|&(x, y)| stairs.iter().flatten().all(|&(sx, sy)| x.abs_diff(sx) + y.abs_diff(sy) > MIN_STAIR_DISTANCE))
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let (x, y) = candidates[rng.random_range(0..candidates.len())];
+        self.set(x, y, TileType::Trapdoor);
+    }
+
+    // A walkable tile with walls on both sides and open ground ahead and behind, along either axis
+    pub fn is_corridor(&self, x: u32, y: u32) -> bool {
+        if x == 0 || y == 0 || x + 1 >= self.width || y + 1 >= self.height || !self.get(x, y).is_walkable() {
+            return false;
+        }
+        let open = |x, y| self.get(x, y).is_walkable();
+        let (north, south, east, west) = (open(x, y + 1), open(x, y - 1), open(x + 1, y), open(x - 1, y));
+        (north && south && !east && !west) || (east && west && !north && !south)
+    }
+
+    /// Positions of every tile of the given type, in row-major order
+    pub fn find_all(&self, kind: TileType) -> Vec<(u32, u32)> {
+        self.iter_tiles().filter(|&(_, _, tile)| tile == kind).map(|(x, y, _)| (x, y)).collect()
+    }
+
+    fn get_floor_positions(&self) -> Vec<(u32, u32)> {
+        self.find_all(TileType::Floor)
+    }
+
+    // Floor in open chambers, or all floor when the level is too cramped to have enough of it
+    fn open_floor_positions(&self) -> Vec<(u32, u32)> {
+        let open: Vec<(u32, u32)> = self.iter_floor().filter(|&(x, y)| self.openness(x, y) >= OPEN_CHAMBER_OPENNESS).collect();
+        if open.len() < 20 {
+            return self.get_floor_positions();
+        }
+        open
+    }
+
+    /// Steps (8-connected) from (x, y) to the nearest wall: 0 on walls, 1 beside one, higher in
+    /// the middle of open chambers. Off-map counts as wall. 0 before compute_openness has run
+    pub fn openness(&self, x: u32, y: u32) -> u32 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        self.openness.get(self.idx(x, y)).copied().unwrap_or(0)
+    }
+
+    /// Refresh the openness field with a multi-source BFS out from every wall.
+    /// Run after the walls change; other tile edits leave it valid
+    pub fn compute_openness(&mut self) {
+        let mut distance = vec![u32::MAX; self.tiles.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for (x, y, tile) in self.iter_tiles() {
+            let on_edge = x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height;
+            let d = if tile == TileType::Wall { 0 } else if on_edge { 1 } else { continue };
+            distance[self.idx(x, y)] = d;
+            queue.push_back((x, y));
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            let next = distance[self.idx(x, y)] + 1;
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32 {
+                    continue;
+                }
+                let n_idx = self.idx(nx as u32, ny as u32);
+                if distance[n_idx] > next {
+                    distance[n_idx] = next;
+                    queue.push_back((nx as u32, ny as u32));
+                }
+            }
+        }
+
+        distance.iter_mut().filter(|d| **d == u32::MAX).for_each(|d| *d = 0); // Only on a map with no tiles to seed from
+        self.openness = distance;
+    }
+    
+    /// Recorded (up, down) stair positions, as placed by `place_stairs`
+    pub fn stairs(&self) -> (Option<(u32, u32)>, Option<(u32, u32)>) {
+        (self.stair_up_pos, self.stair_down_pos)
+    }
+
+    /// A StairMismatch for each recorded stair (or the altar) whose tile was overwritten after placement
+    pub fn stair_mismatches(&self) -> Vec<MapDefect> {
+        let (up, down) = self.stairs();
+        [(up, TileType::StairUp), (down, TileType::StairDown), (self.altar_pos, TileType::Altar)].into_iter()
+            .filter_map(|(pos, expected)| {
+                let (x, y) = pos?;
+                let found = self.get(x, y);
+                (found != expected).then_some(MapDefect::StairMismatch { pos: (x, y), expected, found })
+            })
+            .collect()
+    }
+
+    /// Check the invariants generation should uphold, returning every violation found
+    pub fn validate(&self, ellipse_mask: &EllipseMask) -> Result<(), Vec<MapDefect>> {
+        let mut defects = Vec::new();
+
+        // Stairs must sit on their own tiles
+        defects.extend(self.stair_mismatches());
+
+        // Nothing walkable outside the boundary
+        for (x, y, tile) in self.iter_tiles() {
+            if tile.is_walkable() && !self.is_within_boundary(x, y, ellipse_mask) {
+                defects.push(MapDefect::WalkableOutsideBoundary { pos: (x, y) });
+            }
+        }
+
+        // Exactly one walkable region, and the stairs share it
+        match self.connected_component_count() {
+            0 => defects.push(MapDefect::NoWalkableTiles),
+            1 => {}
+            components => defects.push(MapDefect::Disconnected { components }),
+        }
+
+        let regions = self.walkable_regions();
+        // On the bottom level the altar stands in for the down stair
+        if let (Some(up), Some(down)) = (self.stair_up_pos, self.stair_down_pos.or(self.altar_pos)) {
+            let same_region = regions.iter().any(|region| region.contains(&up) && region.contains(&down));
+            if !same_region {
+                defects.push(MapDefect::StairsUnreachable { up, down });
+            }
+            let distance = up.0.abs_diff(down.0) + up.1.abs_diff(down.1);
+            if distance < MIN_STAIR_DISTANCE {
+                defects.push(MapDefect::StairsTooClose { up, down, distance });
+            }
+        }
+
+        // Degenerate layouts: a sliver of floor isn't worth playing
+        let walkable: usize = regions.iter().map(|region| region.len()).sum();
+        if walkable > 0 && walkable < MIN_WALKABLE_TILES {
+            defects.push(MapDefect::TooFewWalkable { count: walkable, min: MIN_WALKABLE_TILES });
+        }
+
+        if defects.is_empty() { Ok(()) } else { Err(defects) }
+    }
+
+    /// Number of separate 4-connected groups of walkable tiles. A generated level should always
+    /// have exactly one; zero means there's nothing to stand on.
+    pub fn connected_component_count(&self) -> usize {
+        self.walkable_regions().len()
+    }
+
+    /// Id of the walkable region containing (x, y): the row-major index of the region's first tile.
+    /// Only that one region is flooded, so it's cheap enough to call every frame
+    pub fn region_id(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.width || y >= self.height || !self.get(x, y).is_walkable() {
+            return None;
+        }
+
+        let mut visited = vec![false; self.tiles.len()];
+        let mut stack = vec![(x, y)];
+        visited[self.idx(x, y)] = true;
+        let mut first = self.idx(x, y);
+        while let Some((cx, cy)) = stack.pop() {
+            first = first.min(self.idx(cx, cy));
+            for (nx, ny) in [(cx.wrapping_sub(1), cy), (cx + 1, cy), (cx, cy.wrapping_sub(1)), (cx, cy + 1)] {
+                if nx < self.width && ny < self.height && !visited[self.idx(nx, ny)] && self.get(nx, ny).is_walkable() {
+                    visited[self.idx(nx, ny)] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        Some(first)
+    }
+
+    /// Up to `count` plain floor tiles for placing encounters, taken round-robin across walkable
+    /// regions so every region gets a share. Each point is at least `min_distance` (Manhattan) from
+    /// `from` (usually the player's spawn) and from every other point; stairs, items and the altar
+    /// are never chosen. Fewer points come back when the map can't fit them all.
+    pub fn spawn_points(&self, count: usize, min_distance: u32, from: (u32, u32), rng: &mut impl Rng) -> Vec<(u32, u32)> {
+        let distance = |a: (u32, u32), b: (u32, u32)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
 
-                // Make sure stairs aren't too close to each other
-                if self.stair_up_pos.map_or(true, |(ux, uy)| {
-                    ((x as i32 - ux as i32).abs() + (y as i32 - uy as i32).abs()) > 5
-                }) {
-                    self.set(x, y, TileType::StairDown);
-                    self.stair_down_pos = Some((x, y));
-                    break;
-                }
+        let mut candidates: Vec<Vec<(u32, u32)>> = self.walkable_regions().into_iter()
+            .map(|region| region.into_iter()
+                .filter(|&(x, y)| self.get(x, y) == TileType::Floor && distance((x, y), from) >= min_distance)
+                .collect::<Vec<_>>())
+            .filter(|region| !region.is_empty())
+            .collect();
+        // Largest regions first so a small leftover pocket doesn't take the first pick; the sort
+        // also keeps results independent of HashSet iteration order
+        for region in candidates.iter_mut() {
+            region.sort_unstable();
+        }
+        candidates.sort_by_key(|region| std::cmp::Reverse(region.len()));
 
-                attempts += 1;
-                if attempts > 100 {
-                    // Fallback: place anywhere
-                    self.set(x, y, TileType::StairDown);
-                    self.stair_down_pos = Some((x, y));
+        let mut points: Vec<(u32, u32)> = Vec::with_capacity(count);
+        while points.len() < count && candidates.iter().any(|region| !region.is_empty()) {
+            for region in candidates.iter_mut() {
+                if points.len() >= count {
                     break;
                 }
+                // Draw at random until one clears every existing point (or the region runs dry)
+                while !region.is_empty() {
+                    let pos = region.swap_remove(rng.random_range(0..region.len()));
+                    if points.iter().all(|&other| distance(pos, other) >= min_distance) {
+                        points.push(pos);
+                        break;
+                    }
+                }
             }
         }
+        points
     }
 
-    fn get_floor_positions(&self) -> Vec<(u32, u32)> {
-        let mut positions = Vec::new();
+    // Group walkable tiles into 4-connected regions
+    fn walkable_regions(&self) -> Vec<HashSet<(u32, u32)>> {
+        let mut visited = vec![false; self.tiles.len()];
+        let mut regions = Vec::new();
+
         for y in 0..self.height {
             for x in 0..self.width {
-                if self.get(x, y) == TileType::Floor {
-                    positions.push((x, y));
+                if visited[self.idx(x, y)] || !self.get(x, y).is_walkable() {
+                    continue;
+                }
+
+                let mut region = HashSet::new();
+                let mut stack = vec![(x, y)];
+                visited[self.idx(x, y)] = true;
+
+                while let Some((cx, cy)) = stack.pop() {
+                    region.insert((cx, cy));
+
+                    for &(dx, dy) in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                        let nx = cx as i32 + dx;
+                        let ny = cy as i32 + dy;
+                        if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32 {
+                            continue;
+                        }
+                        let (nx, ny) = (nx as u32, ny as u32);
+                        let n_idx = self.idx(nx, ny);
+                        if !visited[n_idx] && self.get(nx, ny).is_walkable() {
+                            visited[n_idx] = true;
+                            stack.push((nx, ny));
+                        }
+                    }
                 }
+
+                regions.push(region);
             }
         }
-        positions
+
+        regions
     }
-    
+
     pub fn from_saved_data(saved: &SavedMapData) -> Self {
         let mut map = GameMap::new(saved.width, saved.height);
-        map.tiles = saved.tiles.clone();
+        map.tiles = saved.tiles.decode();
+        debug_assert_eq!(map.tiles.len(), (saved.width * saved.height) as usize, "saved tile runs don't cover the map");
         map.stair_up_pos = saved.stair_up_pos;
         map.stair_down_pos = saved.stair_down_pos;
+        map.altar_pos = saved.altar_pos;
+        debug_assert!(map.stair_mismatches().is_empty(), "saved level's stair positions don't match its tiles: {:?}", map.stair_mismatches());
+        map.compute_openness();
         map
     }
 
@@ -270,9 +1277,10 @@ impl GameMap {
         SavedMapData {
             width: self.width,
             height: self.height,
-            tiles: self.tiles.clone(),
+            tiles: RleTiles::encode(&self.tiles),
             stair_up_pos: self.stair_up_pos,
             stair_down_pos: self.stair_down_pos,
+            altar_pos: self.altar_pos,
             biome,
             tile_visibility,
         }
@@ -285,70 +1293,94 @@ impl GameMap {
         }
         self.get(x, y - 1) == TileType::Wall
     }
+
+    // 4-bit mask of cardinal wall neighbors (N=1, E=2, S=4, W=8); off-map counts as wall
+    pub fn wall_mask(&self, x: u32, y: u32) -> u8 {
+        let is_wall = |nx: i32, ny: i32| {
+            nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32
+                || self.get(nx as u32, ny as u32) == TileType::Wall
+        };
+        let (x, y) = (x as i32, y as i32);
+
+        let mut mask = 0;
+        if is_wall(x, y + 1) { mask |= 1; }
+        if is_wall(x + 1, y) { mask |= 2; }
+        if is_wall(x, y - 1) { mask |= 4; }
+        if is_wall(x - 1, y) { mask |= 8; }
+        mask
+    }
+
+    // A wall with at least one non-wall tile among its 8 neighbors, i.e. one the player could ever see
+    pub fn is_exposed_wall(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.get(x, y) == TileType::Wall && self.wall_neighbors(x, y) < 8
+    }
+
+    // Tiles that can end up explored: everything but rock buried inside other walls
+    pub fn viewable_tile_count(&self) -> usize {
+        self.iter_tiles().filter(|&(x, y, tile)| tile != TileType::Wall || self.is_exposed_wall(x, y)).count()
+    }
 }
 
-// Helper function to get the correct tile texture index based on tile type and context
-pub fn get_tile_texture_index(tile_type: TileType, map: &GameMap, x: u32, y: u32, sprite_db: &SpriteDatabase, rng: &mut impl Rng) -> u32 {
-    match tile_type {
-        TileType::Floor => {
-            // Get a random floor sprite from the floors category
-            sprite_db.get_random_sprite_from_category("floors", rng)
-                .unwrap_or(sprite_position_to_index(1, 6)) // fallback to floor_stone1
-        },
-        TileType::Wall => {
-            if map.has_wall_below(x, y) {
-                // Use wall_top sprites
-                sprite_db.get_random_sprite_from_category("wall_top", rng)
-                    .unwrap_or(sprite_position_to_index(0, 0)) // fallback to dirt_wall_top
-            } else {
-                // Use wall_side sprites  
-                sprite_db.get_random_sprite_from_category("wall_side", rng)
-                    .unwrap_or(sprite_position_to_index(1, 0)) // fallback to dirt_wall_side
-            }
-        },
-        TileType::StairUp => sprite_position_to_index(8, 16), // stair_up at 8,16
-        TileType::StairDown => sprite_position_to_index(7, 16), // stair_down at 7,16
-        _ => sprite_position_to_index(0, 6), // Default to blank_floor_dark_grey
+// The tiles of `group` inside `rect`, or if it has none there, the point on the rect edge closest to it
+fn rect_anchors(group: &[(u32, u32)], rect: URect) -> Vec<(u32, u32)> {
+    let clamp = |(x, y): (u32, u32)| (x.clamp(rect.min.x, rect.max.x - 1), y.clamp(rect.min.y, rect.max.y - 1));
+    let inside: Vec<(u32, u32)> = group.iter().copied().filter(|&pos| clamp(pos) == pos).collect();
+    if !inside.is_empty() {
+        return inside;
     }
+    group.iter()
+        .min_by_key(|&&(x, y)| {
+            let edge = clamp((x, y));
+            x.abs_diff(edge.0) + y.abs_diff(edge.1)
+        })
+        .map(|&pos| clamp(pos))
+        .into_iter()
+        .collect()
+}
+
+// Texture index for a tile: the biome's sprite choice turned into an index on the loaded sheet
+pub fn get_tile_texture_index(biome_config: &BiomeConfig, sprite_db: &SpriteDatabase, tile_type: TileType, map: &GameMap, x: u32, y: u32, rng: &mut impl Rng) -> u32 {
+    let (sprite_x, sprite_y) = select_biome_asset(biome_config, sprite_db, tile_type, map, x, y, rng);
+    sprite_db.position_to_index(sprite_x, sprite_y)
 }
 
-// Add biome asset selection function with context-aware wall selection
-pub fn select_biome_asset(biome_config: &BiomeConfig, tile_type: TileType, map: &GameMap, x: u32, y: u32, rng: &mut impl Rng) -> (u32, u32) {
+// Add biome asset selection function with context-aware wall selection. A biome's sprites come from
+// its "<biome>/<role>" category in the sprite database when the loaded sheet has one, else from the
+// BiomeConfig; a biome with neither uses the sheet's generic categories
+pub fn select_biome_asset(biome_config: &BiomeConfig, sprite_db: &SpriteDatabase, tile_type: TileType, map: &GameMap, x: u32, y: u32, rng: &mut impl Rng) -> (u32, u32) {
     match tile_type {
         TileType::Floor => {
-            let assets = &biome_config.allowed_floor_assets;
+            let assets = sprite_db.biome_category(biome_config.name, "floors").unwrap_or(&biome_config.allowed_floor_assets);
             if assets.is_empty() {
-                return (1, 6); // fallback to floor_stone1
+                return sprite_db.get_random_sprite_from_category("floors", rng).unwrap_or((1, 6)); // fallback to floor_stone1
             }
             
-            // Special weighted selection for Cinder Gaol to prefer dark_brown_bg and red floors
-            if biome_config.name == "Cinder Gaol" {
-                let rand_val = rng.random::<f32>();
-                if rand_val < 0.35 && assets.contains(&(0, 15)) {
-                    return (0, 15); // 35% chance for dark_brown_bg (most common)
-                } else if rand_val < 0.55 {
-                    // 20% chance for red floors - pick randomly from available red floors
-                    let red_floors: Vec<_> = assets.iter()
-                        .filter(|&&(_, y)| y == 11) // Red floors are on row 11
-                        .cloned()
-                        .collect();
-                    if !red_floors.is_empty() {
-                        return red_floors[rng.random_range(0..red_floors.len())];
-                    }
-                }
-                // Remaining 45% falls through to normal random selection (bones, etc.)
+            // Biome-themed details (prison floors, grass tufts) before the uniform pick
+            if let Some(sprite) = biome_config.roll_floor_decoration(assets, rng) {
+                return sprite;
             }
             
             assets[rng.random_range(0..assets.len())]
         },
         TileType::Wall => {
-            let wall_assets = &biome_config.allowed_wall_assets;
+            // Biome-specific edge/corner pieces take priority over the top/side split. The enclosed
+            // piece is only for rock with no open neighbor at all; a wall that touches floor just
+            // diagonally is still on the perimeter, so it falls through to a regular wall top
+            let mask = map.wall_mask(x, y);
+            let diagonal_only = mask == 15 && map.is_exposed_wall(x, y);
+            if let Some(&(_, sprite)) = biome_config.wall_autotile.iter().find(|(m, _)| *m == mask && !diagonal_only) {
+                return sprite;
+            }
+
+            let wall_assets = sprite_db.biome_category(biome_config.name, "walls").unwrap_or(&biome_config.allowed_wall_assets);
             if wall_assets.is_empty() {
                 // Fallback logic with proper wall type selection
                 if !map.has_wall_below(x, y) {
-                    return (1, 0); // dirt_wall_side (no wall below = exposed bottom edge)
+                    // dirt_wall_side (no wall below = exposed bottom edge)
+                    return sprite_db.get_random_sprite_from_category("wall_side", rng).unwrap_or((1, 0));
                 } else {
-                    return (0, 0); // dirt_wall_top (wall below = top surface of continuing wall)
+                    // dirt_wall_top (wall below = top surface of continuing wall)
+                    return sprite_db.get_random_sprite_from_category("wall_top", rng).unwrap_or((0, 0));
                 }
             }
             
@@ -390,36 +1422,57 @@ pub fn select_biome_asset(biome_config: &BiomeConfig, tile_type: TileType, map:
             }
         },
         TileType::Water => {
-            let assets = &biome_config.allowed_water_assets;
+            let assets = sprite_db.biome_category(biome_config.name, "water").unwrap_or(&biome_config.allowed_water_assets);
             if assets.is_empty() {
                 return (0, 12); // fallback to blank_blue_floor for water
             }
             assets[rng.random_range(0..assets.len())]
         },
-        TileType::StairUp => {
-            // Always use the specific staircase_up sprite
-            (8, 16)
+        TileType::StairUp => biome_config.stairs().up,
+        TileType::StairDown => biome_config.stairs().down,
+        TileType::Altar => {
+            // Pentagram marks the goal
+            (14, 16)
         },
-        TileType::StairDown => {
-            // Always use the specific staircase_down sprite  
-            (7, 16)
+        // Undetected traps must be indistinguishable from the floor around them
+        TileType::TrapHidden => select_biome_asset(biome_config, sprite_db, TileType::Floor, map, x, y, rng),
+        TileType::TrapRevealed => (15, 16), // spikes
+        TileType::Trapdoor => (13, 16),     // trap_door
+        TileType::Item(item) => match item {
+            ItemKind::Potion => (2, 17), // jar_closed
+            ItemKind::Scroll => (0, 17), // chest_closed
+            ItemKind::Gold => (5, 17),   // ore_sack
         },
     }
 }
 
+/// Rng for one tile's sprite variant, seeded from the level seed and the tile's position so a
+/// level draws with the same art every time its tiles are respawned
+pub fn tile_variant_rng(level_seed: u64, x: u32, y: u32) -> rand::rngs::StdRng {
+    use rand::SeedableRng;
+    let position = ((x as u64) << 32) | y as u64;
+    rand::rngs::StdRng::seed_from_u64(level_seed ^ position.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
 pub fn spawn_map(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    _sprite_db: Res<SpriteDatabase>,
+    sprite_db: Res<SpriteDatabase>,
     level_maps: Res<LevelMaps>,
+    mut level_seeds: ResMut<LevelSeeds>,
     current_level: Res<CurrentLevel>,
     mut tile_index: ResMut<TileIndex>,
     mut ellipse_mask: ResMut<EllipseMask>,
     mut rng: ResMut<GlobalRng>,
+    mut metrics: ResMut<PerfMetrics>,
+    stair_mode: Res<StairMode>,
+    dungeon: Res<DungeonConfig>,
 ) {
     // Biome-aware config
     let biome_config = current_level.biome.get_config();
 
+    // The level's stored seed drives both generation and the per-tile sprite variants
+    let seed = level_seeds.seed_for(current_level.level, rng.as_mut());
     let map = if let Some(saved_data) = level_maps.maps.get(&current_level.level) {
         // Load existing map
         GameMap::from_saved_data(saved_data)
@@ -430,9 +1483,14 @@ pub fn spawn_map(
         // Update ellipse mask for map dimensions
         ellipse_mask.resize(80, 50);
 
-        // Use biome-aware generation
-        map.generate_with_biome(current_level.biome, current_level.level, rng.as_mut(), &ellipse_mask);
-        map.place_stairs(current_level.level, rng.as_mut());
+        // Use biome-aware generation from the level's stored seed
+        println!("Generating level {} with seed {}", current_level.level, seed);
+        let started = PerfMetrics::now();
+        let attempts = map.generate_level_validated(current_level.biome, current_level.level, dungeon.max_depth, seed, *stair_mode, &ellipse_mask);
+        metrics.record(PerfStat::MapGeneration, started);
+        if attempts > 1 {
+            println!("Level {} needed {} generation attempts", current_level.level, attempts);
+        }
         map
     };
 
@@ -447,8 +1505,7 @@ pub fn spawn_map(
         for x in 0..map.width {
             let tile_type = map.get(x, y);
             // Select sprite position based on biome configuration
-            let (sprite_x, sprite_y) = select_biome_asset(&biome_config, tile_type, &map, x, y, rng.as_mut());
-            let texture_index = sprite_position_to_index(sprite_x, sprite_y);
+            let texture_index = get_tile_texture_index(biome_config, &sprite_db, tile_type, &map, x, y, &mut tile_variant_rng(seed, x, y));
 
             let tile_pos = TilePos { x, y };
             let tile_entity = commands
@@ -469,8 +1526,9 @@ pub fn spawn_map(
         }
     }
     
-    let tile_size = TilemapTileSize { x: 32.0, y: 32.0 };
-    let grid_size = tile_size.into();
+    let tile_size = TilemapTileSize { x: TILE_SIZE, y: TILE_SIZE };
+    let grid_size: TilemapGridSize = tile_size.into();
+    debug_assert_eq!(grid_size.x, TILE_SIZE, "tilemap grid must match TILE_SIZE for grid/world conversions");
     let map_type = TilemapType::default();
     
     commands.entity(tilemap_entity).insert(TilemapBundle {
@@ -486,3 +1544,835 @@ pub fn spawn_map(
     
     commands.insert_resource(map);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::find_path;
+    use crate::assets::SpriteDatabaseConfig;
+    use crate::constants::MAX_DEPTH;
+    use crate::biome::StairSprites;
+
+    // Floor enclosed by a one-tile wall ring
+    fn open_room(width: u32, height: u32) -> GameMap {
+        let mut map = GameMap::new(width, height);
+        map.fill(TileType::Floor);
+        map.border(TileType::Wall);
+        map
+    }
+
+    #[test]
+    fn fill_sets_every_tile() {
+        let mut map = GameMap::new(6, 4);
+        map.fill(TileType::Water);
+        assert!(map.iter_tiles().all(|(_, _, tile)| tile == TileType::Water));
+    }
+
+    #[test]
+    fn fill_rect_stays_inside_the_rect_and_map() {
+        let mut map = GameMap::new(6, 4);
+        map.fill_rect(URect::new(4, 2, 10, 10), TileType::Floor);
+        for (x, y, tile) in map.iter_tiles() {
+            assert_eq!(tile == TileType::Floor, x >= 4 && y >= 2, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn border_only_sets_the_perimeter() {
+        let map = open_room(7, 5);
+        for (x, y, tile) in map.iter_tiles() {
+            let edge = x == 0 || y == 0 || x == 6 || y == 4;
+            assert_eq!(tile, if edge { TileType::Wall } else { TileType::Floor }, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn boundary_floor_tiles_are_the_floor_touching_the_mask_edge() {
+        let mask = EllipseMask::new(21, 15);
+        let mut map = GameMap::new(21, 15);
+        for (x, y) in map.iter_tiles().map(|(x, y, _)| (x, y)).collect::<Vec<_>>() {
+            if mask.is_within(x, y) {
+                map.set(x, y, TileType::Floor);
+            }
+        }
+        // Floor outside the boundary never counts
+        map.set(0, 0, TileType::Floor);
+
+        let edges = map.boundary_floor_tiles(&mask);
+        let leftmost = (0..21).find(|&x| mask.is_within(x, 7)).unwrap();
+        assert!(edges.contains(&(leftmost, 7)));
+        assert!(!edges.contains(&(10, 7)), "the center is interior");
+        assert!(!edges.contains(&(0, 0)));
+        for &(x, y) in &edges {
+            let outside = |dx: i32, dy: i32| !mask.is_within((x as i32 + dx) as u32, (y as i32 + dy) as u32);
+            assert!(outside(1, 0) || outside(-1, 0) || outside(0, 1) || outside(0, -1), "({}, {}) is interior", x, y);
+        }
+
+        // Walling an edge tile drops it
+        map.set(leftmost, 7, TileType::Wall);
+        assert!(!map.boundary_floor_tiles(&mask).contains(&(leftmost, 7)));
+    }
+
+    #[test]
+    fn bfs_distance_matches_path_length_and_handles_unreachable() {
+        let mut map = open_room(12, 8);
+        map.fill_rect(URect::new(6, 1, 7, 6), TileType::Wall); // Wall with a gap at y = 6
+        let (from, to) = ((2, 2), (9, 2));
+
+        let distance = map.bfs_distance(from, to, BiomeType::Caverns);
+        assert_eq!(distance, Some(find_path(from, to, &map, BiomeType::Caverns).len() as u32));
+        assert_eq!(map.bfs_distance(from, from, BiomeType::Caverns), Some(0));
+
+        map.set(6, 6, TileType::Wall);
+        assert_eq!(map.bfs_distance(from, to, BiomeType::Caverns), None);
+        assert_eq!(map.bfs_distance((40, 2), to, BiomeType::Caverns), None);
+    }
+
+    #[test]
+    fn regenerate_region_leaves_the_outside_alone_and_stays_connected() {
+        let mask = EllipseMask::new(80, 50);
+        let rect = URect::new(30, 18, 45, 30);
+        let params = MapGenParams::for_biome(BiomeType::Caverns, 3);
+
+        for seed in 0..6 {
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(BiomeType::Caverns, 3, MAX_DEPTH, seed, StairMode::BothWays, &mask);
+            // An item and a known trap inside the rect are not terrain, so they must survive
+            let inside_floor: Vec<(u32, u32)> = map.iter_floor().filter(|&(x, y)| rect.contains(UVec2::new(x, y))).collect();
+            if let [item, trap, ..] = inside_floor[..] {
+                map.set(item.0, item.1, TileType::Item(ItemKind::Gold));
+                map.set(trap.0, trap.1, TileType::TrapRevealed);
+            }
+            assert_eq!(map.connected_component_count(), 1, "seed {} starts connected", seed);
+            let before = map.snapshot();
+
+            let changes = map.regenerate_region(rect, &params, &[], &mask, &mut GlobalRng::with_seed(seed + 100));
+
+            for change in &changes {
+                assert!(rect.contains(UVec2::new(change.pos.0, change.pos.1)), "seed {}: {:?} changed outside the rect", seed, change.pos);
+            }
+            for (i, (&old, &new)) in before.tiles.iter().zip(map.tiles.iter()).enumerate() {
+                if !matches!(old, TileType::Floor | TileType::Wall | TileType::Water) {
+                    assert_eq!(old, new, "seed {}: tile {} was not terrain", seed, i);
+                }
+            }
+            assert_eq!(map.connected_component_count(), 1, "seed {} disconnected", seed);
+        }
+    }
+
+    #[test]
+    fn validated_generation_retries_until_a_layout_passes() {
+        let mask = EllipseMask::new(80, 50);
+        let mut calls = 0;
+        let mut map = GameMap::new(80, 50);
+
+        // The first two layouts are solid rock, the third is an open cave with far-apart stairs
+        let attempts = map.generate_validated_with(0, 9, &mask, |candidate, _rng| {
+            calls += 1;
+            if calls <= 2 {
+                return;
+            }
+            for (x, y) in candidate.iter_tiles().map(|(x, y, _)| (x, y)).collect::<Vec<_>>() {
+                if mask.is_within(x, y) {
+                    candidate.set(x, y, TileType::Floor);
+                }
+            }
+            candidate.set(20, 25, TileType::StairUp);
+            candidate.set(60, 25, TileType::StairDown);
+            candidate.stair_up_pos = Some((20, 25));
+            candidate.stair_down_pos = Some((60, 25));
+        });
+
+        assert_eq!(attempts, 3);
+        assert_eq!(calls, 3);
+        assert!(map.validate(&mask).is_ok());
+        assert_eq!(map.stairs(), (Some((20, 25)), Some((60, 25))));
+    }
+
+    #[test]
+    fn validated_generation_keeps_the_least_severe_failure() {
+        let mask = EllipseMask::new(80, 50);
+        let mut calls = 0;
+        let mut map = GameMap::new(80, 50);
+
+        // Every attempt fails: the first with one severe defect, the rest with two mild ones
+        let attempts = map.generate_validated_with(0, 9, &mask, |candidate, _rng| {
+            calls += 1;
+            for (x, y) in candidate.iter_tiles().map(|(x, y, _)| (x, y)).collect::<Vec<_>>() {
+                if mask.is_within(x, y) {
+                    candidate.set(x, y, TileType::Floor);
+                }
+            }
+            if calls == 1 {
+                // A recorded stair that isn't on the map
+                candidate.stair_up_pos = Some((20, 25));
+            } else {
+                // Stairs side by side, and one floor tile poking out of the boundary
+                candidate.set(20, 25, TileType::StairUp);
+                candidate.set(22, 25, TileType::StairDown);
+                candidate.stair_up_pos = Some((20, 25));
+                candidate.stair_down_pos = Some((22, 25));
+                let edge = (0..80).find(|&x| mask.is_within(x, 25)).unwrap();
+                candidate.set(edge - 1, 25, TileType::Floor);
+            }
+        });
+
+        assert_eq!(attempts, MAX_GENERATION_ATTEMPTS);
+        let defects = map.validate(&mask).unwrap_err();
+        assert_eq!(defects.len(), 2);
+        assert!(defects.iter().all(|defect| defect.severity() == 1), "{:?}", defects);
+    }
+
+    #[test]
+    fn water_bodies_keep_the_stairs_reachable() {
+        let mask = EllipseMask::new(80, 50);
+        for biome in [BiomeType::StygianPool, BiomeType::AbyssalHold] {
+            for seed in 0..5 {
+                let mut map = GameMap::new(80, 50);
+                map.generate_level_validated(biome, 4, MAX_DEPTH, seed, StairMode::BothWays, &mask);
+                assert!(!map.find_all(TileType::Water).is_empty(), "{:?} seed {} has no water", biome, seed);
+
+                let (Some(up), Some(down)) = map.stairs() else { panic!("{:?} seed {} is missing stairs", biome, seed) };
+                // Water can't be walked, so check reachability with the strictest biome rules
+                assert!(map.bfs_distance(up, down, BiomeType::Caverns).is_some(), "{:?} seed {}: stairs cut off", biome, seed);
+                assert_eq!(map.connected_component_count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn removable_walkable_refuses_a_corridor_tile() {
+        let mut map = GameMap::new(7, 5);
+        map.fill_rect(URect::new(1, 2, 6, 3), TileType::Floor);
+        assert!(!map.is_removable_walkable(3, 2), "the middle of a corridor is a cut tile");
+        assert!(map.is_removable_walkable(5, 2), "a dead end can go");
+
+        map.fill_rect(URect::new(1, 1, 6, 4), TileType::Floor);
+        assert!(map.is_removable_walkable(3, 2), "a room tile has a detour");
+    }
+
+    #[test]
+    fn bottom_level_records_its_single_altar() {
+        let mask = EllipseMask::new(80, 50);
+        let mut map = GameMap::new(80, 50);
+        map.generate_level_validated(BiomeType::Caverns, MAX_DEPTH, MAX_DEPTH, 7, StairMode::BothWays, &mask);
+
+        let altars: Vec<(u32, u32)> = map.iter_tiles()
+            .filter(|&(_, _, tile)| tile == TileType::Altar)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(altars.len(), 1);
+        assert_eq!(map.altar_pos, Some(altars[0]));
+        assert_eq!(map.stair_down_pos, None);
+
+        // The position survives a save round trip
+        let restored = GameMap::from_saved_data(&map.to_saved_data(BiomeType::Caverns, Default::default()));
+        assert_eq!(restored.altar_pos, map.altar_pos);
+    }
+
+    #[test]
+    fn biome_sprites_come_from_the_database_before_the_biome_config() {
+        let mut config = SpriteDatabaseConfig::default();
+        config.categories.insert("Caverns/floors".to_string(), vec!["dirt2".to_string()]);
+        let sprite_db = SpriteDatabase::from_config(config);
+        let map = open_room(5, 5);
+        let mut rng = tile_variant_rng(1, 2, 2);
+
+        // Caverns has no floor decorations, so every pick comes from the override
+        for _ in 0..20 {
+            assert_eq!(select_biome_asset(BiomeType::Caverns.get_config(), &sprite_db, TileType::Floor, &map, 2, 2, &mut rng), (2, 8));
+        }
+        // Without an override a biome keeps its own coordinates
+        let water = &BiomeType::Caverns.get_config().allowed_water_assets;
+        assert!(water.contains(&select_biome_asset(BiomeType::Caverns.get_config(), &sprite_db, TileType::Water, &map, 2, 2, &mut rng)));
+    }
+
+    #[test]
+    fn shipped_sprite_database_parses() {
+        let config: SpriteDatabaseConfig = ron::from_str(include_str!("../assets/config/sprite_database.ron")).unwrap();
+        assert_eq!(config.sprites.get("dirt2"), Some(&(2, 8)));
+        assert!(["floors", "wall_top", "wall_side"].iter().all(|category| config.categories.contains_key(*category)));
+    }
+
+    #[test]
+    fn a_short_dungeon_ends_in_an_altar_at_its_max_depth() {
+        let mask = EllipseMask::new(80, 50);
+        let count = |map: &GameMap, wanted: TileType| map.iter_tiles().filter(|&(_, _, tile)| tile == wanted).count();
+
+        let mut top = GameMap::new(80, 50);
+        top.generate_level_validated(BiomeType::Caverns, 0, 3, 11, StairMode::BothWays, &mask);
+        assert_eq!((count(&top, TileType::StairUp), count(&top, TileType::StairDown)), (0, 1), "level 0 has no way up");
+
+        let mut above_bottom = GameMap::new(80, 50);
+        above_bottom.generate_level_validated(BiomeType::Caverns, 2, 3, 11, StairMode::BothWays, &mask);
+        assert_eq!(count(&above_bottom, TileType::StairDown), 1);
+
+        let mut bottom = GameMap::new(80, 50);
+        bottom.generate_level_validated(BiomeType::Caverns, 3, 3, 11, StairMode::BothWays, &mask);
+        assert_eq!(count(&bottom, TileType::StairDown), 0, "no down stairs on the last level");
+        assert_eq!(count(&bottom, TileType::Trapdoor), 0);
+        assert_eq!(count(&bottom, TileType::Altar), 1);
+    }
+
+    #[test]
+    fn hidden_traps_draw_as_floor_and_revealed_ones_as_traps() {
+        let map = open_room(5, 5);
+        let sprite_db = SpriteDatabase::new();
+        for biome in [BiomeType::Caverns, BiomeType::Underglade, BiomeType::CinderGaol] {
+            let config = biome.get_config();
+            for seed in 0..10 {
+                let hidden = select_biome_asset(config, &sprite_db, TileType::TrapHidden, &map, 2, 2, &mut tile_variant_rng(seed, 2, 2));
+                let floor = select_biome_asset(config, &sprite_db, TileType::Floor, &map, 2, 2, &mut tile_variant_rng(seed, 2, 2));
+                assert_eq!(hidden, floor, "{:?} seed {}", biome, seed);
+            }
+            assert_eq!(select_biome_asset(config, &sprite_db, TileType::TrapRevealed, &map, 2, 2, &mut tile_variant_rng(0, 2, 2)), (15, 16));
+        }
+    }
+
+    #[test]
+    fn paths_walk_through_hidden_traps_but_around_revealed_ones() {
+        // A wall down column 3 with a short gap at (3, 2) and a long way round through (3, 5)
+        let mut map = open_room(7, 7);
+        for y in [1, 3, 4] {
+            map.set(3, y, TileType::Wall);
+        }
+
+        map.set(3, 2, TileType::TrapHidden);
+        let path = find_path((1, 2), (5, 2), &map, BiomeType::Caverns);
+        assert!(path.contains(&(3, 2)), "the player doesn't know about the trap");
+        assert_eq!(path.len(), 4);
+
+        map.set(3, 2, TileType::TrapRevealed);
+        let path = find_path((1, 2), (5, 2), &map, BiomeType::Caverns);
+        assert!(!path.is_empty() && !path.contains(&(3, 2)), "known traps are worth a detour");
+        assert!(path.contains(&(3, 5)));
+    }
+
+    #[test]
+    fn biomes_with_their_own_stairs_draw_them_and_the_rest_use_the_defaults() {
+        let sprite_db = SpriteDatabase::new();
+        let mut map = open_room(5, 5);
+        map.set(1, 1, TileType::StairUp);
+        map.set(3, 3, TileType::StairDown);
+        let stairs = |biome: BiomeType| {
+            let config = biome.get_config();
+            StairSprites {
+                up: select_biome_asset(config, &sprite_db, TileType::StairUp, &map, 1, 1, &mut tile_variant_rng(0, 1, 1)),
+                down: select_biome_asset(config, &sprite_db, TileType::StairDown, &map, 3, 3, &mut tile_variant_rng(0, 3, 3)),
+            }
+        };
+
+        assert_eq!(stairs(BiomeType::CinderGaol), StairSprites { up: (6, 16), down: (11, 16) });
+        assert_eq!(stairs(BiomeType::Underglade), StairSprites { up: (8, 16), down: (12, 16) });
+        assert_eq!(stairs(BiomeType::Caverns), StairSprites::DEFAULT);
+        assert_eq!(stairs(BiomeType::StygianPool), StairSprites::DEFAULT);
+    }
+
+    #[test]
+    fn wall_masks_match_known_wall_shapes() {
+        // A 3x3 floor pocket inside a 7x7 block of rock; y grows upward, so N is y + 1
+        let mut map = GameMap::new(7, 7);
+        map.fill_rect(URect::new(2, 2, 5, 5), TileType::Floor);
+
+        assert_eq!(map.wall_mask(0, 0), 15, "solid rock");
+        assert_eq!(map.wall_mask(3, 1), 2 | 4 | 8, "wall below the pocket is open to the north");
+        assert_eq!(map.wall_mask(3, 5), 1 | 2 | 8, "wall above the pocket is open to the south");
+        assert_eq!(map.wall_mask(1, 3), 1 | 4 | 8, "wall left of the pocket is open to the east");
+
+        // A lone pillar and the outer corners of an L of walls
+        let mut room = open_room(7, 7);
+        room.set(3, 3, TileType::Wall);
+        assert_eq!(room.wall_mask(3, 3), 0);
+        room.set(3, 4, TileType::Wall);
+        room.set(4, 3, TileType::Wall);
+        assert_eq!(room.wall_mask(3, 3), 1 | 2, "N+E corner");
+    }
+
+    #[test]
+    fn autotiled_biomes_dress_corners_and_the_rest_keep_the_top_side_split() {
+        let sprite_db = SpriteDatabase::new();
+        let mut room = open_room(7, 7);
+        for pos in [(3, 3), (3, 4), (4, 3)] {
+            room.set(pos.0, pos.1, TileType::Wall);
+        }
+        let wall = |biome: BiomeType, map: &GameMap, x: u32, y: u32| {
+            select_biome_asset(biome.get_config(), &sprite_db, TileType::Wall, map, x, y, &mut tile_variant_rng(0, x, y))
+        };
+
+        assert_eq!(wall(BiomeType::Caverns, &room, 3, 3), (1, 1), "outer corner");
+        assert_eq!(wall(BiomeType::Caverns, &room, 4, 3), (1, 0), "open to the south shows the side");
+        assert_eq!(wall(BiomeType::Caverns, &room, 3, 4), (0, 0), "a wall below shows the top");
+        assert_eq!(wall(BiomeType::Caverns, &room, 6, 6).0, 0, "the border corner only touches floor diagonally, so it shows a top");
+        let mut pocket = GameMap::new(5, 5);
+        pocket.set(2, 2, TileType::Floor);
+        assert_eq!(wall(BiomeType::Caverns, &pocket, 0, 0), (2, 0), "rock with no open neighbor is enclosed");
+        assert_eq!(wall(BiomeType::Caverns, &pocket, 1, 1).0, 0);
+        assert_eq!(wall(BiomeType::CinderGaol, &room, 3, 3), (1, 5));
+        assert_eq!(wall(BiomeType::Underglade, &room, 4, 3), (1, 1));
+        for biome in BiomeType::all() {
+            assert!(matches!(biome.get_config().wall_autotile.len(), 0 | 16), "{:?} has a partial mask table", biome);
+        }
+
+        // No table: walls with a wall below them show a top, the rest a side
+        let config = BiomeType::FungalDeep.get_config();
+        assert!(config.wall_autotile.is_empty());
+        let top = wall(BiomeType::FungalDeep, &room, 3, 4);
+        let side = wall(BiomeType::FungalDeep, &room, 4, 3);
+        assert!(config.allowed_wall_assets.contains(&top) && config.allowed_wall_assets.contains(&side));
+    }
+
+    #[test]
+    fn generated_levels_pass_validation() {
+        let mask = EllipseMask::new(80, 50);
+        for &biome in BiomeType::all() {
+            for seed in 0..3 {
+                let mut map = GameMap::new(80, 50);
+                map.generate_level_validated(biome, 1, MAX_DEPTH, seed, StairMode::BothWays, &mask);
+                assert!(map.validate(&mask).is_ok(), "{:?} seed {}: {:?}", biome, seed, map.validate(&mask));
+            }
+        }
+    }
+
+    #[test]
+    fn validate_names_each_broken_invariant() {
+        let mask = EllipseMask::new(80, 50);
+        let mut map = GameMap::new(80, 50);
+        map.fill_rect(URect::new(10, 20, 30, 30), TileType::Floor);
+        map.fill_rect(URect::new(50, 20, 70, 30), TileType::Floor);
+        map.set(0, 0, TileType::Floor); // Outside the ellipse
+        map.stair_up_pos = Some((15, 25));
+        map.set(15, 25, TileType::StairUp);
+        map.stair_down_pos = Some((60, 25)); // Recorded but never placed
+
+        let defects = map.validate(&mask).unwrap_err();
+        assert!(defects.contains(&MapDefect::WalkableOutsideBoundary { pos: (0, 0) }));
+        assert!(defects.contains(&MapDefect::StairMismatch { pos: (60, 25), expected: TileType::StairDown, found: TileType::Floor }));
+        assert!(defects.contains(&MapDefect::Disconnected { components: 3 }));
+        assert!(defects.contains(&MapDefect::StairsUnreachable { up: (15, 25), down: (60, 25) }));
+        assert!(!defects.iter().any(|defect| matches!(defect, MapDefect::TooFewWalkable { .. })));
+
+        assert_eq!(GameMap::new(80, 50).validate(&mask), Err(vec![MapDefect::NoWalkableTiles]));
+    }
+
+    #[test]
+    fn smoothing_keeps_the_stairs_and_the_boundary() {
+        let mask = EllipseMask::new(80, 50);
+        let mut map = GameMap::new(80, 50);
+        // Jagged single-tile spurs around two stairs that a harsh rule would fill in completely
+        for (x, y) in map.iter_tiles().map(|(x, y, _)| (x, y)).collect::<Vec<_>>() {
+            if mask.is_within(x, y) && (x + y) % 3 != 0 {
+                map.set(x, y, TileType::Floor);
+            }
+        }
+        map.set(0, 0, TileType::Floor);
+        map.set(20, 25, TileType::StairUp);
+        map.set(60, 25, TileType::StairDown);
+
+        map.smooth(5, 1, 9, &mask);
+
+        assert_eq!(map.get(20, 25), TileType::StairUp);
+        assert_eq!(map.get(60, 25), TileType::StairDown);
+        assert_eq!(map.get(0, 0), TileType::Wall, "outside the boundary stays rock");
+        assert!(map.iter_tiles().all(|(x, y, tile)| mask.is_within(x, y) || tile == TileType::Wall));
+    }
+
+    #[test]
+    fn smoothed_biomes_still_place_reachable_stairs() {
+        let mask = EllipseMask::new(80, 50);
+        for &biome in BiomeType::all() {
+            if MapGenParams::for_biome(biome, 1).smoothing.is_none() {
+                continue;
+            }
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(biome, 1, MAX_DEPTH, 4, StairMode::BothWays, &mask);
+            let (Some(up), Some(down)) = map.stairs() else { panic!("{:?} is missing stairs", biome) };
+            assert_eq!(map.get(up.0, up.1), TileType::StairUp);
+            assert_eq!(map.get(down.0, down.1), TileType::StairDown);
+            assert!(map.bfs_distance(up, down, biome).is_some(), "{:?} stairs are cut off", biome);
+        }
+    }
+
+    #[test]
+    fn the_same_seed_rebuilds_the_same_level() {
+        let mask = EllipseMask::new(80, 50);
+        for &biome in BiomeType::all() {
+            let build = || {
+                let mut map = GameMap::new(80, 50);
+                map.generate_level_validated(biome, 2, MAX_DEPTH, 77, StairMode::BothWays, &mask);
+                map
+            };
+            let (first, second) = (build(), build());
+            assert!(first.tiles == second.tiles, "{:?} rebuilt a different layout", biome);
+            assert_eq!(first.stairs(), second.stairs());
+        }
+    }
+
+    #[test]
+    fn generated_items_sit_on_floor_away_from_the_stairs() {
+        let mask = EllipseMask::new(80, 50);
+        let mut map = GameMap::new(80, 50);
+        map.generate_level_validated(BiomeType::Caverns, 1, MAX_DEPTH, 5, StairMode::BothWays, &mask);
+        let items: Vec<_> = map.iter_tiles().filter(|&(_, _, tile)| matches!(tile, TileType::Item(_))).collect();
+        assert!(!items.is_empty());
+        let (up, down) = map.stairs();
+        assert!(items.iter().all(|&(x, y, _)| Some((x, y)) != up && Some((x, y)) != down));
+    }
+
+    #[test]
+    fn crop_keeps_tiles_at_translated_coordinates_and_drops_outside_stairs() {
+        let mut map = GameMap::new(10, 8);
+        for (i, (x, y)) in [(3, 2), (5, 4), (6, 3)].into_iter().enumerate() {
+            map.set(x, y, [TileType::Floor, TileType::Water, TileType::TrapRevealed][i]);
+        }
+        map.set(4, 3, TileType::StairUp);
+        map.stair_up_pos = Some((4, 3));
+        map.set(9, 7, TileType::StairDown);
+        map.stair_down_pos = Some((9, 7));
+
+        let cropped = map.cropped(URect::new(2, 1, 7, 6));
+        assert_eq!((cropped.width, cropped.height), (5, 5));
+        for (x, y, tile) in cropped.iter_tiles() {
+            assert_eq!(tile, map.get(x + 2, y + 1), "({}, {})", x, y);
+        }
+        assert_eq!(cropped.stair_up_pos, Some((2, 2)));
+        assert_eq!(cropped.stair_down_pos, None, "the down stair was outside the crop");
+
+        // A rect running off the map is clamped to it
+        let clamped = map.cropped(URect::new(8, 6, 20, 20));
+        assert_eq!((clamped.width, clamped.height), (2, 2));
+        assert_eq!(clamped.stair_down_pos, Some((1, 1)));
+    }
+
+    #[test]
+    fn resize_keeps_the_overlap_and_fills_the_rest() {
+        let mut map = open_room(4, 3);
+        map.set(3, 2, TileType::StairDown);
+        map.stair_down_pos = Some((3, 2));
+
+        let grown = map.resized(6, 5, TileType::Water);
+        for (x, y, tile) in grown.iter_tiles() {
+            let expected = if x < 4 && y < 3 { map.get(x, y) } else { TileType::Water };
+            assert_eq!(tile, expected, "({}, {})", x, y);
+        }
+        assert_eq!(grown.stair_down_pos, Some((3, 2)));
+
+        let shrunk = map.resized(3, 3, TileType::Water);
+        assert_eq!(shrunk.tiles.len(), 9);
+        assert!(shrunk.iter_tiles().all(|(x, y, tile)| tile == map.get(x, y)));
+        assert_eq!(shrunk.stair_down_pos, None, "the stair fell off the edge");
+    }
+
+    #[test]
+    fn find_all_returns_every_tile_of_a_kind_in_row_major_order() {
+        let mut map = open_room(6, 5);
+        map.set(4, 1, TileType::StairUp);
+        for (x, y) in [(3, 3), (1, 2), (2, 2)] {
+            map.set(x, y, TileType::Water);
+        }
+
+        assert_eq!(map.find_all(TileType::StairUp), vec![(4, 1)]);
+        assert_eq!(map.find_all(TileType::Water), vec![(1, 2), (2, 2), (3, 3)]);
+        assert!(map.find_all(TileType::StairDown).is_empty());
+        assert_eq!(map.get_floor_positions().len(), 4 * 3 - 4);
+        assert_eq!(map.get_floor_positions(), map.find_all(TileType::Floor));
+    }
+
+    #[test]
+    fn diff_and_apply_round_trip_and_an_untouched_map_has_no_diff() {
+        let mut map = open_room(8, 6);
+        let baseline = map.snapshot();
+        assert!(map.diff(&baseline).is_empty());
+
+        map.set(2, 2, TileType::Water);
+        map.set(5, 3, TileType::Item(ItemKind::Scroll));
+        map.set(0, 0, TileType::Floor);
+        let changes = map.diff(&baseline);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.contains(&TileChange { pos: (2, 2), from: TileType::Floor, to: TileType::Water }));
+
+        let mut replay = GameMap::new(8, 6);
+        replay.tiles = baseline.tiles.clone();
+        replay.apply_diff(&changes);
+        assert_eq!(replay.tiles, map.tiles);
+        assert!(replay.diff(&map.snapshot()).is_empty());
+
+        // Swapped changes undo the edit
+        let undo: Vec<TileChange> = changes.iter().map(|c| TileChange { pos: c.pos, from: c.to, to: c.from }).collect();
+        replay.apply_diff(&undo);
+        assert_eq!(replay.tiles, baseline.tiles);
+    }
+
+    #[test]
+    fn carving_a_circle_sets_about_its_area_and_stays_on_the_map() {
+        let mask = EllipseMask::new(60, 60);
+        for radius in [2, 5, 9] {
+            let mut map = GameMap::new(60, 60);
+            let changed = map.carve_circle((30, 30), radius, TileType::Floor, &mask);
+            let area = std::f32::consts::PI * (radius * radius) as f32;
+            assert!((changed as f32 - area).abs() <= area * 0.2 + 4.0, "radius {} carved {} tiles", radius, changed);
+            assert_eq!(map.find_all(TileType::Floor).len(), changed);
+            assert!(map.find_all(TileType::Floor).iter().all(|&(x, y)| (x as i64 - 30).pow(2) + (y as i64 - 30).pow(2) <= (radius * radius) as i64));
+
+            // Carving the same tile again changes nothing
+            assert_eq!(map.carve_circle((30, 30), radius, TileType::Floor, &mask), 0);
+        }
+
+        // Brushes over the map edge clip to the map and boundary instead of wrapping or panicking
+        let mut map = GameMap::new(60, 60);
+        let disc = map.carve_circle((30, 2), 8, TileType::Floor, &mask);
+        assert!(disc > 0 && disc < 200, "the top edge clips the disc, got {}", disc);
+        let changed = disc + map.carve_circle((0, 0), 12, TileType::Floor, &mask)
+            + map.carve_rect(URect::new(50, 25, 90, 35), TileType::Floor, &mask);
+        assert_eq!(map.tiles.len(), 60 * 60);
+        assert_eq!(map.find_all(TileType::Floor).len(), changed);
+        assert!(map.find_all(TileType::Floor).iter().all(|&(x, y)| mask.is_within(x, y)));
+    }
+
+    #[test]
+    fn every_tunnel_style_connects_its_endpoints() {
+        let mask = EllipseMask::new(60, 40);
+        for style in [CorridorStyle::LShaped, CorridorStyle::Diagonal, CorridorStyle::Winding] {
+            for seed in 0..5 {
+                let mut rng = GlobalRng::with_seed(seed);
+                let mut map = GameMap::new(60, 40);
+                let (a, b) = ((14, 12), (45, 28));
+                let carved = map.tunnel_between(a, b, style, 1, &mask, &mut rng);
+
+                assert!(carved.contains(&a) && carved.contains(&b), "{:?}", style);
+                assert!(carved.iter().all(|&(x, y)| map.is_within_boundary(x, y, &mask) && map.get(x, y) == TileType::Floor));
+                assert_eq!(map.find_all(TileType::Floor).len(), carved.len(), "{:?} returns every carved tile", style);
+                assert!(map.bfs_distance(a, b, BiomeType::Caverns).is_some(), "{:?} seed {} left a gap", style, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_ring_of_walls_around_a_room_is_exposed() {
+        let mut map = GameMap::new(11, 9);
+        map.fill_rect(URect::new(3, 3, 8, 6), TileType::Floor);
+
+        let ring: Vec<(u32, u32)> = map.iter_tiles()
+            .filter(|&(x, y, tile)| (2..=8).contains(&x) && (2..=6).contains(&y) && tile == TileType::Wall)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(ring.len(), 7 * 5 - 5 * 3);
+        let exposed: Vec<(u32, u32)> = map.iter_tiles().filter(|&(x, y, _)| map.is_exposed_wall(x, y)).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(exposed, ring);
+
+        assert!(map.is_exposed_wall(2, 2), "diagonal contact counts");
+        assert!(!map.is_exposed_wall(0, 0) && !map.is_exposed_wall(1, 4), "solid interior rock");
+        assert!(!map.is_exposed_wall(4, 4), "floor is never a wall");
+        assert!(!map.is_exposed_wall(11, 4), "off the map");
+    }
+
+    #[test]
+    fn spawn_points_are_distinct_floor_clear_of_the_start_and_each_other() {
+        let mask = EllipseMask::new(80, 50);
+        for seed in 0..4 {
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(BiomeType::Caverns, 2, MAX_DEPTH, seed, StairMode::BothWays, &mask);
+            let start = map.stair_up_pos.unwrap();
+            let points = map.spawn_points(8, 6, start, &mut GlobalRng::with_seed(seed));
+
+            assert!(!points.is_empty());
+            let distance = |a: (u32, u32), b: (u32, u32)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+            for (i, &p) in points.iter().enumerate() {
+                assert_eq!(map.get(p.0, p.1), TileType::Floor, "seed {}: {:?} is not plain floor", seed, p);
+                assert!(distance(p, start) >= 6, "seed {}: {:?} too close to the start", seed, p);
+                for &q in &points[i + 1..] {
+                    assert!(distance(p, q) >= 6, "seed {}: {:?} and {:?} too close", seed, p, q);
+                }
+            }
+        }
+
+        // A cramped room hands back what fits rather than breaking the spacing
+        let map = open_room(6, 6);
+        let points = map.spawn_points(10, 3, (1, 1), &mut GlobalRng::with_seed(1));
+        assert!(points.len() < 10 && !points.is_empty());
+    }
+
+    #[test]
+    fn tiny_and_one_wide_maps_generate_without_panicking() {
+        for (width, height) in [(5, 5), (1, 12), (12, 1), (1, 1), (3, 3), (0, 0)] {
+            let mask = EllipseMask::new(width, height);
+            for &biome in BiomeType::all() {
+                for seed in 0..3 {
+                    let mut map = GameMap::new(width, height);
+                    map.generate_level_validated(biome, 1, MAX_DEPTH, seed, StairMode::BothWays, &mask);
+                    assert_eq!(map.tiles.len(), (width * height) as usize, "{:?} {}x{}", biome, width, height);
+                    let (up, down) = map.stairs();
+                    assert!(up.into_iter().chain(down).all(|(x, y)| x < width && y < height), "{:?} {}x{} stairs off the map", biome, width, height);
+                    let _ = map.validate(&mask);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn region_id_names_each_room_by_its_first_tile() {
+        let mut map = GameMap::new(12, 6);
+        map.fill_rect(URect::new(1, 1, 5, 5), TileType::Floor);
+        map.fill_rect(URect::new(7, 2, 11, 5), TileType::Floor);
+
+        // Every tile of a room shares the id, which is the room's first tile in row-major order
+        assert!(map.iter_floor().filter(|&(x, _)| x < 6).all(|(x, y)| map.region_id(x, y) == Some(map.idx(1, 1))));
+        assert!(map.iter_floor().filter(|&(x, _)| x > 6).all(|(x, y)| map.region_id(x, y) == Some(map.idx(7, 2))));
+        assert_eq!(map.region_id(0, 0), None, "walls aren't in a region");
+        assert_eq!(map.region_id(40, 3), None);
+
+        // Joining the rooms puts them in one region named by the earlier room
+        map.fill_rect(URect::new(5, 3, 7, 4), TileType::Floor);
+        assert_eq!(map.region_id(10, 4), Some(map.idx(1, 1)));
+    }
+
+    #[test]
+    fn component_count_sees_separate_rooms() {
+        let mut map = GameMap::new(12, 6);
+        map.fill_rect(URect::new(1, 1, 5, 5), TileType::Floor);
+        map.fill_rect(URect::new(7, 1, 11, 5), TileType::Floor);
+        assert_eq!(map.connected_component_count(), 2);
+
+        map.fill_rect(URect::new(5, 2, 7, 3), TileType::Floor);
+        assert_eq!(map.connected_component_count(), 1);
+    }
+
+    #[test]
+    fn iter_tiles_walks_rows_in_order_with_the_same_values_as_get() {
+        let mut map = GameMap::new(5, 4);
+        map.fill_rect(URect::new(1, 1, 4, 3), TileType::Floor);
+        map.set(2, 1, TileType::Water);
+        map.set(3, 2, TileType::StairUp);
+
+        let mut direct = Vec::new();
+        for y in 0..map.height {
+            for x in 0..map.width {
+                direct.push((x, y, map.get(x, y)));
+            }
+        }
+        assert_eq!(map.iter_tiles().collect::<Vec<_>>(), direct);
+
+        let floor: Vec<(u32, u32)> = direct.iter().filter(|t| t.2 == TileType::Floor).map(|&(x, y, _)| (x, y)).collect();
+        assert_eq!(map.iter_floor().collect::<Vec<_>>(), floor);
+        assert_eq!(floor.len(), 4);
+    }
+
+    #[test]
+    fn flips_and_rotations_move_tiles_and_stairs_together() {
+        let mut map = open_room(7, 4);
+        map.set(2, 1, TileType::StairUp);
+        map.stair_up_pos = Some((2, 1));
+        map.set(5, 2, TileType::Water);
+
+        let h = map.flipped_horizontal();
+        let v = map.flipped_vertical();
+        let r = map.rotated_90();
+        assert_eq!(h.get(4, 1), TileType::StairUp);
+        assert_eq!(h.stair_up_pos, Some((4, 1)));
+        assert_eq!(v.get(5, 1), TileType::Water);
+        assert_eq!(v.stair_up_pos, Some((2, 2)));
+        assert_eq!((r.width, r.height), (4, 7));
+        // A clockwise turn sends (x, y) to (y, width - 1 - x)
+        assert_eq!(r.get(1, 4), TileType::StairUp);
+        assert_eq!(r.stair_up_pos, Some((1, 4)));
+        assert_eq!(r.get(2, 1), TileType::Water);
+
+        // Flipping twice, or turning four times, gives the original back
+        assert_eq!(h.flipped_horizontal().tiles, map.tiles);
+        assert_eq!(v.flipped_vertical().tiles, map.tiles);
+        let full_turn = r.rotated_90().rotated_90().rotated_90();
+        assert_eq!((full_turn.width, full_turn.tiles.clone(), full_turn.stair_up_pos), (map.width, map.tiles.clone(), map.stair_up_pos));
+    }
+
+    #[test]
+    fn descend_only_levels_have_no_up_stairs() {
+        // A short run still covers the top, the middle and the altar level
+        let max_depth = 8;
+        let mask = EllipseMask::new(80, 50);
+        let biomes: Vec<BiomeType> = BiomeType::all().iter().copied().filter(|b| b.is_implemented()).collect();
+        for level in 0..=max_depth {
+            let biome = biomes[level as usize % biomes.len()];
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(biome, level, max_depth, level as u64, StairMode::DescendOnly, &mask);
+            assert!(map.find_all(TileType::StairUp).is_empty(), "{:?} level {} has an up stair", biome, level);
+            assert_eq!(map.stair_up_pos, None);
+            assert!(map.stair_down_pos.is_some() || level == max_depth, "level {} still leads down", level);
+        }
+    }
+
+    #[test]
+    fn both_connectivity_strategies_leave_one_component() {
+        let mask = EllipseMask::new(60, 40);
+        for connectivity in [ConnectivityStrategy::Tunnel, ConnectivityStrategy::MergeNearest { max_wall: 3 }] {
+            let params = MapGenParams { connectivity, ..MapGenParams::for_biome(BiomeType::Caverns, 1) };
+            for seed in 0..4 {
+                // Two rooms a single wall apart, and a third far off to the side
+                let mut map = GameMap::new(60, 40);
+                map.fill_rect(URect::new(16, 15, 24, 24), TileType::Floor);
+                map.fill_rect(URect::new(25, 15, 32, 24), TileType::Floor);
+                map.fill_rect(URect::new(42, 17, 48, 22), TileType::Floor);
+                assert_eq!(map.connected_component_count(), 3);
+
+                map.ensure_connectivity(&params, &mask, &mut GlobalRng::with_seed(seed));
+                assert_eq!(map.connected_component_count(), 1, "{:?} seed {}", connectivity, seed);
+                if connectivity != ConnectivityStrategy::Tunnel {
+                    assert!((15..24).any(|y| map.get(24, y) == TileType::Floor), "the thin wall was opened up");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_middle_of_a_big_room_is_more_open_than_a_corridor() {
+        // A 9x9 room with a one-wide corridor running east from it
+        let mut map = GameMap::new(24, 11);
+        map.fill_rect(URect::new(1, 1, 10, 10), TileType::Floor);
+        map.fill_rect(URect::new(10, 5, 22, 6), TileType::Floor);
+        assert_eq!(map.openness(5, 5), 0, "nothing is computed until compute_openness runs");
+        map.compute_openness();
+
+        assert_eq!(map.openness(5, 5), 5);
+        assert_eq!(map.openness(16, 5), 1);
+        assert_eq!(map.openness(1, 1), 1, "room corners touch the wall");
+        assert_eq!(map.openness(0, 0), 0);
+        assert!(map.openness(5, 5) > map.openness(16, 5));
+    }
+
+    #[test]
+    fn a_pass_that_buries_a_placed_stair_fails_validation() {
+        let mask = EllipseMask::new(80, 50);
+        let mut map = GameMap::new(80, 50);
+        map.generate_level_validated(BiomeType::Caverns, 3, MAX_DEPTH, 21, StairMode::BothWays, &mask);
+        let (up, down) = map.stairs();
+        let (up, down) = (up.unwrap(), down.unwrap());
+        assert!(map.stair_mismatches().is_empty());
+
+        // A later pass walls over the down stair while the recorded position still points at it
+        map.carve_circle(down, 1, TileType::Wall, &mask);
+
+        assert_eq!(map.stairs(), (Some(up), Some(down)), "the recorded positions don't follow the tiles");
+        let expected = MapDefect::StairMismatch { pos: down, expected: TileType::StairDown, found: TileType::Wall };
+        assert_eq!(map.stair_mismatches(), vec![expected.clone()]);
+        assert!(map.validate(&mask).unwrap_err().contains(&expected));
+    }
+
+    #[test]
+    fn stairs_keep_their_distance_or_go_as_far_as_the_room_allows() {
+        for seed in 0..20 {
+            let mut rng = GlobalRng::with_seed(seed);
+            let mut roomy = open_room(30, 20);
+            roomy.place_stairs(3, MAX_DEPTH, StairMode::BothWays, &mut rng);
+            let ((ux, uy), (dx, dy)) = (roomy.stair_up_pos.unwrap(), roomy.stair_down_pos.unwrap());
+            assert!(ux.abs_diff(dx) + uy.abs_diff(dy) >= MIN_STAIR_DISTANCE, "seed {}", seed);
+
+            // Nothing in a 4x4 floor is MIN_STAIR_DISTANCE away, so the down stair takes the farthest tile
+            let mut cramped = open_room(6, 6);
+            cramped.place_stairs(3, MAX_DEPTH, StairMode::BothWays, &mut rng);
+            let (up, down) = (cramped.stair_up_pos.unwrap(), cramped.stair_down_pos.unwrap());
+            let farthest = cramped.get_floor_positions().into_iter().chain([down])
+                .map(|(x, y)| x.abs_diff(up.0) + y.abs_diff(up.1))
+                .max()
+                .unwrap();
+            assert_eq!(down.0.abs_diff(up.0) + down.1.abs_diff(up.1), farthest, "seed {}", seed);
+        }
+    }
+}
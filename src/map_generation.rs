@@ -1,25 +1,158 @@
 // Map Generation Module - Compact Organic Algorithm
-use rand::RngCore;
+use rand::{Rng, RngCore};
 use crate::components::TileType;
 use crate::biome::BiomeType;
 
+/// Shape of the corridors carved to connect disconnected areas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CorridorStyle {
+    LShaped,  // Horizontal leg then vertical leg
+    Diagonal, // Straight Bresenham line toward the target
+    Winding,  // Random staircase walk toward the target
+}
+
+/// How separate floor regions are joined after generation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectivityStrategy {
+    Tunnel, // Tunnel from every region to the largest one
+    // Join regions nearest-first by breaking through the thinnest wall between them,
+    // tunneling instead when that wall is more than `max_wall` tiles thick
+    MergeNearest { max_wall: u32 },
+}
+
+/// Thresholds for the optional cellular-automata smoothing post-process
+#[derive(Clone, Copy, Debug)]
+pub struct CellularSmoothing {
+    pub iterations: u32,
+    pub birth: u8,   // Floor becomes wall with at least this many wall neighbors
+    pub survive: u8, // Wall stays wall with at least this many wall neighbors
+}
+
 /// Map generation parameters that control the generation algorithm
 #[derive(Clone, Debug)]
 pub struct MapGenParams {
     pub max_rooms: u32,
+    pub corridor_width: u32,
+    pub corridor_style: CorridorStyle,
+    pub water_bodies: u32, // Number of contiguous water pools to flood (0 = none)
+    pub smoothing: Option<CellularSmoothing>,
+    pub connectivity: ConnectivityStrategy,
 }
 
 impl MapGenParams {
     /// Get generation parameters for a specific biome
-    pub fn for_biome(_biome: BiomeType, level: u32) -> Self {
+    pub fn for_biome(biome: BiomeType, level: u32) -> Self {
+        // Corridor shape is what gives each biome its feel
+        let (corridor_width, corridor_style) = match biome {
+            BiomeType::Caverns => (2, CorridorStyle::Diagonal), // Wide natural passages
+            BiomeType::ChthronicCrypts | BiomeType::HypogealKnot => (1, CorridorStyle::Winding), // Tight and twisty
+            _ => (1, CorridorStyle::LShaped),
+        };
+
+        let water_bodies = match biome {
+            BiomeType::StygianPool => 3,
+            BiomeType::AbyssalHold => 2,
+            _ => 0,
+        };
+
+        // Caverns round off dead-end nubs and lone pillars for a more natural look
+        let smoothing = match biome {
+            BiomeType::Caverns => Some(CellularSmoothing { iterations: 2, birth: 7, survive: 1 }),
+            _ => None,
+        };
+
+        // Organic biomes open up thin walls rather than scarring the caves with tunnels
+        let connectivity = match biome {
+            BiomeType::Caverns | BiomeType::Underglade => ConnectivityStrategy::MergeNearest { max_wall: 3 },
+            _ => ConnectivityStrategy::Tunnel,
+        };
+
         // All biomes use the same compact organic generation
         // max_rooms controls number of interior wall divisions (2-4)
         Self {
             max_rooms: 3 + (level / 5).min(2), // 3-5 divisions based on level
+            corridor_width,
+            corridor_style,
+            water_bodies,
+            smoothing,
+            connectivity,
         }
     }
 }
 
+/// Centerline of a corridor from `start` to `end`, excluding `start` and including `end`.
+/// Every step is 4-connected so a 1-wide corridor is always walkable.
+pub fn corridor_path(start: (u32, u32), end: (u32, u32), style: CorridorStyle, rng: &mut dyn RngCore) -> Vec<(i32, i32)> {
+    let mut x = start.0 as i32;
+    let mut y = start.1 as i32;
+    let target_x = end.0 as i32;
+    let target_y = end.1 as i32;
+    let mut path = Vec::new();
+
+    match style {
+        CorridorStyle::LShaped => {
+            while x != target_x {
+                x += (target_x - x).signum();
+                path.push((x, y));
+            }
+            while y != target_y {
+                y += (target_y - y).signum();
+                path.push((x, y));
+            }
+        },
+        CorridorStyle::Diagonal => {
+            let dx = (target_x - x).abs();
+            let dy = -(target_y - y).abs();
+            let sx = (target_x - x).signum();
+            let sy = (target_y - y).signum();
+            let mut err = dx + dy;
+
+            while x != target_x || y != target_y {
+                let e2 = 2 * err;
+                let step_x = e2 >= dy;
+                let step_y = e2 <= dx;
+                if step_x {
+                    err += dy;
+                    x += sx;
+                }
+                if step_x && step_y {
+                    // Fill the corner so diagonal steps stay 4-connected
+                    path.push((x, y));
+                }
+                if step_y {
+                    err += dx;
+                    y += sy;
+                }
+                path.push((x, y));
+            }
+        },
+        CorridorStyle::Winding => {
+            while x != target_x || y != target_y {
+                let remaining_x = (target_x - x).abs();
+                let remaining_y = (target_y - y).abs();
+
+                // Pick an axis at random, weighted by how far is left along it
+                if rng.random_range(0..remaining_x + remaining_y) < remaining_x {
+                    x += (target_x - x).signum();
+                } else {
+                    y += (target_y - y).signum();
+                }
+                path.push((x, y));
+            }
+        },
+    }
+
+    path
+}
+
+/// Offsets covered by a square brush of the given width centred on a corridor tile
+pub fn brush_offsets(width: u32) -> impl Iterator<Item = (i32, i32)> {
+    let width = width.max(1) as i32;
+    let low = -(width - 1) / 2;
+    let high = low + width - 1;
+    (low..=high).flat_map(move |dy| (low..=high).map(move |dx| (dx, dy)))
+}
+
 /// Trait for map generators (using RngCore which is dyn-safe)
 pub trait MapGenerator {
     fn generate(&mut self, width: u32, height: u32, params: &MapGenParams, rng: &mut dyn RngCore) -> Vec<TileType>;
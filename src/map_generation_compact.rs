@@ -1,8 +1,8 @@
 // Compact Organic Map Generator - separate file due to size
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 use rand::Rng;
 use crate::components::TileType;
-use crate::map_generation::{MapGenerator, MapGenParams, flatten_tiles};
+use crate::map_generation::{MapGenerator, MapGenParams, flatten_tiles, corridor_path, brush_offsets};
 
 pub struct CompactOrganicGenerator;
 
@@ -10,6 +10,14 @@ impl MapGenerator for CompactOrganicGenerator {
     fn generate(&mut self, width: u32, height: u32, params: &MapGenParams, rng: &mut dyn rand::RngCore) -> Vec<TileType> {
         let mut tiles = vec![vec![TileType::Wall; width as usize]; height as usize];
 
+        // Too small for a wall ring around any floor: a single open cell is the only sensible layout
+        if width < 3 || height < 3 {
+            if width > 0 && height > 0 {
+                tiles[(height / 2) as usize][(width / 2) as usize] = TileType::Floor;
+            }
+            return flatten_tiles(tiles, width, height);
+        }
+
         // Step 1: Generate organic outer boundary (20x20 constraint)
         let boundary = self.generate_organic_boundary(width, height, rng);
 
@@ -24,7 +32,7 @@ impl MapGenerator for CompactOrganicGenerator {
         self.create_doorways(&mut tiles, &divisions, width, height, rng);
 
         // Step 5: Ensure connectivity
-        self.ensure_all_rooms_connected(&mut tiles, width, height);
+        self.ensure_all_rooms_connected(&mut tiles, params, width, height, rng);
 
         flatten_tiles(tiles, width, height)
     }
@@ -36,8 +44,8 @@ impl CompactOrganicGenerator {
         let center_x = width / 2;
         let center_y = height / 2;
 
-        // Start with a seed point
-        let mut active = HashSet::new();
+        // Start with a seed point. Ordered, so candidates come out the same way for the same seed
+        let mut active = BTreeSet::new();
         active.insert((center_x, center_y));
 
         // Target size (20x20 = 400 tiles, but organic so ~300-350)
@@ -110,8 +118,16 @@ impl CompactOrganicGenerator {
         // Create 2-4 wall divisions
         let num_divisions = rng.random_range(2..=4).min(params.max_rooms as i32) as usize;
 
+        // A division needs 3 tiles of room on both sides; skip the axis (or the whole pass) when the blob is too thin
+        let can_split_horizontal = bbox.height > 6;
+        let can_split_vertical = bbox.width > 6;
         for _ in 0..num_divisions {
-            let is_horizontal = rng.random::<bool>();
+            let is_horizontal = match (can_split_horizontal, can_split_vertical) {
+                (true, true) => rng.random::<bool>(),
+                (true, false) => true,
+                (false, true) => false,
+                (false, false) => break,
+            };
 
             if is_horizontal {
                 // Horizontal wall
@@ -175,7 +191,7 @@ impl CompactOrganicGenerator {
                 if division.is_horizontal {
                     let y = division.start.1;
                     let wall_length = division.end.0 - division.start.0;
-                    let doorway_x = division.start.0 + rng.random_range(2..wall_length.saturating_sub(doorway_width + 2));
+                    let doorway_x = division.start.0 + doorway_offset(wall_length, doorway_width, rng);
 
                     // Punch out the doorway
                     for dx in 0..doorway_width {
@@ -187,7 +203,7 @@ impl CompactOrganicGenerator {
                 } else {
                     let x = division.start.0;
                     let wall_length = division.end.1 - division.start.1;
-                    let doorway_y = division.start.1 + rng.random_range(2..wall_length.saturating_sub(doorway_width + 2));
+                    let doorway_y = division.start.1 + doorway_offset(wall_length, doorway_width, rng);
 
                     // Punch out the doorway
                     for dy in 0..doorway_width {
@@ -202,7 +218,8 @@ impl CompactOrganicGenerator {
     }
 
     // Ensure all floor regions are connected
-    fn ensure_all_rooms_connected(&self, tiles: &mut Vec<Vec<TileType>>, width: u32, height: u32) {
+    fn ensure_all_rooms_connected(&self, tiles: &mut Vec<Vec<TileType>>, params: &MapGenParams,
+                                  width: u32, height: u32, rng: &mut dyn rand::RngCore) {
         let regions = self.find_disconnected_regions(tiles, width, height);
 
         if regions.len() <= 1 {
@@ -214,8 +231,8 @@ impl CompactOrganicGenerator {
             let pos1 = regions[i][0];
             let pos2 = regions[i + 1][0];
 
-            // Create a simple corridor
-            self.create_simple_corridor(tiles, pos1, pos2, width, height);
+            // Create a corridor in the biome's style
+            self.create_simple_corridor(tiles, pos1, pos2, params, width, height, rng);
         }
     }
 
@@ -266,41 +283,28 @@ impl CompactOrganicGenerator {
         region
     }
 
-    fn create_simple_corridor(&self, tiles: &mut Vec<Vec<TileType>>,
-                             start: (u32, u32), end: (u32, u32), width: u32, height: u32) {
-        let mut x = start.0 as i32;
-        let mut y = start.1 as i32;
-        let target_x = end.0 as i32;
-        let target_y = end.1 as i32;
-
-        // Move horizontally first
-        while x != target_x {
-            if x < target_x {
-                x += 1;
-            } else {
-                x -= 1;
-            }
-
-            if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
-                tiles[y as usize][x as usize] = TileType::Floor;
-            }
-        }
-
-        // Then vertically
-        while y != target_y {
-            if y < target_y {
-                y += 1;
-            } else {
-                y -= 1;
-            }
+    fn create_simple_corridor(&self, tiles: &mut Vec<Vec<TileType>>, start: (u32, u32), end: (u32, u32),
+                             params: &MapGenParams, width: u32, height: u32, rng: &mut dyn rand::RngCore) {
+        for (path_x, path_y) in corridor_path(start, end, params.corridor_style, rng) {
+            for (dx, dy) in brush_offsets(params.corridor_width) {
+                let x = path_x + dx;
+                let y = path_y + dy;
 
-            if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
-                tiles[y as usize][x as usize] = TileType::Floor;
+                // Keep the outermost ring solid so wide brushes never reach the map edge
+                if x > 0 && x < width as i32 - 1 && y > 0 && y < height as i32 - 1 {
+                    tiles[y as usize][x as usize] = TileType::Floor;
+                }
             }
         }
     }
 }
 
+// Where along a wall a doorway starts, keeping 2 tiles clear of each end; short walls get a centered doorway
+fn doorway_offset(wall_length: u32, doorway_width: u32, rng: &mut dyn rand::RngCore) -> u32 {
+    let max = wall_length.saturating_sub(doorway_width + 2);
+    if max > 2 { rng.random_range(2..max) } else { wall_length.saturating_sub(doorway_width) / 2 }
+}
+
 #[derive(Clone, Debug)]
 struct WallDivision {
     start: (u32, u32),
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+use crate::components::LosCache;
+
+// Aggregated timings for the hot paths behind reported stutters (generation, FOV, particles).
+// Timing only happens with the `perf_metrics` feature; otherwise every call is a no-op.
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerfMetrics>()
+            .add_systems(Update, print_perf_metrics);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerfStat {
+    MapGeneration,
+    FovFull,
+    FovIncremental,
+    ParticleSpawn,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimingStat {
+    pub count: u32,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl TimingStat {
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.count == 0 { Duration::ZERO } else { self.total / self.count }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PerfMetrics {
+    pub map_generation: TimingStat,
+    pub fov_full: TimingStat,
+    pub fov_incremental: TimingStat,
+    pub particle_spawn: TimingStat,
+}
+
+impl PerfMetrics {
+    pub const ENABLED: bool = cfg!(feature = "perf_metrics");
+
+    // Start a measurement; None when metrics are compiled out
+    #[inline]
+    pub fn now() -> Option<Instant> {
+        if Self::ENABLED { Some(Instant::now()) } else { None }
+    }
+
+    // Finish a measurement started with `now`
+    #[inline]
+    pub fn record(&mut self, stat: PerfStat, start: Option<Instant>) {
+        let Some(start) = start else { return; };
+        let elapsed = start.elapsed();
+        match stat {
+            PerfStat::MapGeneration => self.map_generation.record(elapsed),
+            PerfStat::FovFull => self.fov_full.record(elapsed),
+            PerfStat::FovIncremental => self.fov_incremental.record(elapsed),
+            PerfStat::ParticleSpawn => self.particle_spawn.record(elapsed),
+        }
+    }
+}
+
+// Debug: Shift+P prints the collected metrics
+pub fn print_perf_metrics(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    metrics: Res<PerfMetrics>,
+    los_cache: Res<LosCache>,
+) {
+    if !(keyboard_input.just_pressed(KeyCode::KeyP) &&
+       (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight))) {
+        return;
+    }
+
+    if !PerfMetrics::ENABLED {
+        println!("Perf metrics are disabled - rebuild with --features perf_metrics");
+        return;
+    }
+
+    println!("=== Perf Metrics ===");
+    for (name, stat) in [
+        ("Map generation", &metrics.map_generation),
+        ("FOV full", &metrics.fov_full),
+        ("FOV incremental", &metrics.fov_incremental),
+        ("Particle spawn", &metrics.particle_spawn),
+    ] {
+        println!("{}: {} runs, avg {:.2?}, max {:.2?}", name, stat.count, stat.average(), stat.max);
+    }
+
+    if let Some(hit_rate) = los_cache.hit_rate() {
+        println!("LOS cache hit rate: {:.1}% ({} entries)", hit_rate, los_cache.entries.len());
+    }
+    println!("====================");
+}
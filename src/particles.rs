@@ -1,17 +1,21 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::components::{Player, CurrentLevel, TileType, MapTile, BiomeParticle, ParticleType, ParticleSpawner, ParticleSettings, WindState, GlobalRng};
+use crate::components::{Player, CurrentLevel, TileType, MapTile, BiomeParticle, ParticleType, ParticleSpawner, ParticleSettings, WindState, GlobalRng, AccessibilitySettings, VisibleTiles};
 use crate::biome::BiomeType;
 use crate::states::GameState;
 use crate::map::GameMap;
+use crate::constants::TILE_SIZE;
+use crate::metrics::{PerfMetrics, PerfStat};
 
 // Base particle system constants
 const PARTICLE_FADE_DISTANCE: f32 = 900.0;
 const SPAWN_BOUNDARY_PADDING: f32 = 10.0; // Extra tiles beyond map edges for spawning
+const WIND_BIAS_SPEED: f32 = 6.0; // Pixels per second of drift along the wind direction at strength 1.0
+const FULL_DENSITY_OPENNESS: u32 = 4; // Tiles from the nearest wall at which particles spawn at full density
 
 // Biome-specific particle configuration
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Reflect)]
 pub struct BiomeParticleConfig {
     pub primary_max_particles: usize,
     pub secondary_max_particles: usize,
@@ -27,11 +31,21 @@ pub struct BiomeParticleConfig {
     pub primary_velocity_range: (Vec2, Vec2),
     pub secondary_velocity_range: (Vec2, Vec2),
     pub wind_strength_multiplier: f32,
+    // Z ranges per layer; the player sits at Z=1, so keep ranges below it unless the layer is foreground
+    pub primary_z_range: (f32, f32),
+    pub secondary_z_range: (f32, f32),
     pub movement_style: MovementStyle,
+    pub spawn_visibility: SpawnVisibility,
     pub enabled: bool,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum SpawnVisibility {
+    Anywhere,    // Whole-map ambient coverage (Caverns mist)
+    VisibleOnly, // Only on floor the player can currently see; falls back to Anywhere while nothing is visible
+}
+
+#[derive(Clone, Debug, PartialEq, Reflect)]
 pub enum MovementStyle {
     Gentle,      // Slow, smooth movement (Underglade)
     Erratic,     // Sharp, unpredictable movement (Fungal Deep spores)
@@ -65,7 +79,11 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::new(-8.0, -6.0), Vec2::new(8.0, 4.0)),
                 secondary_velocity_range: (Vec2::new(-5.0, -4.0), Vec2::new(5.0, 4.0)),
                 wind_strength_multiplier: 1.2,
+                // Below the player
+                primary_z_range: (0.5, 0.9),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Gentle,
+                spawn_visibility: SpawnVisibility::VisibleOnly,
                 enabled: true,
             },
             BiomeType::FungalDeep => Self {
@@ -90,7 +108,10 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::new(-12.0, -8.0), Vec2::new(12.0, 6.0)),
                 secondary_velocity_range: (Vec2::new(-8.0, -6.0), Vec2::new(8.0, 6.0)),
                 wind_strength_multiplier: 0.8,
+                primary_z_range: (0.5, 0.9),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Erratic,
+                spawn_visibility: SpawnVisibility::VisibleOnly,
                 enabled: true,
             },
             BiomeType::Caverns => Self {
@@ -115,7 +136,11 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::new(-3.0, -2.0), Vec2::new(3.0, 8.0)),
                 secondary_velocity_range: (Vec2::new(-2.0, -1.0), Vec2::new(2.0, 3.0)),
                 wind_strength_multiplier: 0.5,
+                // Foreground mist drifts over the player; glints stay underneath
+                primary_z_range: (1.5, 2.0),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Floating,
+                spawn_visibility: SpawnVisibility::Anywhere,
                 enabled: true,
             },
             BiomeType::CinderGaol => Self {
@@ -140,7 +165,10 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::new(-6.0, 2.0), Vec2::new(6.0, 10.0)),
                 secondary_velocity_range: (Vec2::new(-4.0, -2.0), Vec2::new(4.0, 2.0)),
                 wind_strength_multiplier: 1.5,
+                primary_z_range: (0.5, 0.9),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Swirling,
+                spawn_visibility: SpawnVisibility::VisibleOnly,
                 enabled: true,
             },
             BiomeType::StygianPool => Self {
@@ -165,7 +193,10 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::new(-4.0, -3.0), Vec2::new(4.0, 3.0)),
                 secondary_velocity_range: (Vec2::new(-3.0, -2.0), Vec2::new(3.0, 4.0)),
                 wind_strength_multiplier: 0.7,
+                primary_z_range: (0.5, 0.9),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Flowing,
+                spawn_visibility: SpawnVisibility::VisibleOnly,
                 enabled: true,
             },
             // Biomes with no particle effects
@@ -185,7 +216,10 @@ impl BiomeParticleConfig {
                 primary_velocity_range: (Vec2::ZERO, Vec2::ZERO),
                 secondary_velocity_range: (Vec2::ZERO, Vec2::ZERO),
                 wind_strength_multiplier: 0.0,
+                primary_z_range: (0.5, 0.9),
+                secondary_z_range: (0.6, 0.95),
                 movement_style: MovementStyle::Gentle,
+                spawn_visibility: SpawnVisibility::Anywhere,
                 enabled: false,
             },
         }
@@ -282,16 +316,31 @@ fn spawn_biome_particles(
     player_query: Query<&Transform, With<Player>>,
     tile_query: Query<(&TilePos, &MapTile)>,
     existing_particles: Query<&BiomeParticle>,
-    map: Res<GameMap>,
+    map: Option<Res<GameMap>>,
     mut rng: ResMut<GlobalRng>,
+    mut metrics: ResMut<PerfMetrics>,
+    visible_tiles: Res<VisibleTiles>,
 ) {
+    let Some(map) = map else { return; };
     if !settings.enabled || !spawner.config.enabled {
         return;
     }
 
-    let Ok(_player_transform) = player_query.single() else {
+    let Ok(player_transform) = player_query.single() else {
         return;
     };
+    // Nothing can spawn between timer ticks, so skip the per-frame setup below
+    if spawner.initial_spawn_complete && !spawner.primary_timer.just_finished() && !spawner.secondary_timer.just_finished() {
+        return;
+    }
+    let started = PerfMetrics::now();
+    let spawn_center = settings.spawn_near_player.then(|| player_transform.translation.truncate());
+
+    // Visibility-limited biomes only spawn where the player can see, until FOV has produced anything
+    let visible_positions: Option<Vec<(u32, u32)>> = (spawner.config.spawn_visibility == SpawnVisibility::VisibleOnly
+        && !visible_tiles.0.is_empty())
+        .then(|| visible_tiles.0.iter().copied().collect());
+    let visible = visible_positions.as_deref();
 
     // Count existing particles
     let primary_count = existing_particles.iter()
@@ -301,15 +350,28 @@ fn spawn_biome_particles(
         .filter(|p| p.particle_type() == ParticleType::Secondary)
         .count();
 
+    // Global budget across both layers; near the cap each layer gets a share proportional to its own max
+    let mut remaining = settings.max_total_particles.saturating_sub(primary_count + secondary_count);
+    let layer_total = (spawner.config.primary_max_particles + spawner.config.secondary_max_particles).max(1);
+    let primary_share = spawner.config.primary_max_particles as f32 / layer_total as f32;
+
     // Initial spawn when entering a new biome
     if !spawner.initial_spawn_complete {
-        let initial_primary = (spawner.config.primary_max_particles as f32 * 0.67) as usize;
-        let initial_secondary = (spawner.config.secondary_max_particles as f32 * 0.67) as usize;
+        let fill = settings.initial_fill.clamp(0.0, 1.0);
+        let mut initial_primary = (spawner.config.primary_max_particles as f32 * fill) as usize;
+        let mut initial_secondary = (spawner.config.secondary_max_particles as f32 * fill) as usize;
+        let requested = initial_primary + initial_secondary;
+        if requested > remaining {
+            let scale = remaining as f32 / requested as f32;
+            initial_primary = (initial_primary as f32 * scale) as usize;
+            initial_secondary = (initial_secondary as f32 * scale) as usize;
+        }
+        remaining -= initial_primary + initial_secondary;
 
         // Collect primary spawn positions across entire map
         let mut primary_positions = Vec::with_capacity(initial_primary);
         for _ in 0..initial_primary {
-            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, rng.as_mut()) {
+            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, spawn_center, visible, rng.as_mut()) {
                 primary_positions.push(spawn_pos);
             }
         }
@@ -317,19 +379,20 @@ fn spawn_biome_particles(
         // Collect secondary spawn positions across entire map
         let mut secondary_positions = Vec::with_capacity(initial_secondary);
         for _ in 0..initial_secondary {
-            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, rng.as_mut()) {
+            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, spawn_center, visible, rng.as_mut()) {
                 secondary_positions.push(spawn_pos);
             }
         }
 
-        // Batch spawn all primary particles
+        // Batch spawn all primary particles, warmed up so the field looks settled rather than freshly spawned
+        let warmup = settings.warmup_seconds.max(0.0);
         for spawn_pos in primary_positions {
-            spawn_primary_particle(&mut commands, spawn_pos, &spawner.config, rng.as_mut());
+            spawn_primary_particle(&mut commands, spawn_pos, &spawner.config, warmup, rng.as_mut());
         }
 
         // Batch spawn all secondary particles
         for spawn_pos in secondary_positions {
-            spawn_secondary_particle(&mut commands, spawn_pos, &spawner.config, rng.as_mut());
+            spawn_secondary_particle(&mut commands, spawn_pos, &spawner.config, warmup, rng.as_mut());
         }
 
         spawner.initial_spawn_complete = true;
@@ -341,43 +404,69 @@ fn spawn_biome_particles(
     if spawner.primary_timer.just_finished() && primary_count < spawner.config.primary_max_particles {
         // Multiply base spawn count by density multiplier - no cap to allow high density
         let spawn_count = ((3.0 * settings.density_multiplier).max(1.0) as usize).min(30);
+        let spawn_count = spawn_count.min((remaining as f32 * primary_share) as usize);
+        remaining -= spawn_count;
 
         // Collect spawn positions across entire map
         let mut spawn_positions = Vec::with_capacity(spawn_count);
         for _ in 0..spawn_count {
-            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, rng.as_mut()) {
+            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, spawn_center, visible, rng.as_mut()) {
                 spawn_positions.push(spawn_pos);
             }
         }
 
         // Batch spawn all particles
         for spawn_pos in spawn_positions {
-            spawn_primary_particle(&mut commands, spawn_pos, &spawner.config, rng.as_mut());
+            spawn_primary_particle(&mut commands, spawn_pos, &spawner.config, 0.0, rng.as_mut());
         }
     }
 
-    if spawner.secondary_timer.just_finished() && secondary_count < spawner.config.secondary_max_particles {
+    if spawner.secondary_timer.just_finished() && secondary_count < spawner.config.secondary_max_particles && remaining > 0 {
         use rand::Rng;
         if rng.random::<f32>() < spawner.config.secondary_spawn_chance {
-            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, rng.as_mut()) {
-                spawn_secondary_particle(&mut commands, spawn_pos, &spawner.config, rng.as_mut());
+            if let Some(spawn_pos) = find_map_spawn_position(&tile_query, &map, spawn_center, visible, rng.as_mut()) {
+                spawn_secondary_particle(&mut commands, spawn_pos, &spawner.config, 0.0, rng.as_mut());
             }
         }
     }
+
+    metrics.record(PerfStat::ParticleSpawn, started);
 }
 
-// Spawn particles across extended area beyond map boundaries for consistent coverage
+// Spawn particles across extended area beyond map boundaries for consistent coverage,
+// or within the fade radius around `around` when spawning near the player.
+// With `visible` set, candidates are drawn from those tiles instead (already near the player)
 fn find_map_spawn_position(
     tile_query: &Query<(&TilePos, &MapTile)>,
     map: &GameMap,
+    around: Option<Vec2>,
+    visible: Option<&[(u32, u32)]>,
     rng: &mut impl rand::Rng,
 ) -> Option<Vec2> {
 
     // Try up to 12 times to find a suitable spawn position
     for _ in 0..12 {
-        // Random position with padding beyond map bounds for edge consistency
-        let spawn_tile_x = rng.random_range(-SPAWN_BOUNDARY_PADDING..(map.width as f32 + SPAWN_BOUNDARY_PADDING));
-        let spawn_tile_y = rng.random_range(-SPAWN_BOUNDARY_PADDING..(map.height as f32 + SPAWN_BOUNDARY_PADDING));
+        let (spawn_tile_x, spawn_tile_y) = match (visible, around) {
+            (Some(tiles), _) => {
+                let (x, y) = tiles[rng.random_range(0..tiles.len())];
+                (x as f32, y as f32)
+            }
+            (None, Some(center)) => {
+                // Uniform point in the fade disc, so nothing pops in where it would already be visible
+                let angle = rng.random_range(0.0..std::f32::consts::TAU);
+                let distance = PARTICLE_FADE_DISTANCE * rng.random::<f32>().sqrt();
+                let world_pos = center + Vec2::from_angle(angle) * distance;
+                (
+                    world_pos.x / TILE_SIZE + (map.width as f32 / 2.0 - 0.5),
+                    world_pos.y / TILE_SIZE + (map.height as f32 / 2.0 - 0.5),
+                )
+            }
+            (None, None) => (
+                // Random position with padding beyond map bounds for edge consistency
+                rng.random_range(-SPAWN_BOUNDARY_PADDING..(map.width as f32 + SPAWN_BOUNDARY_PADDING)),
+                rng.random_range(-SPAWN_BOUNDARY_PADDING..(map.height as f32 + SPAWN_BOUNDARY_PADDING)),
+            ),
+        };
 
         // Check if within actual map bounds (for tile validation)
         let is_on_map = spawn_tile_x >= 0.0 && spawn_tile_x < map.width as f32 &&
@@ -389,12 +478,17 @@ fn find_map_spawn_position(
             if !is_suitable_for_particles_fast(spawn_tile_pos, tile_query) {
                 continue; // Skip walls on the map
             }
+            // Thin particles out in tight passages so open chambers read as airier
+            let openness = map.openness(spawn_tile_x as u32, spawn_tile_y as u32);
+            if rng.random::<f32>() > (openness as f32 / FULL_DENSITY_OPENNESS as f32).clamp(0.25, 1.0) {
+                continue;
+            }
         }
         // If outside the map boundaries, we accept it (no tile check needed)
 
         // Convert tile coordinates to world coordinates
-        let world_x = (spawn_tile_x - (map.width as f32 / 2.0 - 0.5)) * 32.0 + rng.random_range(-16.0..16.0);
-        let world_y = (spawn_tile_y - (map.height as f32 / 2.0 - 0.5)) * 32.0 + rng.random_range(-16.0..16.0);
+        let world_x = (spawn_tile_x - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE + rng.random_range(-TILE_SIZE / 2.0..TILE_SIZE / 2.0);
+        let world_y = (spawn_tile_y - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE + rng.random_range(-TILE_SIZE / 2.0..TILE_SIZE / 2.0);
         return Some(Vec2::new(world_x, world_y));
     }
 
@@ -414,7 +508,28 @@ fn is_suitable_for_particles_fast(pos: Vec2, tile_query: &Query<(&TilePos, &MapT
     true
 }
 
-fn spawn_primary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &BiomeParticleConfig, rng: &mut impl rand::Rng) {
+// Head start for a particle spawned on level entry: a random age up to `warmup` seconds (short of
+// its lifetime), applied as plain drift along its velocity. Movement styles and wind aren't replayed
+fn warm_up(particle: &mut BiomeParticle, spawn_pos: Vec2, warmup: f32, rng: &mut impl rand::Rng) -> Vec2 {
+    let max_age = warmup.min(particle.lifetime.duration().as_secs_f32() * 0.9);
+    if max_age <= 0.0 {
+        return spawn_pos;
+    }
+    let age = rng.random_range(0.0..max_age);
+    let elapsed = std::time::Duration::from_secs_f32(age);
+    particle.lifetime.tick(elapsed);
+    particle.glow_timer.tick(elapsed);
+    spawn_pos + particle.velocity * particle.layer_speed * age
+}
+
+// Random depth inside a layer's Z range; a collapsed range pins the layer to one depth
+fn particle_z(range: (f32, f32), rng: &mut impl rand::Rng) -> f32 {
+    let z = if range.1 > range.0 { rng.random_range(range.0..range.1) } else { range.0 };
+    debug_assert!(z >= range.0 && z <= range.1.max(range.0), "particle Z {} outside configured range {:?}", z, range);
+    z
+}
+
+fn spawn_primary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &BiomeParticleConfig, warmup: f32, rng: &mut impl rand::Rng) {
 
     let lifetime = rng.random_range(config.primary_lifetime_range.0..config.primary_lifetime_range.1);
     let velocity = Vec2::new(
@@ -435,25 +550,28 @@ fn spawn_primary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &Bio
     let size_offset = rng.random_range(0.0..std::f32::consts::TAU);
     let color_shift = rng.random_range(0.0..std::f32::consts::TAU);
 
+    let mut particle = BiomeParticle {
+        lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+        velocity,
+        glow_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        layer_speed: rng.random_range(0.6..1.4),
+        original_alpha: color.alpha(),
+        packed_data: BiomeParticle::pack(wind_offset, size_offset, color_shift, ParticleType::Primary),
+    };
+    let position = warm_up(&mut particle, spawn_pos, warmup, rng);
+
     commands.spawn((
         Sprite {
             color,
             custom_size: Some(Vec2::new(size, size)),
             ..default()
         },
-        Transform::from_translation(spawn_pos.extend(1.0)),
-        BiomeParticle {
-            lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
-            velocity,
-            glow_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
-            layer_speed: rng.random_range(0.6..1.4),
-            original_alpha: color.alpha(),
-            packed_data: BiomeParticle::pack(wind_offset, size_offset, color_shift, ParticleType::Primary),
-        },
+        Transform::from_translation(position.extend(particle_z(config.primary_z_range, rng))),
+        particle,
     ));
 }
 
-fn spawn_secondary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &BiomeParticleConfig, rng: &mut impl rand::Rng) {
+fn spawn_secondary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &BiomeParticleConfig, warmup: f32, rng: &mut impl rand::Rng) {
 
     let lifetime = rng.random_range(config.secondary_lifetime_range.0..config.secondary_lifetime_range.1);
     let velocity = Vec2::new(
@@ -474,21 +592,24 @@ fn spawn_secondary_particle(commands: &mut Commands, spawn_pos: Vec2, config: &B
     let size_offset = rng.random_range(0.0..std::f32::consts::TAU);
     let color_shift = rng.random_range(0.0..std::f32::consts::TAU);
 
+    let mut particle = BiomeParticle {
+        lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+        velocity,
+        glow_timer: Timer::from_seconds(rng.random_range(1.5..3.0), TimerMode::Repeating),
+        layer_speed: rng.random_range(0.4..1.2),
+        original_alpha: color.alpha(),
+        packed_data: BiomeParticle::pack(wind_offset, size_offset, color_shift, ParticleType::Secondary),
+    };
+    let position = warm_up(&mut particle, spawn_pos, warmup, rng);
+
     commands.spawn((
         Sprite {
             color,
             custom_size: Some(Vec2::new(size, size)),
             ..default()
         },
-        Transform::from_translation(spawn_pos.extend(2.0)),
-        BiomeParticle {
-            lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
-            velocity,
-            glow_timer: Timer::from_seconds(rng.random_range(1.5..3.0), TimerMode::Repeating),
-            layer_speed: rng.random_range(0.4..1.2),
-            original_alpha: color.alpha(),
-            packed_data: BiomeParticle::pack(wind_offset, size_offset, color_shift, ParticleType::Secondary),
-        },
+        Transform::from_translation(position.extend(particle_z(config.secondary_z_range, rng))),
+        particle,
     ));
 }
 
@@ -500,11 +621,18 @@ fn update_biome_particles(
     player_query: Query<&Transform, (With<Player>, Without<BiomeParticle>)>,
     tile_query: Query<(&TilePos, &MapTile)>,
     mut rng: ResMut<GlobalRng>,
+    accessibility: Res<AccessibilitySettings>,
+    settings: Res<ParticleSettings>,
+    visible_tiles: Res<VisibleTiles>,
+    map: Option<Res<GameMap>>,
 ) {
     if !spawner.config.enabled {
         return;
     }
 
+    // FOV fade only once there's a map and a computed field of view to check against
+    let fov_map = map.as_deref().filter(|_| settings.fade_outside_fov && !visible_tiles.0.is_empty());
+
     let Ok(player_transform) = player_query.single() else {
         return;
     };
@@ -518,15 +646,18 @@ fn update_biome_particles(
 
         let mut movement = particle.velocity * delta * particle.layer_speed;
 
-        // Apply movement style based on biome
-        apply_movement_style(&mut movement, &spawner.config.movement_style, &particle,
-                           current_time, delta, wind_state.strength, rng.as_mut());
+        // Wind drift always applies; reduced motion only calms the biome's sway, jitter and swirl
+        apply_wind_bias(&mut movement, &spawner.config.movement_style, &wind_state, delta);
+        if !accessibility.reduce_motion {
+            apply_movement_style(&mut movement, &spawner.config.movement_style, &particle,
+                               current_time, delta, &wind_state, rng.as_mut());
+        }
 
         // Simplified wall interaction
         if (current_time * 4.0) as i32 % 10 == 0 {
             let tile_pos = Vec2::new(
-                (transform.translation.x / 32.0).round(),
-                (transform.translation.y / 32.0).round(),
+                (transform.translation.x / TILE_SIZE).round(),
+                (transform.translation.y / TILE_SIZE).round(),
             );
             
             if is_near_wall_fast(tile_pos, &tile_query) {
@@ -537,7 +668,7 @@ fn update_biome_particles(
         transform.translation.x += movement.x;
         transform.translation.y += movement.y;
 
-        update_particle_visuals(&mut particle, &mut sprite, &mut transform, current_time);
+        update_particle_visuals(&mut particle, &mut sprite, &mut transform, current_time, accessibility.reduce_motion);
 
         // Distance-based fading
         let distance_to_player = transform.translation.distance(player_transform.translation);
@@ -547,21 +678,44 @@ fn update_biome_particles(
             let fade_factor = (1.0 - (distance_to_player / PARTICLE_FADE_DISTANCE)).max(0.0);
             sprite.color.set_alpha(particle.original_alpha * fade_factor);
         }
+
+        // Hide particles over tiles outside the current FOV so they don't show through walls;
+        // particles off the map keep the distance fade alone
+        if let Some(map) = fov_map {
+            if let Some((x, y)) = map.world_to_grid(transform.translation.truncate()) {
+                if !visible_tiles.contains(x, y) {
+                    sprite.color.set_alpha(0.0);
+                }
+            }
+        }
     }
 }
 
+// Steady push along the current wind direction so a gust carries everything the same way;
+// light particles ride it fully, embers mostly hold their own swirl
+fn apply_wind_bias(movement: &mut Vec2, style: &MovementStyle, wind_state: &WindState, delta: f32) {
+    let wind_bias_weight = match style {
+        MovementStyle::Gentle | MovementStyle::Flowing => 1.0,
+        MovementStyle::Floating => 0.7,
+        MovementStyle::Erratic => 0.4,
+        MovementStyle::Swirling => 0.15,
+    };
+    *movement += wind_state.direction * wind_state.strength * wind_bias_weight * WIND_BIAS_SPEED * delta;
+}
+
 fn apply_movement_style(
     movement: &mut Vec2,
     style: &MovementStyle,
     particle: &BiomeParticle,
     current_time: f32,
     delta: f32,
-    wind_strength: f32,
+    wind_state: &WindState,
     rng: &mut impl rand::Rng,
 ) {
 
     // Unpack wind_offset once
     let wind_offset = particle.wind_offset();
+    let wind_strength = wind_state.strength;
 
     match style {
         MovementStyle::Gentle => {
@@ -634,6 +788,7 @@ fn update_particle_visuals(
     sprite: &mut Sprite,
     transform: &mut Transform,
     current_time: f32,
+    reduce_motion: bool,
 ) {
     match particle.particle_type() {
         ParticleType::Primary => {
@@ -643,7 +798,7 @@ fn update_particle_visuals(
             sprite.color.set_alpha(particle.original_alpha * alpha_pulse * age_factor);
 
             let size_offset = particle.size_offset();
-            let size_breath = (current_time * 1.5 + size_offset).sin() * 0.05 + 1.0;
+            let size_breath = if reduce_motion { 1.0 } else { (current_time * 1.5 + size_offset).sin() * 0.05 + 1.0 };
             transform.scale = Vec3::new(size_breath, size_breath, 1.0);
         },
         ParticleType::Secondary => {
@@ -723,4 +878,249 @@ fn handle_particle_debug(
                 if settings.debug_mode { "enabled" } else { "disabled" },
                 spawner.current_biome, spawner.config.enabled);
     }
-}
\ No newline at end of file
+
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        settings.spawn_near_player = !settings.spawn_near_player;
+        println!("Particle spawn area: {}", if settings.spawn_near_player { "near player" } else { "whole map" });
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        settings.fade_outside_fov = !settings.fade_outside_fov;
+        println!("Particles outside FOV: {}", if settings.fade_outside_fov { "hidden" } else { "shown" });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{EllipseMask, StairMode};
+    use crate::constants::MAX_DEPTH;
+    use rand::SeedableRng;
+    use crate::metrics::PerfMetrics;
+
+    #[test]
+    fn particles_only_spawn_on_tiles_inside_the_generation_boundary() {
+        // The same mask main.rs hands to generation
+        let mask = EllipseMask::new(80, 50);
+        for (i, &biome) in BiomeType::all().iter().filter(|b| b.is_implemented()).enumerate() {
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(biome, 1, MAX_DEPTH, 40 + i as u64, StairMode::BothWays, &mask);
+
+            let mut world = World::new();
+            for (x, y, tile_type) in map.iter_tiles() {
+                world.spawn((TilePos { x, y }, MapTile { tile_type }));
+            }
+            let suitable = world.run_system_cached_with(|In((width, height)): In<(u32, u32)>, tiles: Query<(&TilePos, &MapTile)>| {
+                let mut suitable = Vec::new();
+                for y in 0..height {
+                    for x in 0..width {
+                        if is_suitable_for_particles_fast(Vec2::new(x as f32, y as f32), &tiles) {
+                            suitable.push((x, y));
+                        }
+                    }
+                }
+                suitable
+            }, (map.width, map.height)).unwrap();
+
+            assert!(!suitable.is_empty());
+            for (x, y) in suitable {
+                assert!(map.is_within_boundary(x, y, &mask), "{:?}: particle tile ({}, {}) is outside the boundary", biome, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn spawned_particles_stay_in_their_layer_z_range() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for &biome in BiomeType::all() {
+            let config = BiomeParticleConfig::for_biome(biome);
+            if !config.enabled {
+                continue;
+            }
+            for (primary, range) in [(true, config.primary_z_range), (false, config.secondary_z_range)] {
+                let mut world = World::new();
+                let mut commands_queue = bevy::ecs::world::CommandQueue::default();
+                let mut commands = Commands::new(&mut commands_queue, &world);
+                for _ in 0..100 {
+                    if primary {
+                        spawn_primary_particle(&mut commands, Vec2::ZERO, &config, 0.5, &mut rng);
+                    } else {
+                        spawn_secondary_particle(&mut commands, Vec2::ZERO, &config, 0.5, &mut rng);
+                    }
+                }
+                commands_queue.apply(&mut world);
+
+                let zs: Vec<f32> = world.query::<(&Transform, &BiomeParticle)>().iter(&world).map(|(t, _)| t.translation.z).collect();
+                assert_eq!(zs.len(), 100);
+                assert!(zs.iter().all(|&z| z >= range.0 && z <= range.1), "{:?} primary={} Z outside {:?}", biome, primary, range);
+            }
+        }
+
+        // Only the Caverns mist is configured over the player
+        let player_z = 1.0;
+        for &biome in BiomeType::all() {
+            let config = BiomeParticleConfig::for_biome(biome);
+            assert!(config.secondary_z_range.1 < player_z, "{:?}", biome);
+            assert_eq!(config.primary_z_range.0 > player_z, biome == BiomeType::Caverns, "{:?}", biome);
+        }
+    }
+
+    #[test]
+    fn spawning_never_exceeds_the_total_budget_and_shares_it_across_layers() {
+        for &biome in BiomeType::all().iter().filter(|&&b| BiomeParticleConfig::for_biome(b).enabled) {
+            for budget in [40, 120] {
+                let mut map = GameMap::new(40, 30);
+                map.fill(TileType::Floor);
+
+                let mut world = World::new();
+                world.init_resource::<Time>();
+                world.init_resource::<ParticleSpawner>();
+                world.init_resource::<WindState>();
+                world.init_resource::<PerfMetrics>();
+                world.init_resource::<VisibleTiles>();
+                world.insert_resource(GlobalRng::with_seed(9));
+                world.insert_resource(CurrentLevel { level: 1, biome });
+                world.insert_resource(ParticleSettings { max_total_particles: budget, ..default() });
+                world.insert_resource(map);
+                world.spawn((Player { x: 20, y: 15 }, Transform::default()));
+
+                let count = |world: &mut World, kind: ParticleType| {
+                    world.query::<&BiomeParticle>().iter(world).filter(|p| p.particle_type() == kind).count()
+                };
+                for frame in 0..120 {
+                    world.resource_mut::<Time>().advance_by(std::time::Duration::from_millis(300));
+                    world.run_system_cached(update_particle_spawner).unwrap();
+                    world.run_system_cached(spawn_biome_particles).unwrap();
+                    world.flush();
+
+                    let (primary, secondary) = (count(&mut world, ParticleType::Primary), count(&mut world, ParticleType::Secondary));
+                    assert!(primary + secondary <= budget, "{:?} frame {}: {} + {} over a budget of {}", biome, frame, primary, secondary, budget);
+                    if frame == 0 {
+                        assert!(primary > 0 && secondary > 0, "{:?}: the entry fill is scaled down, not given to one layer", biome);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wind_pushes_particles_along_its_direction() {
+        let wind = WindState { direction: Vec2::new(-0.6, -0.8), strength: 1.2, ..default() };
+        let particle = BiomeParticle {
+            lifetime: Timer::from_seconds(10.0, TimerMode::Once),
+            velocity: Vec2::ZERO,
+            glow_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            layer_speed: 1.0,
+            original_alpha: 1.0,
+            packed_data: BiomeParticle::pack(0.7, 0.0, 0.0, ParticleType::Primary),
+        };
+        let delta = 1.0 / 60.0;
+
+        // The bias on its own is steady and weighted per style
+        let bias = |style: &MovementStyle| {
+            let mut movement = Vec2::ZERO;
+            apply_wind_bias(&mut movement, style, &wind, delta);
+            movement
+        };
+        for style in [MovementStyle::Gentle, MovementStyle::Erratic, MovementStyle::Floating, MovementStyle::Swirling, MovementStyle::Flowing] {
+            assert!(bias(&style).normalize().abs_diff_eq(wind.direction, 1e-5), "{:?}", style);
+        }
+        assert!(bias(&MovementStyle::Gentle).length() > bias(&MovementStyle::Swirling).length() * 4.0);
+        assert_eq!(bias(&MovementStyle::Gentle), bias(&MovementStyle::Flowing));
+
+        // Over a few seconds the wobble averages out and the drift follows the wind
+        for style in [MovementStyle::Gentle, MovementStyle::Flowing] {
+            let mut rng = GlobalRng::with_seed(4);
+            let mut displacement = Vec2::ZERO;
+            for frame in 0..600 {
+                let mut movement = Vec2::ZERO;
+                apply_wind_bias(&mut movement, &style, &wind, delta);
+                apply_movement_style(&mut movement, &style, &particle, frame as f32 * delta, delta, &wind, &mut rng);
+                displacement += movement;
+            }
+            assert!(displacement.normalize().dot(wind.direction) > 0.9, "{:?} drifted {:?}", style, displacement);
+        }
+    }
+
+    #[test]
+    fn particles_over_tiles_out_of_view_fade_out() {
+        let run = |fade_outside_fov: bool| {
+            let map = GameMap::new(20, 12);
+            let tile_center = |x: u32, y: u32| Vec3::new(
+                (x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE,
+                (y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE,
+                0.5,
+            );
+            let spots = [tile_center(10, 6), tile_center(14, 6), Vec3::new(-20.0 * TILE_SIZE, 0.0, 0.5)];
+
+            let mut world = World::new();
+            world.init_resource::<Time>();
+            world.init_resource::<ParticleSpawner>();
+            world.init_resource::<WindState>();
+            world.init_resource::<AccessibilitySettings>();
+            world.insert_resource(GlobalRng::with_seed(2));
+            world.insert_resource(ParticleSettings { fade_outside_fov, ..default() });
+            world.insert_resource(VisibleTiles([(9, 6), (10, 6), (11, 6)].into_iter().collect()));
+            world.spawn((Player { x: 10, y: 6 }, Transform::from_translation(tile_center(10, 6))));
+            world.insert_resource(map);
+            let particles: Vec<Entity> = spots.iter().map(|&at| world.spawn((
+                BiomeParticle {
+                    lifetime: Timer::from_seconds(10.0, TimerMode::Once),
+                    velocity: Vec2::ZERO,
+                    glow_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    layer_speed: 1.0,
+                    original_alpha: 0.8,
+                    packed_data: BiomeParticle::pack(0.0, 0.0, 0.0, ParticleType::Primary),
+                },
+                Sprite::default(),
+                Transform::from_translation(at),
+            )).id()).collect();
+
+            world.run_system_cached(update_biome_particles).unwrap();
+            particles.iter().map(|&p| world.get::<Sprite>(p).unwrap().color.alpha()).collect::<Vec<f32>>()
+        };
+
+        let [seen, unseen, off_map] = run(true)[..] else { unreachable!() };
+        assert!(seen > 0.3, "a particle in view keeps its alpha, got {}", seen);
+        assert!(unseen < 0.01, "a particle over an unseen tile fades out, got {}", unseen);
+        assert!(off_map > 0.1, "off-map particles fall back to the distance fade, got {}", off_map);
+
+        let [_, unseen, _] = run(false)[..] else { unreachable!() };
+        assert!(unseen > 0.3, "the FOV fade can be switched off");
+    }
+
+    #[test]
+    fn the_entry_fill_spawns_its_fraction_of_each_layer_max() {
+        let biome = BiomeType::Caverns;
+        let config = BiomeParticleConfig::for_biome(biome);
+        assert!(config.primary_max_particles > 0 && config.secondary_max_particles > 0);
+        for fill in [0.0, 0.25, 0.67, 1.0] {
+            let mut map = GameMap::new(40, 30);
+            map.fill(TileType::Floor);
+
+            let mut world = World::new();
+            world.init_resource::<Time>();
+            world.init_resource::<ParticleSpawner>();
+            world.init_resource::<WindState>();
+            world.init_resource::<PerfMetrics>();
+            world.init_resource::<VisibleTiles>();
+            world.insert_resource(GlobalRng::with_seed(4));
+            world.insert_resource(CurrentLevel { level: 1, biome });
+            // A budget well above both layers' max, so only the fill fraction limits the entry spawn
+            world.insert_resource(ParticleSettings { max_total_particles: 10_000, initial_fill: fill, ..default() });
+            world.insert_resource(map);
+            world.spawn((Player { x: 20, y: 15 }, Transform::default()));
+
+            // No time passes, so neither spawn timer fires and only the entry fill runs
+            world.run_system_cached(update_particle_spawner).unwrap();
+            world.run_system_cached(spawn_biome_particles).unwrap();
+            world.flush();
+
+            let count = |world: &mut World, kind: ParticleType| {
+                world.query::<&BiomeParticle>().iter(world).filter(|p| p.particle_type() == kind).count()
+            };
+            assert_eq!(count(&mut world, ParticleType::Primary), (config.primary_max_particles as f32 * fill) as usize, "fill {}", fill);
+            assert_eq!(count(&mut world, ParticleType::Secondary), (config.secondary_max_particles as f32 * fill) as usize, "fill {}", fill);
+        }
+    }
+}
@@ -1,9 +1,15 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
+use std::collections::VecDeque;
+use rand::Rng;
 
-use crate::assets::GameAssets;
+use crate::assets::{GameAssets, SpriteDatabase};
+use crate::biome::{BiomeType, can_enter, move_cost};
 use crate::components::*;
-use crate::map::GameMap;
+use crate::map::{GameMap, get_tile_texture_index, tile_variant_rng};
+use crate::constants::TILE_SIZE;
+use crate::input_handler::{KeyBindings, LevelChangeEvent, PlayerLeftStairs, PlayerOnStairs, StairArrivalEvent, remove_auto_actions};
+use crate::level_manager::capture_tile_visibility;
 
 // ============================================================================
 // PLAYER SPAWNING
@@ -14,41 +20,16 @@ pub fn spawn_player(
     assets: Res<GameAssets>,
     map: Res<GameMap>,
     sprite_config: Res<PlayerSpriteConfig>,
+    start_policy: Res<StartPolicy>,
 ) {
-    // Find a suitable spawn position (preferably near center)
-    let center_x = map.width / 2;
-    let center_y = map.height / 2;
-    
-    // Look for a floor tile near the center
-    let mut spawn_pos = (center_x, center_y);
-    
-    // If center is not a floor, search nearby
-    if map.get(center_x, center_y) != TileType::Floor {
-        'search: for radius in 1..10 {
-            for dx in -(radius as i32)..=(radius as i32) {
-                for dy in -(radius as i32)..=(radius as i32) {
-                    let x = center_x as i32 + dx;
-                    let y = center_y as i32 + dy;
-
-                    if x >= 0 && x < map.width as i32 && y >= 0 && y < map.height as i32 {
-                        let ux = x as u32;
-                        let uy = y as u32;
-                        if map.get(ux, uy) == TileType::Floor {
-                            spawn_pos = (ux, uy);
-                            break 'search;
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
+    let spawn_pos = map.start_position(*start_policy);
     let grid_x = spawn_pos.0;
     let grid_y = spawn_pos.1;
     
     // Convert grid position to world position with new map centering
-    let world_x = (grid_x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-    let world_y = (grid_y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
+    let offset = sprite_config.world_offset();
+    let world_x = (grid_x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE + offset.x;
+    let world_y = (grid_y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE + offset.y;
 
     let player_entity = commands.spawn((
         Player { x: grid_x, y: grid_y },
@@ -56,11 +37,19 @@ pub fn spawn_player(
             move_timer: Timer::from_seconds(0.15, TimerMode::Once), // 150ms for hold-to-move
             is_holding: false,
         },
+        Inventory::default(),
+        Facing::default(),
+        SpriteAnimation::new(
+            sprite_config.idle_frames.clone(),
+            sprite_config.frame_duration,
+            AnimationMode::Idle,
+        ),
         Sprite {
             image: assets.rogues.clone(),
             rect: Some(sprite_config.sprite_rect),
             flip_x: false, // Start facing left (natural sprite direction)
             custom_size: Some(sprite_config.custom_size),
+            anchor: sprite_config.anchor,
             ..default()
         },
         Transform::from_xyz(world_x, world_y, 1.0),
@@ -76,17 +65,20 @@ pub fn spawn_player(
 
 pub fn move_player(
     mut player_query: Query<(&Player, &mut Transform), (Changed<Player>, Without<MovementAnimation>)>,
-    map: Res<GameMap>,
+    map: Option<Res<GameMap>>,
+    sprite_config: Res<PlayerSpriteConfig>,
 ) {
+    let Some(map) = map else { return; };
     // Only update transform for players without active movement animation
     // This system should only run when there's no animation active
     for (player, mut transform) in player_query.iter_mut() {
         // Convert grid position to world position
-        let world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-        let world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
+        let world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        let world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        let offset = sprite_config.world_offset();
         
-        transform.translation.x = world_x;
-        transform.translation.y = world_y;
+        transform.translation.x = world_x + offset.x;
+        transform.translation.y = world_y + offset.y;
         transform.translation.z = 1.0; // Ensure consistent Z position
     }
 }
@@ -117,79 +109,345 @@ pub fn animate_movement(
     }
 }
 
+// Tell the UI when the player comes to rest on stairs or leaves them; a level change counts as leaving
+pub fn track_stair_presence(
+    player_query: Query<&Player, Without<MovementAnimation>>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    mut on_stairs_events: EventWriter<PlayerOnStairs>,
+    mut left_stairs_events: EventWriter<PlayerLeftStairs>,
+    mut standing_on: Local<Option<(u32, (u32, u32), TileType)>>,
+) {
+    let Some(map) = map else { return; };
+    // Mid-hop the move hasn't ended yet
+    let Ok(player) = player_query.single() else { return; };
+
+    let pos = (player.x, player.y);
+    let tile_type = map.get(player.x, player.y);
+    let now = matches!(tile_type, TileType::StairUp | TileType::StairDown)
+        .then_some((current_level.level, pos, tile_type));
+    if now == *standing_on {
+        return;
+    }
+
+    if let Some((_, _, stair_type)) = standing_on.take() {
+        left_stairs_events.write(PlayerLeftStairs { stair_type });
+    }
+    if let Some((_, _, stair_type)) = now {
+        on_stairs_events.write(PlayerOnStairs { stair_type });
+    }
+    *standing_on = now;
+}
+
+// Walking onto an item tile moves it into the inventory and leaves plain floor behind
+pub fn pickup_items(
+    mut player_query: Query<(&Player, &mut Inventory), Changed<Player>>,
+    mut tile_query: Query<(&mut MapTile, &mut TileTextureIndex)>,
+    map: Option<ResMut<GameMap>>,
+    tile_index: Res<TileIndex>,
+    sprite_db: Res<SpriteDatabase>,
+    current_level: Res<CurrentLevel>,
+    level_seeds: Res<LevelSeeds>,
+) {
+    let Some(mut map) = map else { return; };
+    let Ok((player, mut inventory)) = player_query.single_mut() else { return; };
+
+    let TileType::Item(item) = map.get(player.x, player.y) else { return; };
+
+    inventory.items.push(item);
+    map.set(player.x, player.y, TileType::Floor);
+    println!("You pick up a {:?} ({} items carried)", item, inventory.items.len());
+
+    // Swap the tile entity over to a floor sprite for this biome
+    retile(&mut tile_query, &tile_index, &sprite_db, &map, &current_level, &level_seeds, (player.x, player.y));
+}
+
+// Point the tile entity at `pos` to the sprite for its current map tile, with the same
+// position-seeded variant a fresh spawn of the level would pick
+pub fn retile(
+    tile_query: &mut Query<(&mut MapTile, &mut TileTextureIndex)>,
+    tile_index: &TileIndex,
+    sprite_db: &SpriteDatabase,
+    map: &GameMap,
+    current_level: &CurrentLevel,
+    level_seeds: &LevelSeeds,
+    pos: (u32, u32),
+) {
+    let Some(&entity) = tile_index.tiles.get(&pos) else { return; };
+    if let Ok((mut map_tile, mut texture_index)) = tile_query.get_mut(entity) {
+        let tile_type = map.get(pos.0, pos.1);
+        let level_seed = level_seeds.seeds.get(&current_level.level).copied().unwrap_or_default();
+        let mut rng = tile_variant_rng(level_seed, pos.0, pos.1);
+        map_tile.tile_type = tile_type;
+        texture_index.0 = get_tile_texture_index(current_level.biome.get_config(), sprite_db, tile_type, map, pos.0, pos.1, &mut rng);
+    }
+}
+
+// Stepping onto a trapdoor drops the player straight to the next level, landing near its center
+// rather than on stairs, so nothing links the landing spot back to the trapdoor
+pub fn fall_through_trapdoors(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Player), Changed<Player>>,
+    tile_visibility_query: Query<(&TilePos, &TileVisibilityState)>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    mut level_maps: ResMut<LevelMaps>,
+    mut level_change_events: EventWriter<LevelChangeEvent>,
+    dungeon: Res<DungeonConfig>,
+) {
+    let Some(map) = map else { return; };
+    let Ok((entity, player)) = player_query.single() else { return; };
+    if map.get(player.x, player.y) != TileType::Trapdoor || current_level.level >= dungeon.max_depth {
+        return;
+    }
+
+    println!("The floor gives way! You fall to level {}", current_level.level + 1);
+    remove_auto_actions(&mut commands.entity(entity));
+    let current_visibility = capture_tile_visibility(&tile_visibility_query, map.width, map.height);
+    level_maps.maps.insert(current_level.level, map.to_saved_data(current_level.biome, current_visibility));
+    level_change_events.write(LevelChangeEvent {
+        new_level: current_level.level + 1,
+        spawn_position: StartPolicy::Center,
+    });
+}
+
+// Hidden traps within this many tiles (Chebyshev) may be noticed on each step
+const TRAP_DETECT_RADIUS: u32 = 2;
+// Chance per step of noticing each hidden trap in range
+const TRAP_DETECT_CHANCE: f64 = 0.3;
+
+// Stepping on a trap springs it (revealing it if hidden); otherwise nearby hidden traps may be spotted
+pub fn update_traps(
+    player_query: Query<&Player, Changed<Player>>,
+    mut tile_query: Query<(&mut MapTile, &mut TileTextureIndex)>,
+    map: Option<ResMut<GameMap>>,
+    tile_index: Res<TileIndex>,
+    sprite_db: Res<SpriteDatabase>,
+    current_level: Res<CurrentLevel>,
+    level_seeds: Res<LevelSeeds>,
+    mut rng: ResMut<TrapRng>,
+) {
+    let Some(mut map) = map else { return; };
+    let Ok(player) = player_query.single() else { return; };
+
+    let mut revealed = Vec::new();
+    match map.get(player.x, player.y) {
+        TileType::TrapHidden => {
+            println!("You trigger a hidden trap at ({}, {})!", player.x, player.y);
+            revealed.push((player.x, player.y));
+        }
+        TileType::TrapRevealed => println!("You pick your way across the trap"),
+        _ => {}
+    }
+
+    let (min_x, min_y) = (player.x.saturating_sub(TRAP_DETECT_RADIUS), player.y.saturating_sub(TRAP_DETECT_RADIUS));
+    let max_x = (player.x + TRAP_DETECT_RADIUS).min(map.width - 1);
+    let max_y = (player.y + TRAP_DETECT_RADIUS).min(map.height - 1);
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if (x, y) != (player.x, player.y) && map.get(x, y) == TileType::TrapHidden && rng.random_bool(TRAP_DETECT_CHANCE) {
+                println!("You spot a trap at ({}, {})", x, y);
+                revealed.push((x, y));
+            }
+        }
+    }
+
+    for pos in revealed {
+        map.set(pos.0, pos.1, TileType::TrapRevealed);
+        retile(&mut tile_query, &tile_index, &sprite_db, &map, &current_level, &level_seeds, pos);
+    }
+}
+
+// Record the step direction, flipping the sprite only for horizontal steps
+pub fn face_step(facing: &mut Facing, sprite: &mut Sprite, from: (u32, u32), to: (u32, u32)) {
+    if let Some(new_facing) = Facing::from_step(from, to) {
+        *facing = new_facing;
+        if let Some(flip) = new_facing.flip_x() {
+            sprite.flip_x = flip;
+        }
+    }
+}
+
+// Advance the player's sprite frames: walk cycle while hopping, idle bob otherwise
+pub fn animate_player_sprite(
+    time: Res<Time>,
+    sprite_config: Res<PlayerSpriteConfig>,
+    mut player_query: Query<(&mut Sprite, &mut SpriteAnimation, Has<MovementAnimation>), With<Player>>,
+) {
+    for (mut sprite, mut animation, moving) in player_query.iter_mut() {
+        let mode = if moving { AnimationMode::Walk } else { AnimationMode::Idle };
+
+        // Restart from the first frame whenever the mode flips
+        if animation.mode != mode {
+            animation.mode = mode;
+            animation.frames = sprite_config.frames_for(mode).to_vec();
+            animation.frame = 0;
+            animation.timer.reset();
+            if let Some(&first) = animation.frames.first() {
+                sprite.rect = Some(first);
+            }
+            continue;
+        }
+
+        if animation.frames.len() < 2 {
+            continue;
+        }
+
+        animation.timer.tick(time.delta());
+        if animation.timer.just_finished() {
+            animation.frame = (animation.frame + 1) % animation.frames.len();
+            sprite.rect = Some(animation.frames[animation.frame]);
+        }
+    }
+}
+
 // ============================================================================
 // AUTOEXPLORE SYSTEMS
 // ============================================================================
 
-pub fn run_autoexplore(
+/// Pick the next unexplored target whenever the autoexplore path runs dry
+pub fn plan_autoexplore(
     mut commands: Commands,
-    time: Res<Time>,
-    mut player_query: Query<(Entity, &mut Player, &mut Autoexplore, &mut Sprite), Without<MovementAnimation>>,
+    mut player_query: Query<(Entity, &Player, &mut Autoexplore), Without<MovementAnimation>>,
     tile_visibility_query: Query<(&TilePos, &TileVisibilityState)>,
-    map: Res<GameMap>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    settings: Res<AutoexploreSettings>,
+    trail: Res<VisitTrail>,
 ) {
-    if let Ok((entity, mut player, mut autoexplore, mut sprite)) = player_query.single_mut() {
-        if !autoexplore.active {
-            // Try to activate if component exists
-            let unexplored = find_nearest_unexplored(&player, &tile_visibility_query, &map);
-            if let Some(target) = unexplored {
-                autoexplore.target = Some(target);
-                autoexplore.path = find_path((player.x, player.y), target, &map);
-                autoexplore.active = true;
-            } else {
-                // No more unexplored tiles - remove component
-                commands.entity(entity).remove::<Autoexplore>();
-                println!("Autoexplore complete - map fully explored!");
-                return;
-            }
+    let Some(map) = map else { return; };
+    if let Ok((entity, player, mut autoexplore)) = player_query.single_mut() {
+        if !autoexplore.path.is_empty() {
+            return;
         }
 
+        if let Some(target) = find_nearest_unexplored(player, &tile_visibility_query, &map, current_level.biome, settings.strategy, &trail) {
+            autoexplore.target = Some(target);
+            autoexplore.path = find_path((player.x, player.y), target, &map, current_level.biome);
+        } else {
+            // No more unexplored tiles - remove component
+            commands.entity(entity).remove::<Autoexplore>();
+            println!("Autoexplore complete - map fully explored!");
+        }
+    }
+}
+
+/// Stamp each tile the player lands on into the visit trail
+pub fn record_visit_trail(
+    player_query: Query<&Player, Changed<Player>>,
+    mut trail: ResMut<VisitTrail>,
+) {
+    if let Ok(player) = player_query.single() {
+        trail.visit((player.x, player.y));
+    }
+}
+
+/// Step the player along the path of any auto-movement component
+pub fn run_auto_move<T: AutoMove>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Player, &mut T, &mut Sprite, &mut Facing), Without<MovementAnimation>>,
+    settings: Res<AutoexploreSettings>,
+    key_bindings: Res<KeyBindings>,
+    mut stair_arrivals: EventWriter<StairArrivalEvent>,
+    map: Option<Res<GameMap>>,
+    current_level: Res<CurrentLevel>,
+    accessibility: Res<AccessibilitySettings>,
+    sprite_config: Res<PlayerSpriteConfig>,
+) {
+    let Some(map) = map else { return; };
+    if let Ok((entity, mut player, mut auto_move, mut sprite, mut facing)) = player_query.single_mut() {
         // Tick timer
-        autoexplore.move_timer.tick(time.delta());
+        let move_timer = auto_move.move_timer_mut();
+        move_timer.tick(time.delta());
 
-        if !autoexplore.move_timer.just_finished() {
+        if !move_timer.just_finished() {
             return;
         }
 
+        // Speed and instant mode only apply to autoexplore, not stair travel
+        let exploring = auto_move.on_arrival() == ArrivalAction::Retarget;
+
+        if exploring && settings.instant {
+            // Jump straight to the target; move_player snaps the transform and FOV
+            // recalculates at this stop before plan_autoexplore picks the next target
+            let path = auto_move.path_mut();
+            if let Some(&destination) = path.back() {
+                if path.iter().all(|&(x, y)| can_enter(map.get(x, y), current_level.biome)) {
+                    face_step(&mut facing, &mut sprite, (player.x, player.y), destination);
+                    player.x = destination.0;
+                    player.y = destination.1;
+                }
+                path.clear();
+                return;
+            }
+        }
+
         // Get next step in path
-        if let Some(next_pos) = autoexplore.path.first().copied() {
-            // Check if we can move to next position
-            if map.get(next_pos.0, next_pos.1) != TileType::Wall {
-                // Calculate animation positions
-                let start_world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let start_world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_x = (next_pos.0 as f32 - (map.width as f32 / 2.0 - 0.5)) * 32.0;
-                let end_world_y = (next_pos.1 as f32 - (map.height as f32 / 2.0 - 0.5)) * 32.0;
-
-                // Update sprite facing
-                if next_pos.0 < player.x {
-                    sprite.flip_x = false; // Moving left
-                } else if next_pos.0 > player.x {
-                    sprite.flip_x = true; // Moving right
+        let Some(next_pos) = auto_move.path_mut().pop_front() else {
+            // Path exhausted - the component decides what arriving means
+            let arrival = auto_move.on_arrival();
+            match arrival {
+                ArrivalAction::Retarget => {} // plan_autoexplore picks the next target
+                ArrivalAction::AnnounceStair(stair_type) | ArrivalAction::TakeStair(stair_type) => {
+                    // The altar needs no announcement - stepping on it ends the run
+                    if map.get(player.x, player.y) == stair_type && stair_type != TileType::Altar {
+                        let direction = if stair_type == TileType::StairUp { "up" } else { "down" };
+                        if arrival == ArrivalAction::TakeStair(stair_type) {
+                            println!("Reached {} stairwell, taking it", direction);
+                            stair_arrivals.write(StairArrivalEvent { stair_type });
+                        } else {
+                            println!("Reached {} stairwell! Press {} to use it.",
+                                direction, key_bindings.stair_key_label(stair_type)
+                            );
+                        }
+                    }
+                    commands.entity(entity).remove::<T>();
+                }
+            }
+            return;
+        };
+
+        // Check if we can move to next position
+        let Some(step_cost) = move_cost(map.get(next_pos.0, next_pos.1), current_level.biome) else {
+            match auto_move.on_arrival() {
+                ArrivalAction::Retarget => {
+                    // Path blocked, drop it so the planner recalculates
+                    auto_move.path_mut().clear();
                 }
+                ArrivalAction::AnnounceStair(_) | ArrivalAction::TakeStair(_) => {
+                    println!("Path to stairwell blocked!");
+                    commands.entity(entity).remove::<T>();
+                }
+            }
+            return;
+        };
 
-                // Move player
-                player.x = next_pos.0;
-                player.y = next_pos.1;
+        // Calculate animation positions
+        let start_world_x = (player.x as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        let start_world_y = (player.y as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        let end_world_x = (next_pos.0 as f32 - (map.width as f32 / 2.0 - 0.5)) * TILE_SIZE;
+        let end_world_y = (next_pos.1 as f32 - (map.height as f32 / 2.0 - 0.5)) * TILE_SIZE;
 
-                // Add fast animation for autoexplore
-                commands.entity(entity).insert(MovementAnimation {
-                    start_pos: Vec3::new(start_world_x, start_world_y, 1.0),
-                    end_pos: Vec3::new(end_world_x, end_world_y, 1.0),
-                    timer: Timer::from_seconds(0.05, TimerMode::Once), // 50ms animation - fast but visible
-                });
+        // Update sprite facing
+        face_step(&mut facing, &mut sprite, (player.x, player.y), next_pos);
 
-                // Remove this step from path
-                autoexplore.path.remove(0);
-            } else {
-                // Path blocked, recalculate
-                autoexplore.path.clear();
-                autoexplore.active = false;
-            }
-        } else {
-            // Reached target or path empty, find new target
-            autoexplore.active = false;
+        // Move player
+        player.x = next_pos.0;
+        player.y = next_pos.1;
+
+        if accessibility.reduce_motion {
+            return; // move_player snaps to the new tile
         }
+
+        // Add fast animation for auto-movement; costly tiles (swimming) take proportionally longer
+        let speed = if exploring { settings.speed_multiplier } else { 1.0 };
+        let offset = sprite_config.world_offset().extend(0.0);
+        commands.entity(entity).insert(MovementAnimation {
+            start_pos: Vec3::new(start_world_x, start_world_y, 1.0) + offset,
+            end_pos: Vec3::new(end_world_x, end_world_y, 1.0) + offset,
+            timer: Timer::from_seconds(0.05 * step_cost as f32 / speed, TimerMode::Once), // 50ms animation at 1x - fast but visible
+        });
     }
 }
 
@@ -197,55 +455,150 @@ pub fn run_autoexplore(
 // AUTOEXPLORE HELPER FUNCTIONS (Public for input_handler)
 // ============================================================================
 
-/// Find nearest unexplored tile using breadth-first search
+/// Find the next explore target for the player using the given strategy
 pub fn find_nearest_unexplored(
     player: &Player,
     tile_visibility_query: &Query<(&TilePos, &TileVisibilityState)>,
     map: &GameMap,
+    biome: BiomeType,
+    strategy: ExploreStrategy,
+    trail: &VisitTrail,
 ) -> Option<(u32, u32)> {
-    use std::collections::VecDeque;
+    // Snapshot visibility into a grid once; tiles without an entity count as unseen
+    let mut unseen = vec![true; map.tiles.len()];
+    for (tile_pos, visibility_state) in tile_visibility_query.iter() {
+        if tile_pos.x < map.width && tile_pos.y < map.height {
+            unseen[(tile_pos.y * map.width + tile_pos.x) as usize] = visibility_state.visibility == TileVisibility::Unseen;
+        }
+    }
+    let is_unseen = |x: u32, y: u32| unseen[(y * map.width + x) as usize];
+    let recency = |x: u32, y: u32| trail.recency((x, y));
 
-    let start = (player.x, player.y);
+    match strategy {
+        ExploreStrategy::NearestUnseen => find_nearest_unexplored_from((player.x, player.y), map, biome, is_unseen, recency),
+        ExploreStrategy::NearestFrontier => find_nearest_frontier_from((player.x, player.y), map, biome, is_unseen, recency),
+    }
+}
+
+/// Breadth-first search for the nearest unexplored floor tile, independent of ECS queries.
+/// Among equally close tiles the one reached through the least recently walked first step wins
+pub fn find_nearest_unexplored_from(
+    start: (u32, u32),
+    map: &GameMap,
+    biome: BiomeType,
+    is_unseen: impl Fn(u32, u32) -> bool,
+    recency: impl Fn(u32, u32) -> u32,
+) -> Option<(u32, u32)> {
     let mut visited = vec![vec![false; map.height as usize]; map.width as usize];
     let mut queue = VecDeque::new();
-    queue.push_back(start);
+    // (position, depth, first step out of start)
+    queue.push_back((start, 0u32, start));
     visited[start.0 as usize][start.1 as usize] = true;
 
-    while let Some((x, y)) = queue.pop_front() {
-        // Check if this tile is unexplored (Unseen)
-        let mut is_unseen = true;
-        for (tile_pos, visibility_state) in tile_visibility_query.iter() {
-            if tile_pos.x == x && tile_pos.y == y {
-                is_unseen = visibility_state.visibility == TileVisibility::Unseen;
-                break;
+    let mut best: Option<(u32, (u32, u32), u32)> = None; // (recency, target, depth)
+    while let Some(((x, y), depth, first_step)) = queue.pop_front() {
+        if let Some((_, _, best_depth)) = best {
+            if depth > best_depth {
+                break; // Every tie at the winning depth has been considered
             }
         }
 
-        if is_unseen && map.get(x, y) == TileType::Floor {
-            return Some((x, y));
+        // Check if this tile is unexplored (Unseen)
+        if is_unseen(x, y) && map.get(x, y) == TileType::Floor {
+            let score = recency(first_step.0, first_step.1);
+            if best.is_none_or(|(best_score, _, _)| score < best_score) {
+                best = Some((score, (x, y), depth));
+            }
+            continue;
+        }
+        if best.is_some() {
+            continue; // Nothing deeper can win, so stop expanding
         }
 
         // Explore neighbors
-        let neighbors = [
-            (x.wrapping_sub(1), y), (x + 1, y),
-            (x, y.wrapping_sub(1)), (x, y + 1),
-        ];
-
-        for (nx, ny) in neighbors {
-            if nx < map.width && ny < map.height && !visited[nx as usize][ny as usize] {
-                if map.get(nx, ny) != TileType::Wall {
-                    visited[nx as usize][ny as usize] = true;
-                    queue.push_back((nx, ny));
+        for ((nx, ny), _) in map.passable_neighbors((x, y), biome) {
+            if !visited[nx as usize][ny as usize] {
+                visited[nx as usize][ny as usize] = true;
+                let first = if (x, y) == start { (nx, ny) } else { first_step };
+                queue.push_back(((nx, ny), depth + 1, first));
+            }
+        }
+    }
+
+    best.map(|(_, target, _)| target)
+}
+
+/// Dijkstra over seen tiles for the cheapest-to-reach frontier: a seen walkable tile
+/// with an unseen walkable cardinal neighbor (unseen walls never resolve into anything to explore). The start tile itself is never returned. Ties on cost go
+/// to the frontier reached through the least recently walked first step.
+pub fn find_nearest_frontier_from(
+    start: (u32, u32),
+    map: &GameMap,
+    biome: BiomeType,
+    is_unseen: impl Fn(u32, u32) -> bool,
+    recency: impl Fn(u32, u32) -> u32,
+) -> Option<(u32, u32)> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let idx = |(x, y): (u32, u32)| (y * map.width + x) as usize;
+    let mut distance = vec![u32::MAX; map.tiles.len()];
+    let mut first_step = vec![start; map.tiles.len()];
+    let mut open_set = BinaryHeap::new();
+    distance[idx(start)] = 0;
+    open_set.push(Reverse((0u32, start)));
+
+    let mut best: Option<(u32, (u32, u32), u32)> = None; // (recency, frontier, cost)
+    while let Some(Reverse((cost, position))) = open_set.pop() {
+        if cost > distance[idx(position)] {
+            continue; // Stale heap entry
+        }
+        if let Some((_, _, best_cost)) = best {
+            if cost > best_cost {
+                break; // Every tie at the winning cost has been considered
+            }
+        }
+
+        if position != start {
+            let (x, y) = position;
+            let borders_unseen = [
+                (x.wrapping_sub(1), y), (x + 1, y),
+                (x, y.wrapping_sub(1)), (x, y + 1),
+            ]
+            .into_iter()
+            .any(|(nx, ny)| nx < map.width && ny < map.height && is_unseen(nx, ny) && map.get(nx, ny).is_walkable());
+            if borders_unseen {
+                let (fx, fy) = first_step[idx(position)];
+                let score = recency(fx, fy);
+                if best.is_none_or(|(best_score, _, _)| score < best_score) {
+                    best = Some((score, position, cost));
                 }
+                continue;
+            }
+        }
+        if best.is_some() {
+            continue; // Step costs are positive, so nothing expanded from here can tie
+        }
+
+        // Only route through known territory
+        for (neighbor, step_cost) in map.passable_neighbors(position, biome) {
+            if is_unseen(neighbor.0, neighbor.1) {
+                continue;
+            }
+            let next = cost + step_cost;
+            if next < distance[idx(neighbor)] {
+                distance[idx(neighbor)] = next;
+                first_step[idx(neighbor)] = if position == start { neighbor } else { first_step[idx(position)] };
+                open_set.push(Reverse((next, neighbor)));
             }
         }
     }
 
-    None
+    best.map(|(_, frontier, _)| frontier)
 }
 
-/// Simple A* pathfinding
-pub fn find_path(start: (u32, u32), goal: (u32, u32), map: &GameMap) -> Vec<(u32, u32)> {
+/// A* pathfinding weighted by each biome's tile entry costs
+pub fn find_path(start: (u32, u32), goal: (u32, u32), map: &GameMap, biome: BiomeType) -> VecDeque<(u32, u32)> {
     use std::collections::{BinaryHeap, HashMap};
     use std::cmp::Ordering;
 
@@ -283,33 +636,18 @@ pub fn find_path(start: (u32, u32), goal: (u32, u32), map: &GameMap) -> Vec<(u32
 
     while let Some(State { position, .. }) = open_set.pop() {
         if position == goal {
-            // Reconstruct path
-            let mut path = Vec::new();
+            // Reconstruct path front-to-back so callers can pop_front each step
+            let mut path = VecDeque::new();
             let mut current = goal;
             while current != start {
-                path.push(current);
+                path.push_front(current);
                 current = *came_from.get(&current).unwrap();
             }
-            path.reverse();
             return path;
         }
 
-        let neighbors = [
-            (position.0.wrapping_sub(1), position.1),
-            (position.0 + 1, position.1),
-            (position.0, position.1.wrapping_sub(1)),
-            (position.0, position.1 + 1),
-        ];
-
-        for neighbor in neighbors {
-            if neighbor.0 >= map.width || neighbor.1 >= map.height {
-                continue;
-            }
-            if map.get(neighbor.0, neighbor.1) == TileType::Wall {
-                continue;
-            }
-
-            let tentative_g_score = g_score.get(&position).unwrap_or(&u32::MAX) + 1;
+        for (neighbor, step_cost) in map.passable_neighbors(position, biome) {
+            let tentative_g_score = g_score.get(&position).unwrap_or(&u32::MAX) + step_cost;
             if tentative_g_score < *g_score.get(&neighbor).unwrap_or(&u32::MAX) {
                 came_from.insert(neighbor, position);
                 g_score.insert(neighbor, tentative_g_score);
@@ -321,30 +659,277 @@ pub fn find_path(start: (u32, u32), goal: (u32, u32), map: &GameMap) -> Vec<(u32
         }
     }
 
-    Vec::new() // No path found
+    VecDeque::new() // No path found
 }
 
-/// Count unexplored tiles on the map
-pub fn count_unexplored_tiles(
-    tile_visibility_query: &Query<(&TilePos, &TileVisibilityState)>,
-    map: &GameMap,
-) -> usize {
-    let mut count = 0;
-    for x in 0..map.width {
-        for y in 0..map.height {
-            if map.get(x, y) == TileType::Floor {
-                let mut is_unseen = true;
-                for (tile_pos, visibility_state) in tile_visibility_query.iter() {
-                    if tile_pos.x == x && tile_pos.y == y {
-                        is_unseen = visibility_state.visibility == TileVisibility::Unseen;
-                        break;
-                    }
-                }
-                if is_unseen {
-                    count += 1;
-                }
+/// Count floor tiles the player hasn't seen yet
+pub fn count_unexplored_tiles(explored: &ExploredTiles, map: &GameMap) -> usize {
+    map.iter_floor().filter(|&(x, y)| !explored.contains(x, y)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_handler::{handle_movement_input, CancelAutoActions, MoveBuffer, MoveDirection, PlayerMoveIntent};
+    use bevy::sprite::Anchor;
+    use crate::input_handler::{PlayerLeftStairs, PlayerOnStairs};
+
+    // One run_auto_move::<AutoMoveToStair> pass with the player already standing on the stairs
+    fn arrive_on_stairs(take_on_arrival: bool) -> (usize, bool) {
+        let mut map = GameMap::new(3, 3);
+        map.set(1, 1, TileType::StairDown);
+
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(std::time::Duration::from_millis(5)); // Past the move timer
+        world.insert_resource(time);
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<AutoexploreSettings>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<AccessibilitySettings>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<Events<StairArrivalEvent>>();
+        let stair_move = AutoMoveToStair::new((1, 1), VecDeque::new(), TileType::StairDown).with_take_on_arrival(take_on_arrival);
+        let player = world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default(), stair_move)).id();
+
+        let step = world.register_system(run_auto_move::<AutoMoveToStair>);
+        world.run_system(step).unwrap();
+        world.flush();
+
+        let arrivals = world.resource::<Events<StairArrivalEvent>>().len();
+        (arrivals, world.get::<AutoMoveToStair>(player).is_some())
+    }
+
+    #[test]
+    fn stair_travel_takes_the_stairs_only_when_asked() {
+        assert_eq!(arrive_on_stairs(false), (0, false), "announced, then stopped");
+        assert_eq!(arrive_on_stairs(true), (1, false), "taken, then stopped");
+    }
+
+    #[test]
+    fn movement_systems_ride_out_frames_without_a_map() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(std::time::Duration::from_millis(5));
+        world.insert_resource(time);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<AutoexploreSettings>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<AccessibilitySettings>();
+        world.init_resource::<VisitTrail>();
+        world.init_resource::<TileIndex>();
+        world.init_resource::<MoveBuffer>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<Events<StairArrivalEvent>>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        world.spawn((Player { x: 2, y: 2 }, Transform::default(), Sprite::default(), Facing::default(), Autoexplore::default()));
+
+        let frame = world.register_system(|world: &mut World| {
+            world.run_system_cached(handle_movement_input).unwrap();
+            world.run_system_cached(plan_autoexplore).unwrap();
+            world.run_system_cached(run_auto_move::<Autoexplore>).unwrap();
+            world.run_system_cached(run_auto_move::<AutoMoveToStair>).unwrap();
+            world.run_system_cached(move_player).unwrap();
+        });
+
+        // Level transitions swap the map out for a frame at a time; every other frame has none
+        for level in 0..20u32 {
+            world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+            if level % 2 == 0 {
+                world.remove_resource::<GameMap>();
+            } else {
+                let mut map = GameMap::new(8, 5);
+                map.fill_rect(URect::new(1, 1, 7, 4), TileType::Floor);
+                world.insert_resource(map);
+                world.query::<&mut Player>().single_mut(&mut world).unwrap().x = 2;
             }
+            world.run_system(frame).unwrap();
+            world.flush();
         }
     }
-    count
+
+    #[test]
+    fn walking_onto_an_item_picks_it_up_and_leaves_floor() {
+        let mut map = GameMap::new(5, 3);
+        map.fill_rect(URect::new(1, 1, 4, 2), TileType::Floor);
+        map.set(2, 1, TileType::Item(ItemKind::Potion));
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<LevelSeeds>();
+        world.insert_resource(SpriteDatabase::new());
+        let mut index = TileIndex::default();
+        let tile = world.spawn((MapTile { tile_type: TileType::Item(ItemKind::Potion) }, TileTextureIndex(0))).id();
+        index.insert(2, 1, tile);
+        world.insert_resource(index);
+        let player = world.spawn((Player { x: 1, y: 1 }, Inventory::default())).id();
+
+        let pickup = world.register_system(pickup_items);
+        world.run_system(pickup).unwrap();
+        assert!(world.get::<Inventory>(player).unwrap().items.is_empty(), "nothing underfoot yet");
+
+        world.get_mut::<Player>(player).unwrap().x = 2;
+        world.run_system(pickup).unwrap();
+        assert_eq!(world.get::<Inventory>(player).unwrap().items, vec![ItemKind::Potion]);
+        assert_eq!(world.resource::<GameMap>().get(2, 1), TileType::Floor);
+        assert_eq!(world.get::<MapTile>(tile).unwrap().tile_type, TileType::Floor, "the tile sprite is swapped too");
+
+        // Standing still doesn't pick up again
+        world.run_system(pickup).unwrap();
+        assert_eq!(world.get::<Inventory>(player).unwrap().items.len(), 1);
+    }
+
+    #[test]
+    fn odd_sized_sprites_line_up_with_their_tile() {
+        // Where the sprite's bounding box lands for a player standing on `tile`
+        fn drawn_rect(config: PlayerSpriteConfig, tile: (u32, u32)) -> Rect {
+            let mut world = World::new();
+            world.insert_resource(GameMap::new(7, 5));
+            let size = config.custom_size;
+            let anchor = config.anchor.as_vec();
+            world.insert_resource(config);
+            let player = world.spawn((Player { x: tile.0, y: tile.1 }, Transform::default())).id();
+            world.run_system_cached(move_player).unwrap();
+
+            let at = world.get::<Transform>(player).unwrap().translation.truncate();
+            let min = at - (anchor + Vec2::splat(0.5)) * size;
+            Rect::from_corners(min, min + size)
+        }
+
+        let tile = (4, 1);
+        let tile_center = Vec2::new((4.0 - 3.0) * TILE_SIZE, (1.0 - 2.0) * TILE_SIZE);
+        let tall = Vec2::new(24.0, 40.0);
+
+        let centered = drawn_rect(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 24.0, 40.0), tall), tile);
+        assert_eq!(centered.center(), tile_center);
+
+        let feet = drawn_rect(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 24.0, 40.0), tall).with_anchor(Anchor::BottomCenter), tile);
+        assert_eq!(feet.center().x, tile_center.x);
+        assert_eq!(feet.min.y, tile_center.y - TILE_SIZE / 2.0, "feet rest on the tile's bottom edge");
+        assert_eq!(feet.height(), 40.0);
+    }
+
+    #[test]
+    fn autoexplore_takes_the_branch_it_has_not_just_walked() {
+        // A corridor forking left and right from (10, 1); both ends are still unseen and equally far
+        let mut map = GameMap::new(21, 3);
+        map.fill_rect(URect::new(1, 1, 20, 2), TileType::Floor);
+        let is_unseen = |x: u32, _y: u32| x <= 3 || x >= 17;
+
+        for came_from_left in [true, false] {
+            let mut trail = VisitTrail::default();
+            let walked: Vec<u32> = if came_from_left { (5..=10).collect() } else { (10..=15).rev().collect() };
+            for x in walked {
+                trail.visit((x, 1));
+            }
+            let recency = |x, y| trail.recency((x, y));
+
+            let unexplored = find_nearest_unexplored_from((10, 1), &map, BiomeType::Caverns, is_unseen, recency).unwrap();
+            let frontier = find_nearest_frontier_from((10, 1), &map, BiomeType::Caverns, is_unseen, recency).unwrap();
+            if came_from_left {
+                assert_eq!((unexplored, frontier), ((17, 1), (16, 1)), "heads right, away from the walked branch");
+            } else {
+                assert_eq!((unexplored, frontier), ((3, 1), (4, 1)), "heads left, away from the walked branch");
+            }
+        }
+    }
+
+    #[test]
+    fn on_up_stair_start_puts_the_player_on_the_up_stair() {
+        let spawn_at = |map_level: u32, policy: StartPolicy| {
+            let mask = EllipseMask::new(80, 50);
+            let mut map = GameMap::new(80, 50);
+            map.generate_level_validated(BiomeType::Caverns, map_level, crate::constants::MAX_DEPTH, 11, StairMode::BothWays, &mask);
+            let (up, center) = (map.stair_up_pos, map.floor_nearest_center());
+
+            let mut world = World::new();
+            world.insert_resource(GameAssets { rogues: default(), tiles: default(), akkurat_font: default() });
+            world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+            world.insert_resource(policy);
+            world.insert_resource(map);
+            world.run_system_cached(spawn_player).unwrap();
+            let player = world.query::<&Player>().single(&world).unwrap();
+            ((player.x, player.y), up, center)
+        };
+
+        let (at, up, _) = spawn_at(3, StartPolicy::OnUpStair);
+        assert_eq!(Some(at), up);
+
+        // The top level has no up stair, so the policy falls back to the center
+        let (at, up, center) = spawn_at(0, StartPolicy::OnUpStair);
+        assert_eq!((at, up), (center, None));
+    }
+
+    #[test]
+    fn walking_onto_a_trapdoor_drops_to_the_next_level() {
+        let mut map = GameMap::new(5, 3);
+        map.fill_rect(URect::new(1, 1, 4, 2), TileType::Floor);
+        map.set(2, 1, TileType::Trapdoor);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 3, biome: BiomeType::CinderGaol });
+        world.init_resource::<AccessibilitySettings>();
+        world.insert_resource(PlayerSpriteConfig::single_frame(Rect::new(0.0, 0.0, 32.0, 32.0), Vec2::splat(32.0)));
+        world.init_resource::<TileIndex>();
+        world.init_resource::<MoveBuffer>();
+        world.init_resource::<LevelMaps>();
+        world.init_resource::<DungeonConfig>();
+        world.init_resource::<Events<PlayerMoveIntent>>();
+        world.init_resource::<Events<CancelAutoActions>>();
+        world.init_resource::<Events<LevelChangeEvent>>();
+        world.spawn((Player { x: 1, y: 1 }, Sprite::default(), Facing::default()));
+        let frame = |world: &mut World| {
+            world.run_system_cached(handle_movement_input).unwrap();
+            world.run_system_cached(fall_through_trapdoors).unwrap();
+            world.flush();
+            world.resource_mut::<Events<LevelChangeEvent>>().drain().map(|event| (event.new_level, event.spawn_position)).collect::<Vec<_>>()
+        };
+
+        assert!(frame(&mut world).is_empty(), "standing next to it is safe");
+        world.send_event(PlayerMoveIntent { direction: MoveDirection::Right });
+        assert_eq!(frame(&mut world), vec![(4, StartPolicy::Center)], "no key press needed, and no stairs to land on");
+        assert!(world.resource::<LevelMaps>().maps.contains_key(&3), "the level fallen from is saved");
+    }
+
+    #[test]
+    fn ending_a_move_on_stairs_and_stepping_off_send_their_events() {
+        let mut map = GameMap::new(6, 3);
+        map.fill_rect(URect::new(1, 1, 5, 2), TileType::Floor);
+        map.set(2, 1, TileType::StairDown);
+        map.set(3, 1, TileType::StairUp);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 1, biome: BiomeType::Caverns });
+        world.init_resource::<Events<PlayerOnStairs>>();
+        world.init_resource::<Events<PlayerLeftStairs>>();
+        let player = world.spawn(Player { x: 1, y: 1 }).id();
+        let move_to = |world: &mut World, x: u32| {
+            world.get_mut::<Player>(player).unwrap().x = x;
+            world.run_system_cached(track_stair_presence).unwrap();
+            let on: Vec<TileType> = world.resource_mut::<Events<PlayerOnStairs>>().drain().map(|e| e.stair_type).collect();
+            let left: Vec<TileType> = world.resource_mut::<Events<PlayerLeftStairs>>().drain().map(|e| e.stair_type).collect();
+            (on, left)
+        };
+
+        assert_eq!(move_to(&mut world, 1), (vec![], vec![]));
+
+        // Mid-hop onto the down stair nothing is sent; the event waits for the move to end
+        world.entity_mut(player).insert(MovementAnimation {
+            start_pos: Vec3::ZERO,
+            end_pos: Vec3::X,
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        });
+        assert_eq!(move_to(&mut world, 2), (vec![], vec![]));
+        world.entity_mut(player).remove::<MovementAnimation>();
+        assert_eq!(move_to(&mut world, 2), (vec![TileType::StairDown], vec![]));
+        assert_eq!(move_to(&mut world, 2), (vec![], vec![]), "standing still sends nothing more");
+
+        assert_eq!(move_to(&mut world, 3), (vec![TileType::StairUp], vec![TileType::StairDown]));
+        assert_eq!(move_to(&mut world, 4), (vec![], vec![TileType::StairUp]));
+    }
 }
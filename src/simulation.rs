@@ -0,0 +1,107 @@
+// Headless simulation - map generation and autoexplore without a Bevy App
+use crate::biome::BiomeType;
+use crate::components::{EllipseMask, ExploreStrategy, FovConfig, StairMode, VisitTrail};
+use crate::constants::MAX_DEPTH;
+use crate::fov::visible_tiles_from;
+use crate::map::GameMap;
+use crate::player::{find_nearest_frontier_from, find_nearest_unexplored_from, find_path};
+
+/// Outcome of a headless autoexplore run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExploreStats {
+    pub steps: usize,          // Tiles walked
+    pub targets: usize,        // Exploration targets picked
+    pub explored_floor: usize, // Floor tiles seen by the end of the run
+    pub total_floor: usize,    // Floor tiles on the map
+    pub completed: bool,       // True if exploration ran out of targets rather than hitting the step cap
+}
+
+/// Generate a level the same way the game does, from a fixed seed
+pub fn generate_level(biome: BiomeType, level: u32, seed: u64) -> GameMap {
+    let ellipse_mask = EllipseMask::new(80, 50);
+
+    let mut map = GameMap::new(80, 50);
+    map.generate_level_validated(biome, level, MAX_DEPTH, seed, StairMode::BothWays, &ellipse_mask);
+    map
+}
+
+/// Run the autoexplore loop (pick target -> path -> step -> FOV) from `start` until nothing is left to explore
+pub fn simulate_autoexplore(map: &GameMap, biome: BiomeType, strategy: ExploreStrategy, start: (u32, u32)) -> ExploreStats {
+    let fov_config = FovConfig::default();
+    let idx = |x: u32, y: u32| (y * map.width + x) as usize;
+    let reveal = |seen: &mut [bool], pos: (u32, u32)| {
+        for (x, y) in visible_tiles_from(map, pos, &fov_config) {
+            seen[idx(x, y)] = true;
+        }
+    };
+
+    let mut stats = ExploreStats::default();
+    let mut seen = vec![false; map.tiles.len()];
+    let mut pos = start;
+    let mut trail = VisitTrail::default();
+    reveal(&mut seen, pos);
+
+    // Safety cap so a generator bug can't hang a fuzzing run
+    let max_steps = map.tiles.len() * 8;
+
+    while stats.steps < max_steps {
+        let is_unseen = |x, y| !seen[idx(x, y)];
+        let recency = |x, y| trail.recency((x, y));
+        let next_target = match strategy {
+            ExploreStrategy::NearestUnseen => find_nearest_unexplored_from(pos, map, biome, is_unseen, recency),
+            ExploreStrategy::NearestFrontier => find_nearest_frontier_from(pos, map, biome, is_unseen, recency),
+        };
+        let Some(target) = next_target else {
+            stats.completed = true;
+            break;
+        };
+
+        let path = find_path(pos, target, map, biome);
+        if path.is_empty() {
+            break;
+        }
+        stats.targets += 1;
+
+        for step in path {
+            pos = step;
+            stats.steps += 1;
+            trail.visit(pos);
+            reveal(&mut seen, pos);
+        }
+    }
+
+    for (x, y) in map.iter_floor() {
+        stats.total_floor += 1;
+        if seen[idx(x, y)] {
+            stats.explored_floor += 1;
+        }
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontier_strategy_is_never_much_worse_than_nearest_unseen() {
+        // On any one map either strategy can get lucky, so each seed only gets a bound on how much
+        // further frontier walks; over all of them it has to come out ahead
+        let (mut unseen_steps, mut frontier_steps) = (0, 0);
+        for seed in 0..8 {
+            let map = generate_level(BiomeType::Caverns, 1, seed);
+            let start = map.floor_nearest_center();
+
+            let unseen = simulate_autoexplore(&map, BiomeType::Caverns, ExploreStrategy::NearestUnseen, start);
+            let frontier = simulate_autoexplore(&map, BiomeType::Caverns, ExploreStrategy::NearestFrontier, start);
+
+            assert!(unseen.completed && frontier.completed, "seed {}", seed);
+            assert_eq!(frontier.explored_floor, unseen.explored_floor, "seed {}", seed);
+            assert!(frontier.steps * 4 <= unseen.steps * 5, "seed {}: frontier took {} steps, nearest-unseen {}", seed, frontier.steps, unseen.steps);
+            unseen_steps += unseen.steps;
+            frontier_steps += frontier.steps;
+        }
+        assert!(frontier_steps <= unseen_steps, "frontier took {} steps, nearest-unseen {}", frontier_steps, unseen_steps);
+    }
+}
@@ -3,6 +3,10 @@ use bevy::prelude::*;
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum GameState {
     #[default]
+    MainMenu,
     AssetLoading,
     Playing,
+    LevelTransition, // Fading out, swapping the tilemap, then fading back in
+    Paused,     // Window lost focus - gameplay frozen until refocus
+    Victory,    // Reached the altar on the bottom level
 }
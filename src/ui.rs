@@ -1,21 +1,198 @@
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
 
 use crate::assets::GameAssets;
-use crate::components::{CurrentLevel, DepthIndicator};
+use crate::components::{Player, ExploredTiles, RememberedFeatures, TileType, AccessibilitySettings, AutoexploreSettings, ExamineMode, ExaminePanel, ExploreStrategy, FovConfig, FovShape, FovState, ParticleSettings, MenuFocus, SettingsMenu, SettingsMenuRoot, SettingsMenuRow, CurrentLevel, DepthIndicator, StairPrompt, EllipseMask, GameCamera, GlobalRng, TrapRng, MainMenuRoot, MapTile, MenuButton, ReduceMotionLabel, StairMode, StairModeLabel, TileIndex, TileInspector, TileInspectorPanel, TileVisibility, TileVisibilityState, VictoryScreenRoot};
+use crate::input_handler::{KeyBindings, MenuAction, PlayerLeftStairs, PlayerOnStairs};
+use crate::map::GameMap;
 use crate::states::GameState;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Playing), 
-            spawn_depth_indicator
+        app.init_resource::<TileInspector>()
+        .init_resource::<AccessibilitySettings>()
+        .init_resource::<SettingsMenu>()
+        .add_systems(Startup, load_settings)
+        .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+        .add_systems(Update, handle_main_menu.run_if(in_state(GameState::MainMenu)))
+        .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
+        .add_systems(
+            OnTransition { exited: GameState::AssetLoading, entered: GameState::Playing },
+            (spawn_depth_indicator, spawn_stair_prompt, spawn_tile_inspector, spawn_examine_panel)
         )
         .add_systems(
             Update, 
-            update_depth_indicator.run_if(in_state(GameState::Playing))
-        );
+            (
+                update_depth_indicator,
+                update_stair_prompt,
+                toggle_tile_inspector,
+                update_tile_inspector.after(toggle_tile_inspector),
+                update_examine_panel,
+            ).run_if(in_state(GameState::Playing))
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_settings_menu,
+                navigate_settings_menu.after(toggle_settings_menu).run_if(in_state(GameState::Paused)),
+            ).run_if(in_state(GameState::Playing).or(in_state(GameState::Paused)))
+        )
+        .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
+        .add_systems(Update, handle_victory_screen.run_if(in_state(GameState::Victory)))
+        .add_systems(OnExit(GameState::Victory), despawn_run_ui);
+    }
+}
+
+// The menu runs before asset loading, so it sticks to Bevy's built-in font. There's no save/load
+// yet, so there's no Continue either
+pub fn spawn_main_menu(mut commands: Commands, accessibility: Res<AccessibilitySettings>, stair_mode: Res<StairMode>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            MainMenuRoot,
+            MenuFocus::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Deepdive"),
+                TextFont { font_size: 64.0, ..default() },
+                TextColor(Color::WHITE),
+            ));
+
+            for button in MAIN_MENU_BUTTONS {
+                let label = match button {
+                    MenuButton::NewGame => "New Game".to_string(),
+                    MenuButton::ReduceMotion => accessibility.reduce_motion_label(),
+                    MenuButton::Stairs => stair_mode.label(),
+                    MenuButton::BackToMenu => "Main Menu".to_string(),
+                    MenuButton::Quit => "Quit".to_string(),
+                };
+                parent.spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(240.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.2)),
+                    button,
+                )).with_children(|button_parent| {
+                    let mut text = button_parent.spawn((
+                        Text::new(label),
+                        TextFont { font_size: 28.0, ..default() },
+                        TextColor(Color::WHITE),
+                    ));
+                    if button == MenuButton::ReduceMotion {
+                        text.insert(ReduceMotionLabel);
+                    }
+                    if button == MenuButton::Stairs {
+                        text.insert(StairModeLabel);
+                    }
+                });
+            }
+        });
+}
+
+// Main menu buttons, top to bottom; MenuFocus on the main menu indexes into this
+const MAIN_MENU_BUTTONS: [MenuButton; 4] = [MenuButton::NewGame, MenuButton::ReduceMotion, MenuButton::Stairs, MenuButton::Quit];
+
+// Mouse or keyboard: the mouse moves focus by hovering, Up/Down move it with wrap-around, and
+// Confirm (or a click) activates the button
+pub fn handle_main_menu(
+    mut commands: Commands,
+    mut button_query: Query<(Ref<Interaction>, &MenuButton, &mut BackgroundColor)>,
+    mut focus_query: Query<&mut MenuFocus, With<MainMenuRoot>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit_events: EventWriter<AppExit>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut label_query: Query<&mut Text, (With<ReduceMotionLabel>, Without<StairModeLabel>)>,
+    mut stair_mode: ResMut<StairMode>,
+    mut stair_label_query: Query<&mut Text, With<StairModeLabel>>,
+) {
+    let Ok(mut focus) = focus_query.single_mut() else { return; };
+    let focusable = [true; MAIN_MENU_BUTTONS.len()];
+
+    let mut selected = None;
+    match key_bindings.menu_action(&keyboard_input) {
+        Some(MenuAction::Up) => focus.step(-1, &focusable),
+        Some(MenuAction::Down) => focus.step(1, &focusable),
+        Some(MenuAction::Confirm) => selected = MAIN_MENU_BUTTONS.get(focus.index).copied(),
+        _ => {}
+    }
+
+    for (interaction, button, _) in button_query.iter() {
+        if !interaction.is_changed() {
+            continue;
+        }
+        match *interaction {
+            Interaction::Pressed => selected = Some(*button),
+            Interaction::Hovered => {
+                if let Some(index) = MAIN_MENU_BUTTONS.iter().position(|row| row == button) {
+                    focus.index = index;
+                }
+            }
+            Interaction::None => {}
+        }
+    }
+
+    // The focused button is highlighted the same way whether the mouse or the keyboard put it there
+    for (_, button, mut background) in button_query.iter_mut() {
+        let focused = MAIN_MENU_BUTTONS.get(focus.index) == Some(button);
+        let color = if focused { Color::srgb(0.25, 0.25, 0.35) } else { Color::srgb(0.15, 0.15, 0.2) };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+
+    match selected {
+        Some(MenuButton::NewGame) => {
+            // Fresh run: new RNG stream; end_run already dropped the last run's levels
+            commands.insert_resource(GlobalRng::new());
+            commands.insert_resource(TrapRng::default());
+            next_state.set(GameState::AssetLoading);
+        }
+        Some(MenuButton::ReduceMotion) => {
+            accessibility.reduce_motion = !accessibility.reduce_motion;
+            for mut text in label_query.iter_mut() {
+                text.0 = accessibility.reduce_motion_label();
+            }
+            println!("Reduce motion: {}", if accessibility.reduce_motion { "ON" } else { "OFF" });
+        }
+        Some(MenuButton::Stairs) => {
+            *stair_mode = if stair_mode.allows_ascent() { StairMode::DescendOnly } else { StairMode::BothWays };
+            for mut text in stair_label_query.iter_mut() {
+                text.0 = stair_mode.label();
+            }
+            println!("Stair mode: {:?}", *stair_mode);
+        }
+        Some(MenuButton::Quit) => {
+            exit_events.write(AppExit::Success);
+        }
+        Some(MenuButton::BackToMenu) | None => {}
+    }
+}
+
+pub fn despawn_main_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<MainMenuRoot>>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn();
     }
 }
 
@@ -44,13 +221,757 @@ pub fn spawn_depth_indicator(
         });
 }
 
+// Depth and how much of the level has been explored, and once the player has found the way down
+// (or the altar), how many steps off it is
 pub fn update_depth_indicator(
     current_level: Res<CurrentLevel>,
+    map: Option<Res<GameMap>>,
+    remembered: Res<RememberedFeatures>,
+    explored: Res<ExploredTiles>,
+    player_query: Query<Ref<Player>>,
     mut text_query: Query<&mut Text, With<DepthIndicator>>,
 ) {
-    if current_level.is_changed() {
-        for mut text in text_query.iter_mut() {
-            text.0 = format!("Depth {}", current_level.level);
+    let player = player_query.single().ok();
+    let moved = player.as_ref().is_some_and(|player| player.is_changed());
+    if !current_level.is_changed() && !remembered.is_changed() && !explored.is_changed() && !moved {
+        return;
+    }
+
+    let mut report = format!("Depth {}", current_level.level);
+    if let Some(map) = map.as_ref().filter(|_| !explored.is_empty()) {
+        let percent = (explored.len() * 100 / map.viewable_tile_count().max(1)).min(100);
+        report.push_str(&format!(" - {}% explored", percent));
+    }
+    let goal = remembered.positions_of(TileType::StairDown).next().map(|pos| ("Stairs", pos))
+        .or_else(|| remembered.positions_of(TileType::Altar).next().map(|pos| ("Altar", pos)));
+    if let (Some(map), Some(player), Some((name, pos))) = (map, player, goal) {
+        if let Some(steps) = map.bfs_distance((player.x, player.y), pos, current_level.biome) {
+            report.push_str(&format!("\n{} {} {} away", name, steps, if steps == 1 { "tile" } else { "tiles" }));
+        }
+    }
+
+    for mut text in text_query.iter_mut() {
+        if text.0 != report {
+            text.0 = report.clone();
+        }
+    }
+}
+
+pub fn spawn_stair_prompt(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: assets.akkurat_font.clone(),
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        StairPrompt,
+    ));
+}
+
+// "Press D to descend" while the player stands on stairs, using the current key bindings
+pub fn update_stair_prompt(
+    mut on_stairs_events: EventReader<PlayerOnStairs>,
+    mut left_stairs_events: EventReader<PlayerLeftStairs>,
+    key_bindings: Res<KeyBindings>,
+    stair_mode: Res<StairMode>,
+    mut showing: Local<Option<TileType>>,
+    mut prompt_query: Query<(&mut Text, &mut Visibility), With<StairPrompt>>,
+) {
+    let before = *showing;
+    for event in left_stairs_events.read() {
+        if *showing == Some(event.stair_type) {
+            *showing = None;
+        }
+    }
+    if let Some(event) = on_stairs_events.read().last() {
+        *showing = Some(event.stair_type);
+    }
+    if *showing == before {
+        return;
+    }
+
+    let prompt = match *showing {
+        Some(TileType::StairUp) if !stair_mode.allows_ascent() => "There's no way back up".to_string(),
+        Some(stair_type) => format!("Press {} to {}",
+            key_bindings.stair_key_label(stair_type),
+            if stair_type == TileType::StairUp { "ascend" } else { "descend" }
+        ),
+        None => String::new(),
+    };
+    for (mut text, mut visibility) in prompt_query.iter_mut() {
+        text.0 = prompt.clone();
+        *visibility = if showing.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+pub fn spawn_tile_inspector(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: assets.akkurat_font.clone(),
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            top: Val::Px(20.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        TileInspectorPanel,
+    ));
+}
+
+pub fn spawn_examine_panel(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: assets.akkurat_font.clone(),
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        ExaminePanel,
+    ));
+}
+
+// Describe the tile under the examine cursor; the cursor never rests on Unseen tiles
+pub fn update_examine_panel(
+    examine: Res<ExamineMode>,
+    mut panel_query: Query<(&mut Text, &mut Visibility), With<ExaminePanel>>,
+    tile_query: Query<&TileVisibilityState>,
+    tile_index: Res<TileIndex>,
+    map: Option<Res<GameMap>>,
+) {
+    let Some(map) = map else { return; };
+    if !examine.is_changed() {
+        return;
+    }
+
+    let (x, y) = examine.cursor;
+    let remembered = tile_index.tiles.get(&(x, y))
+        .and_then(|&entity| tile_query.get(entity).ok())
+        .is_some_and(|state| state.visibility == TileVisibility::Seen);
+    let report = format!(
+        "({}, {}) {:?}{}\nArrows move, X or Esc to exit",
+        x, y, map.get(x, y).as_perceived(), if remembered { " (remembered)" } else { "" }
+    );
+
+    for (mut text, mut visibility) in panel_query.iter_mut() {
+        *visibility = if examine.active { Visibility::Inherited } else { Visibility::Hidden };
+        text.0 = report.clone();
+    }
+}
+
+// Debug: Shift+I shows the tile inspector panel
+pub fn toggle_tile_inspector(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut inspector: ResMut<TileInspector>,
+    mut panel_query: Query<&mut Visibility, With<TileInspectorPanel>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyI) &&
+       (keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight)) {
+        inspector.enabled = !inspector.enabled;
+        for mut visibility in panel_query.iter_mut() {
+            *visibility = if inspector.enabled { Visibility::Inherited } else { Visibility::Hidden };
+        }
+        println!("Tile inspector: {}", if inspector.enabled { "ON" } else { "OFF" });
+    }
+}
+
+pub fn update_tile_inspector(
+    inspector: Res<TileInspector>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut panel_query: Query<&mut Text, With<TileInspectorPanel>>,
+    tile_query: Query<(&MapTile, &TileVisibilityState)>,
+    tile_index: Res<TileIndex>,
+    ellipse_mask: Res<EllipseMask>,
+    map: Option<Res<GameMap>>,
+) {
+    let Some(map) = map else { return; };
+    if !inspector.enabled {
+        return;
+    }
+
+    // Cursor -> world -> grid; a cursor off the window counts as out of bounds
+    let hovered = match (window_query.single(), camera_query.single()) {
+        (Ok(window), Ok((camera, camera_transform))) => window.cursor_position()
+            .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+            .and_then(|world| map.world_to_grid(world)),
+        _ => None,
+    };
+
+    let report = match hovered {
+        Some((x, y)) => {
+            // Read the live tile entity so the panel shows what's actually drawn, not just the map data
+            let (tile_type, visibility) = match tile_index.tiles.get(&(x, y)).and_then(|&entity| tile_query.get(entity).ok()) {
+                Some((map_tile, visibility_state)) => (format!("{:?}", map_tile.tile_type), format!("{:?}", visibility_state.visibility)),
+                None => (format!("{:?}", map.get(x, y)), "no tile entity".to_string()),
+            };
+            let region = map.region_id(x, y).map_or("none".to_string(), |id| id.to_string());
+            format!(
+                "Tile ({}, {})\nType: {}\nVisibility: {}\nRegion: {}\nIn ellipse: {}",
+                x, y, tile_type, visibility, region, map.is_within_boundary(x, y, &ellipse_mask)
+            )
+        }
+        None => "Out of bounds".to_string(),
+    };
+
+    for mut text in panel_query.iter_mut() {
+        if text.0 != report {
+            text.0 = report.clone();
+        }
+    }
+}
+
+pub fn spawn_victory_screen(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            VictoryScreenRoot,
+            MenuFocus::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Victory"),
+                TextFont {
+                    font: assets.akkurat_font.clone(),
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.4)),
+            ));
+            parent.spawn((
+                Text::new("You reached the altar at the bottom of the world."),
+                TextFont {
+                    font: assets.akkurat_font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            for button in VICTORY_BUTTONS {
+                let label = if button == MenuButton::BackToMenu { "Main Menu" } else { "Quit" };
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(240.0),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.2)),
+                        button,
+                    ))
+                    .with_children(|button_parent| {
+                        button_parent.spawn((
+                            Text::new(label),
+                            TextFont {
+                                font: assets.akkurat_font.clone(),
+                                font_size: 28.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+// Victory screen buttons, top to bottom; MenuFocus on the victory screen indexes into this
+const VICTORY_BUTTONS: [MenuButton; 2] = [MenuButton::BackToMenu, MenuButton::Quit];
+
+// Same controls as the main menu, plus Back as a shortcut to Main Menu
+pub fn handle_victory_screen(
+    mut button_query: Query<(Ref<Interaction>, &MenuButton, &mut BackgroundColor)>,
+    mut focus_query: Query<&mut MenuFocus, With<VictoryScreenRoot>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    let Ok(mut focus) = focus_query.single_mut() else { return; };
+    let focusable = [true; VICTORY_BUTTONS.len()];
+
+    let mut selected = None;
+    match key_bindings.menu_action(&keyboard_input) {
+        Some(MenuAction::Up) => focus.step(-1, &focusable),
+        Some(MenuAction::Down) => focus.step(1, &focusable),
+        Some(MenuAction::Confirm) => selected = VICTORY_BUTTONS.get(focus.index).copied(),
+        Some(MenuAction::Back) => selected = Some(MenuButton::BackToMenu),
+        _ => {}
+    }
+
+    for (interaction, button, _) in button_query.iter() {
+        if !interaction.is_changed() {
+            continue;
+        }
+        match *interaction {
+            Interaction::Pressed => selected = Some(*button),
+            Interaction::Hovered => {
+                if let Some(index) = VICTORY_BUTTONS.iter().position(|row| row == button) {
+                    focus.index = index;
+                }
+            }
+            Interaction::None => {}
+        }
+    }
+
+    for (_, button, mut background) in button_query.iter_mut() {
+        let focused = VICTORY_BUTTONS.get(focus.index) == Some(button);
+        let color = if focused { Color::srgb(0.25, 0.25, 0.35) } else { Color::srgb(0.15, 0.15, 0.2) };
+        if background.0 != color {
+            background.0 = color;
         }
     }
+
+    match selected {
+        Some(MenuButton::BackToMenu) => {
+            next_state.set(GameState::MainMenu);
+        }
+        Some(MenuButton::Quit) => {
+            exit_events.write(AppExit::Success);
+        }
+        _ => {}
+    }
+}
+
+// The run's HUD and the victory screen go when the victory screen closes; level_manager::end_run
+// clears the world underneath
+pub fn despawn_run_ui(
+    mut commands: Commands,
+    root_query: Query<Entity, Or<(With<VictoryScreenRoot>, With<TileInspectorPanel>, With<ExaminePanel>, With<StairPrompt>)>>,
+    depth_query: Query<&ChildOf, With<DepthIndicator>>,
+) {
+    for entity in root_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    // The depth text sits inside an unmarked positioning node
+    for child_of in depth_query.iter() {
+        commands.entity(child_of.parent()).despawn();
+    }
+}
+
+// Settings chosen in the overlay, written on close and applied at startup
+const SETTINGS_PATH: &str = "settings.ron";
+
+const SETTINGS_ITEMS: [&str; 8] = ["FOV radius", "FOV shape", "Particle density", "Reduce motion", "High contrast", "Careful movement", "Autoexplore speed", "Explore strategy"];
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    fov_radius: u32,
+    #[serde(default)] // Added later; older files keep the circle
+    fov_shape: FovShape,
+    particle_density: f32,
+    reduce_motion: bool,
+    #[serde(default)]
+    high_contrast: bool,
+    #[serde(default)]
+    careful_movement: bool,
+    autoexplore_speed: f32,
+    explore_strategy: ExploreStrategy,
+}
+
+pub fn load_settings(
+    mut fov_config: ResMut<FovConfig>,
+    mut fov_state: ResMut<FovState>,
+    mut particle_settings: ResMut<ParticleSettings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut autoexplore: ResMut<AutoexploreSettings>,
+) {
+    // No file yet is the normal first-run case; anything else falls back to defaults with a log line
+    let contents = match std::fs::read_to_string(SETTINGS_PATH) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            println!("Failed to read {}: {} - using default settings", SETTINGS_PATH, e);
+            return;
+        }
+    };
+    let saved: SavedSettings = match ron::from_str(&contents) {
+        Ok(saved) => saved,
+        Err(e) => {
+            println!("Failed to parse {}: {} - using default settings", SETTINGS_PATH, e);
+            return;
+        }
+    };
+
+    fov_config.radius = saved.fov_radius.clamp(fov_config.min_radius, 40);
+    fov_config.shape = saved.fov_shape;
+    fov_state.needs_recalculation = true;
+    particle_settings.density_multiplier = saved.particle_density.clamp(0.25, 6.0);
+    accessibility.reduce_motion = saved.reduce_motion;
+    accessibility.high_contrast = saved.high_contrast;
+    accessibility.careful_movement = saved.careful_movement;
+    autoexplore.speed_multiplier = saved.autoexplore_speed.clamp(AutoexploreSettings::MIN_SPEED, AutoexploreSettings::MAX_SPEED);
+    autoexplore.strategy = saved.explore_strategy;
+    println!("Loaded settings from {}", SETTINGS_PATH);
+}
+
+fn save_settings(saved: &SavedSettings) {
+    let result = ron::ser::to_string_pretty(saved, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|contents| std::fs::write(SETTINGS_PATH, contents).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => println!("Settings saved to {}", SETTINGS_PATH),
+        Err(e) => println!("Failed to save settings to {}: {}", SETTINGS_PATH, e),
+    }
+}
+
+// F10 opens the settings overlay and pauses; F10 or Escape closes it, saves and resumes
+pub fn toggle_settings_menu(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    key_bindings: Res<KeyBindings>,
+    mut menu: ResMut<SettingsMenu>,
+    root_query: Query<Entity, With<SettingsMenuRoot>>,
+    assets: Res<GameAssets>,
+    fov_config: Res<FovConfig>,
+    particle_settings: Res<ParticleSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    autoexplore: Res<AutoexploreSettings>,
+) {
+    let toggled = keyboard_input.just_pressed(KeyCode::F10)
+        || (menu.open && key_bindings.menu_action(&keyboard_input) == Some(MenuAction::Back));
+    if !toggled {
+        return;
+    }
+
+    if menu.open {
+        for entity in root_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        menu.open = false;
+        save_settings(&SavedSettings {
+            fov_radius: fov_config.radius,
+            fov_shape: fov_config.shape,
+            particle_density: particle_settings.density_multiplier,
+            reduce_motion: accessibility.reduce_motion,
+            high_contrast: accessibility.high_contrast,
+            careful_movement: accessibility.careful_movement,
+            autoexplore_speed: autoexplore.speed_multiplier,
+            explore_strategy: autoexplore.strategy,
+        });
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    // Only open from live gameplay, not on top of a focus pause
+    if *state.get() != GameState::Playing {
+        return;
+    }
+
+    menu.open = true;
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            GlobalZIndex(10),
+            SettingsMenuRoot,
+            MenuFocus::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                TextFont {
+                    font: assets.akkurat_font.clone(),
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            // Row text is filled in by navigate_settings_menu
+            for index in 0..SETTINGS_ITEMS.len() {
+                parent.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font: assets.akkurat_font.clone(),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    SettingsMenuRow(index),
+                ));
+            }
+            parent.spawn((
+                Text::new("Up/Down select, Left/Right or Enter change, F10 or Esc to close"),
+                TextFont {
+                    font: assets.akkurat_font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            ));
+        });
+    next_state.set(GameState::Paused);
+    println!("Settings opened - game paused");
+}
+
+// Up/Down move the focused row (wrapping), Left/Right or Confirm change its value live
+pub fn navigate_settings_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    menu: Res<SettingsMenu>,
+    mut focus_query: Query<&mut MenuFocus, With<SettingsMenuRoot>>,
+    mut row_query: Query<(&SettingsMenuRow, &mut Text, &mut TextColor)>,
+    mut fov_config: ResMut<FovConfig>,
+    mut fov_state: ResMut<FovState>,
+    mut particle_settings: ResMut<ParticleSettings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut autoexplore: ResMut<AutoexploreSettings>,
+) {
+    if !menu.open {
+        return;
+    }
+    let Ok(mut focus) = focus_query.single_mut() else { return; };
+
+    let focusable = [true; SETTINGS_ITEMS.len()];
+    let step = match key_bindings.menu_action(&keyboard_input) {
+        Some(MenuAction::Up) => {
+            focus.step(-1, &focusable);
+            0
+        }
+        Some(MenuAction::Down) => {
+            focus.step(1, &focusable);
+            0
+        }
+        Some(MenuAction::Left) => -1,
+        Some(MenuAction::Right) | Some(MenuAction::Confirm) => 1,
+        _ => 0,
+    };
+    let changed = step != 0;
+    if changed {
+        match focus.index {
+            0 => {
+                fov_config.radius = fov_config.radius.saturating_add_signed(step).clamp(fov_config.min_radius, 40);
+                fov_state.needs_recalculation = true;
+            }
+            1 => {
+                const SHAPES: [FovShape; 3] = [FovShape::Circle, FovShape::Square, FovShape::Diamond];
+                let current = SHAPES.iter().position(|&shape| shape == fov_config.shape).unwrap_or(0);
+                fov_config.shape = SHAPES[(current as i32 + step).rem_euclid(SHAPES.len() as i32) as usize];
+                fov_state.needs_recalculation = true;
+            }
+            2 => particle_settings.density_multiplier = (particle_settings.density_multiplier + 0.25 * step as f32).clamp(0.25, 6.0),
+            3 => accessibility.reduce_motion = !accessibility.reduce_motion,
+            4 => accessibility.high_contrast = !accessibility.high_contrast,
+            5 => accessibility.careful_movement = !accessibility.careful_movement,
+            6 => {
+                let speed = if step > 0 { autoexplore.speed_multiplier * 2.0 } else { autoexplore.speed_multiplier / 2.0 };
+                autoexplore.speed_multiplier = speed.clamp(AutoexploreSettings::MIN_SPEED, AutoexploreSettings::MAX_SPEED);
+            }
+            _ => {
+                autoexplore.strategy = match autoexplore.strategy {
+                    ExploreStrategy::NearestUnseen => ExploreStrategy::NearestFrontier,
+                    ExploreStrategy::NearestFrontier => ExploreStrategy::NearestUnseen,
+                };
+            }
+        }
+    }
+
+    if !changed && !menu.is_changed() && !focus.is_changed() {
+        return;
+    }
+
+    for (row, mut text, mut color) in row_query.iter_mut() {
+        let value = match row.0 {
+            0 => fov_config.radius.to_string(),
+            1 => format!("{:?}", fov_config.shape),
+            2 => format!("{:.2}x", particle_settings.density_multiplier),
+            3 => if accessibility.reduce_motion { "On".to_string() } else { "Off".to_string() },
+            4 => if accessibility.high_contrast { "On".to_string() } else { "Off".to_string() },
+            5 => if accessibility.careful_movement { "On".to_string() } else { "Off".to_string() },
+            6 => format!("{}x", autoexplore.speed_multiplier),
+            _ => format!("{:?}", autoexplore.strategy),
+        };
+        let selected = row.0 == focus.index;
+        text.0 = format!("{} {}: {}", if selected { ">" } else { " " }, SETTINGS_ITEMS[row.0], value);
+        color.0 = if selected { Color::srgb(1.0, 0.85, 0.4) } else { Color::WHITE };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biome::BiomeType;
+    use crate::input_handler::{PlayerLeftStairs, PlayerOnStairs};
+
+    // A victory screen with its two buttons, driven through the shared menu keys
+    fn victory_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<NextState<GameState>>();
+        world.init_resource::<Events<AppExit>>();
+        world.spawn((VictoryScreenRoot, MenuFocus::default()));
+        for button in VICTORY_BUTTONS {
+            world.spawn((Interaction::None, button, BackgroundColor(Color::srgb(0.15, 0.15, 0.2))));
+        }
+        world
+    }
+
+    fn press(world: &mut World, key: KeyCode) {
+        let mut input = world.resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        input.press(key);
+    }
+
+    #[test]
+    fn the_depth_readout_counts_steps_to_discovered_stairs_and_exploration() {
+        let mut map = GameMap::new(12, 5);
+        map.fill_rect(URect::new(1, 1, 11, 4), TileType::Floor);
+        map.fill_rect(URect::new(5, 1, 6, 3), TileType::Wall); // Forces a detour through row 3
+        map.set(9, 1, TileType::StairDown);
+
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.insert_resource(CurrentLevel { level: 4, biome: BiomeType::Caverns });
+        world.init_resource::<RememberedFeatures>();
+        world.init_resource::<ExploredTiles>();
+        let player = world.spawn(Player { x: 1, y: 1 }).id();
+        let text = world.spawn((Text::new("Depth 0"), DepthIndicator)).id();
+        let readout = |world: &mut World| {
+            world.run_system_cached(update_depth_indicator).unwrap();
+            world.get::<Text>(text).unwrap().0.clone()
+        };
+
+        assert_eq!(readout(&mut world), "Depth 4", "undiscovered stairs aren't given away");
+        world.resource_mut::<RememberedFeatures>().features.insert((9, 1), TileType::StairDown);
+        assert_eq!(readout(&mut world), "Depth 4\nStairs 12 tiles away");
+        world.get_mut::<Player>(player).unwrap().x = 8;
+        assert_eq!(readout(&mut world), "Depth 4\nStairs 1 tile away");
+
+        // Every tile of this map touches floor, so a quarter of its 60 tiles is a quarter explored
+        world.resource_mut::<ExploredTiles>().0.extend((0..15).map(|x| (x % 12, x / 12)));
+        assert_eq!(readout(&mut world), "Depth 4 - 25% explored\nStairs 1 tile away");
+    }
+
+    #[test]
+    fn focus_wraps_and_skips_rows_that_cannot_take_it() {
+        let mut focus = MenuFocus::default();
+        let focusable = [true, false, true, true];
+        focus.step(1, &focusable);
+        assert_eq!(focus.index, 2);
+        focus.step(1, &focusable);
+        focus.step(1, &focusable);
+        assert_eq!(focus.index, 0);
+        focus.step(-1, &focusable);
+        assert_eq!(focus.index, 3);
+
+        focus.step(1, &[false; 4]);
+        assert_eq!(focus.index, 3);
+    }
+
+    #[test]
+    fn victory_screen_keys_move_focus_and_confirm_the_focused_button() {
+        let mut world = victory_world();
+        let bindings = KeyBindings::default();
+        let system = world.register_system(handle_victory_screen);
+        let focus = |world: &mut World| world.query::<&MenuFocus>().single(world).unwrap().index;
+
+        press(&mut world, bindings.menu_down[0]);
+        world.run_system(system).unwrap();
+        assert_eq!(focus(&mut world), 1);
+        press(&mut world, bindings.menu_down[0]);
+        world.run_system(system).unwrap();
+        assert_eq!(focus(&mut world), 0, "focus wraps past the last button");
+        assert!(matches!(*world.resource::<NextState<GameState>>(), NextState::Unchanged));
+
+        // The focused button is the one lit up
+        let colors: Vec<_> = world.query::<(&MenuButton, &BackgroundColor)>().iter(&world)
+            .map(|(button, background)| (*button, background.0))
+            .collect();
+        assert!(colors.contains(&(MenuButton::BackToMenu, Color::srgb(0.25, 0.25, 0.35))));
+        assert!(colors.contains(&(MenuButton::Quit, Color::srgb(0.15, 0.15, 0.2))));
+
+        press(&mut world, bindings.menu_confirm[0]);
+        world.run_system(system).unwrap();
+        assert!(matches!(*world.resource::<NextState<GameState>>(), NextState::Pending(GameState::MainMenu)));
+        assert!(world.resource::<Events<AppExit>>().is_empty());
+    }
+
+    #[test]
+    fn the_stair_prompt_names_the_bound_key_while_on_stairs() {
+        let mut world = World::new();
+        world.init_resource::<KeyBindings>();
+        world.insert_resource(StairMode::BothWays);
+        world.init_resource::<Events<PlayerOnStairs>>();
+        world.init_resource::<Events<PlayerLeftStairs>>();
+        let prompt = world.spawn((Text::new(""), Visibility::Hidden, StairPrompt)).id();
+        let shown = |world: &mut World| {
+            world.run_system_cached(update_stair_prompt).unwrap();
+            let visible = world.get::<Visibility>(prompt) != Some(&Visibility::Hidden);
+            visible.then(|| world.get::<Text>(prompt).unwrap().0.clone())
+        };
+
+        assert_eq!(shown(&mut world), None);
+        world.send_event(PlayerOnStairs { stair_type: TileType::StairDown });
+        assert_eq!(shown(&mut world).as_deref(), Some("Press D to descend"));
+        world.send_event(PlayerLeftStairs { stair_type: TileType::StairDown });
+        assert_eq!(shown(&mut world), None);
+
+        world.insert_resource(StairMode::DescendOnly);
+        world.send_event(PlayerOnStairs { stair_type: TileType::StairUp });
+        assert_eq!(shown(&mut world).as_deref(), Some("There's no way back up"));
+    }
 }
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::assets::SpriteDatabase;
+use crate::components::{CurrentLevel, MapTile, TileType, TileVisibility, TileVisibilityState};
+use crate::states::GameState;
+
+// Shared shimmer clock driving every water tile at once
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct WaterAnimation {
+    pub timer: Timer,
+    pub frame: usize,
+}
+
+impl Default for WaterAnimation {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.4, TimerMode::Repeating),
+            frame: 0,
+        }
+    }
+}
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaterAnimation>()
+            .add_systems(Update, animate_water_tiles.run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Cycle discovered water tiles through the biome's water sprites
+pub fn animate_water_tiles(
+    time: Res<Time>,
+    current_level: Res<CurrentLevel>,
+    mut animation: ResMut<WaterAnimation>,
+    sprite_db: Res<SpriteDatabase>,
+    mut tile_query: Query<(&TilePos, &MapTile, &TileVisibilityState, &mut TileTextureIndex)>,
+) {
+    // Biomes with fewer than two water sprites have nothing to animate
+    let biome_config = current_level.biome.get_config();
+    let frames = sprite_db.biome_category(biome_config.name, "water").unwrap_or(&biome_config.allowed_water_assets);
+    if frames.len() < 2 {
+        return;
+    }
+
+    animation.timer.tick(time.delta());
+    if !animation.timer.just_finished() {
+        return;
+    }
+    animation.frame = animation.frame.wrapping_add(1);
+
+    for (tile_pos, map_tile, visibility_state, mut texture_index) in tile_query.iter_mut() {
+        if map_tile.tile_type != TileType::Water || visibility_state.visibility == TileVisibility::Unseen {
+            continue;
+        }
+
+        // Offset the frame by position so pools ripple instead of blinking in unison
+        let frame = (animation.frame + (tile_pos.x + tile_pos.y) as usize) % frames.len();
+        let (sprite_x, sprite_y) = frames[frame];
+        texture_index.0 = sprite_db.position_to_index(sprite_x, sprite_y);
+    }
+}